@@ -3,21 +3,6 @@ use std::time::Duration;
 use airsim_client::{DrivetrainType, MultiRotorClient, NetworkResult, Position3, YawMode};
 use async_std::task;
 
-use futures::future::FutureExt;
-
-async fn cancel_ongoing_async() -> NetworkResult<()> {
-    let address = "127.0.0.1:41451";
-    let vehicle_name = "";
-
-    // connect
-    log::info!("connect for cancelling");
-    let client = MultiRotorClient::connect(address, vehicle_name).await?;
-    task::sleep(Duration::from_secs(5)).await;
-
-    let res = client.cancel_last_task().await?;
-    log::info!("cancelled successfully: {res:?}");
-    Ok(())
-}
 async fn connect_drone() -> NetworkResult<()> {
     let address = "127.0.0.1:41451";
     let vehicle_name = "";
@@ -38,26 +23,25 @@ async fn connect_drone() -> NetworkResult<()> {
     let res = client.arm_disarm(true).await?;
     log::info!("Response: {res:?}");
 
-    // Start the canceller at the background
-    task::spawn(cancel_ongoing_async());
-
     // take off
     log::info!("take off drone");
-    let _t1 = client.take_off_async(20.0).fuse().await?;
-    let _t2 = task::sleep(Duration::from_secs(10)).fuse();
+    client.take_off_async(20.0).await?;
 
+    // start moving and cancel the move after 5 seconds
     log::info!("move to position");
-    client
-        .move_to_position_async(
-            Position3::new(-10.0, 10.0, -100.0),
-            3.0,
-            1000.0,
-            DrivetrainType::ForwardOnly,
-            YawMode::new(false, 90.0),
-            None,
-            None,
-        )
-        .await?;
+    let move_to_position = client.move_to_position_async(
+        Position3::new(-10.0, 10.0, -100.0),
+        3.0,
+        1000.0,
+        DrivetrainType::ForwardOnly,
+        YawMode::new(false, 90.0),
+        None,
+        None,
+    );
+    task::sleep(Duration::from_secs(5)).await;
+
+    let res = move_to_position.cancel().await?;
+    log::info!("cancelled successfully: {res:?}");
 
     // land drone
     log::info!("land drone");
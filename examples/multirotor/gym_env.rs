@@ -0,0 +1,53 @@
+use airsim_client::{
+    terminate_on_collision_or_landed, ActionMode, DroneEnv, Env, MultiRotorClient, NetworkResult, SteppedSim,
+};
+use async_std::task;
+
+async fn run_episodes() -> NetworkResult<()> {
+    let address = "127.0.0.1:41451";
+    let vehicle_name = "";
+
+    // connect
+    log::info!("connect");
+    let client = MultiRotorClient::connect(address, vehicle_name).await?;
+    let sim = SteppedSim::new(client.airsim_client()).await?;
+
+    // reward the drone for climbing towards z = -10 (NED)
+    let mut env = DroneEnv::new(&client, ActionMode::Velocity { max_velocity: 3.0 })
+        .with_stepped_sim(sim)
+        .with_step_duration(0.05)
+        .with_max_steps(200)
+        .with_termination(terminate_on_collision_or_landed())
+        .with_reward(Box::new(|obs, _| {
+            -(obs.state.kinematics_estimated.position.z + 10.0).abs()
+        }));
+
+    log::info!("action space: {:?}", env.action_space());
+    log::info!("observation space: {:?}", env.observation_space());
+
+    for episode in 0..3 {
+        env.reset().await?;
+        let mut total_reward = 0.0;
+
+        loop {
+            // a trivial policy: climb
+            let action = ActionMode::Velocity { max_velocity: 3.0 }
+                .action(&[0.0, 0.0, -1.0])
+                .unwrap();
+            let step = env.step(action).await?;
+            total_reward += step.reward;
+            if step.done() {
+                break;
+            }
+        }
+        log::info!("episode {episode}: {} steps, reward {total_reward}", env.steps());
+    }
+
+    log::info!("Done!");
+    Ok(())
+}
+
+fn main() -> NetworkResult<()> {
+    env_logger::init();
+    task::block_on(run_episodes())
+}
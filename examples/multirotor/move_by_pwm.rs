@@ -25,13 +25,13 @@ async fn connect_drone() -> NetworkResult<()> {
 
     log::info!("move by manual pwm");
     client
-        .move_by_motor_pwms_async(PWM::new(0.6, 0.6, 0.6, 0.6), 6.0)
+        .move_by_motor_pwms_async(PWM::new(0.6, 0.6, 0.6, 0.6)?, 6.0)?
         .await?;
     log::info!("done with pwm");
 
     log::info!("move by pwm again");
     client
-        .move_by_motor_pwms_async(PWM::new(0.6, 0.605, 0.6, 0.605), 1.0)
+        .move_by_motor_pwms_async(PWM::new(0.6, 0.605, 0.6, 0.605)?, 1.0)?
         .await?;
     log::info!("done with pwm");
 
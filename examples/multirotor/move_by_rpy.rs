@@ -1,4 +1,5 @@
-use airsim_client::{MultiRotorClient, NetworkResult, Orientation2, Orientation3};
+use airsim_client::{MultiRotorClient, NetworkResult, Orientation2, Orientation3, UnitInterval};
+use std::convert::TryFrom;
 use std::sync::Arc;
 // use async_std::task;
 
@@ -55,14 +56,14 @@ async fn connect_drone() -> NetworkResult<()> {
 
     log::info!("turn negative -180 with throttle");
     client_clone
-        .move_by_roll_pitch_yaw_throttle_async(Orientation3::new(0.0, 0.0, -1.57), 0.7, 3.0)
+        .move_by_roll_pitch_yaw_throttle_async(Orientation3::new(0.0, 0.0, -1.57), UnitInterval::try_from(0.7)?, 3.0)
         .await
         .unwrap();
     log::info!("done!");
 
     log::info!("turn with yawrate and Z throttle");
     let s = client_clone
-        .move_by_roll_pitch_yawrate_throttle_async(Orientation2::new(0.0, 0.0), 6.0, 0.45, 2.0)
+        .move_by_roll_pitch_yawrate_throttle_async(Orientation2::new(0.0, 0.0), 6.0, UnitInterval::try_from(0.45)?, 2.0)
         .await
         .unwrap();
     log::info!("done! {s:?}");
@@ -76,7 +77,7 @@ async fn connect_drone() -> NetworkResult<()> {
 
     log::info!("move by angle rates and throttle");
     let s = client_clone
-        .move_by_angle_rates_throttle_async(Orientation3::new(0.2, 0.0, 0.0), 0.65, 3.0)
+        .move_by_angle_rates_throttle_async(Orientation3::new(0.2, 0.0, 0.0), UnitInterval::try_from(0.65)?, 3.0)
         .await
         .unwrap();
     log::info!("done! {s:?}");
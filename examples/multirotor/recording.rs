@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use airsim_client::{MultiRotorClient, NetworkResult, Recording};
+use async_std::task;
+
+async fn record_drone() -> NetworkResult<()> {
+    let address = "127.0.0.1:41451";
+    let vehicle_name = "";
+
+    // connect
+    log::info!("connect");
+    let client = MultiRotorClient::connect(address, vehicle_name).await?;
+
+    // record for a few seconds
+    log::info!("start recording");
+    client.start_recording().await?;
+    log::info!("is recording: {:?}", client.is_recording().await?);
+    task::sleep(Duration::from_secs(3)).await;
+    client.stop_recording().await?;
+    log::info!("stopped recording");
+
+    // parse a recording folder given as first argument
+    if let Some(dir) = std::env::args().nth(1) {
+        let recording = Recording::from_dir(dir)?;
+        for entry in &recording.entries {
+            log::info!("{} {:?}", entry.timestamp, entry.pose.position);
+            for image in &entry.image_files {
+                log::info!("  image: {:?}", recording.image_path(image));
+            }
+        }
+    }
+
+    log::info!("Done!");
+    Ok(())
+}
+
+fn main() -> NetworkResult<()> {
+    env_logger::init();
+    task::block_on(record_drone())
+}
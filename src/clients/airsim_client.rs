@@ -1,81 +1,380 @@
-use async_std::net::ToSocketAddrs;
+use async_std::channel::Receiver;
+use async_std::future::timeout;
+use async_std::net::{SocketAddr, ToSocketAddrs};
+use async_std::task;
 use msgpack_rpc::{
-    message::{Request, Response},
+    message::{Notification, Request, Response},
     Utf8String,
 };
 use rmpv::Value;
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::{
+    codec::{FromMsgpack, ToMsgpack},
     error::NetworkResult,
+    msgpack::Handlers,
     types::{geopoint::GeoPoint, pose::Pose3},
-    CompressedImage, ImageRequests, ImageType, MsgPackClient, NetworkError, SceneObjects, Vector3, WeatherParameter,
+    Batch, CompressedImage, ImageRequests, ImageType, MsgPackClient, NetworkError, SceneObjects, UnitInterval, Vector3,
+    WeatherParameter,
 };
 
+use super::capabilities::{self, Capabilities, OPTIONAL_METHODS};
+use super::channel::Channel;
+use super::connect_options::ConnectOptions;
+use super::motion::MotionTracker;
+
+/// AirSim RPC API version implemented by this client
+const CLIENT_VERSION: u64 = 1;
+
+/// Oldest AirSim RPC API version this client works with
+const MIN_SERVER_VERSION: u64 = 1;
+
+/// A request that was sent and whose response has not been awaited yet
+pub(crate) struct PendingRpc {
+    method: String,
+    receiver: Receiver<Response>,
+    rpc_timeout: Option<Duration>,
+}
+
+impl PendingRpc {
+    pub(crate) async fn response(self) -> NetworkResult<Response> {
+        let receiver = self.receiver;
+        let response = async move { receiver.recv().await.map_err(NetworkError::Recv) };
+
+        match self.rpc_timeout {
+            Some(rpc_timeout) => timeout(rpc_timeout, response).await.unwrap_or_else(|_| {
+                Err(NetworkError::Timeout {
+                    message: format!("{} did not answer within {rpc_timeout:?}", self.method),
+                })
+            }),
+            None => response.await,
+        }
+    }
+
+    /// Wait for the response without the RPC timeout, for commands that bound their own duration
+    pub(crate) async fn completion(self) -> NetworkResult<Response> {
+        self.receiver.recv().await.map_err(NetworkError::Recv)
+    }
+}
+
+#[derive(Clone)]
 pub struct AirsimClient {
-    client: MsgPackClient,
-    last_request_id: AtomicU32,
+    control: MsgPackClient,
+    telemetry: MsgPackClient,
+    imagery: MsgPackClient,
+    handlers: Arc<Handlers>,
+    rpc_timeout: Option<Duration>,
+    last_request_id: Arc<AtomicU32>,
+    motion: Arc<MotionTracker>,
 }
 
 impl AirsimClient {
     pub async fn connect(addrs: impl ToSocketAddrs, vehicle_name: &str) -> NetworkResult<Self> {
+        Self::connect_with_channels(addrs, vehicle_name, &[]).await
+    }
+
+    /// Connect with a dedicated TCP connection for each of the given channels
+    ///
+    /// Channels that are not listed share the control connection. For a fast control loop
+    /// next to camera capture, use `&[Channel::Imagery]` or all three channels.
+    ///
+    /// args:
+    ///     addrs (impl ToSocketAddrs): Address of the AirSim RPC server
+    ///     vehicle_name (&str): Name of the vehicle to enable API control for
+    ///     dedicated (&[Channel]): Channels that get their own connection
+    pub async fn connect_with_channels(
+        addrs: impl ToSocketAddrs,
+        vehicle_name: &str,
+        dedicated: &[Channel],
+    ) -> NetworkResult<Self> {
+        let addrs: Vec<SocketAddr> = addrs.to_socket_addrs().await?.collect();
+        let options = ConnectOptions::default().with_dedicated_channels(dedicated);
+
+        let airsim = Self::open(&addrs, &options).await?;
+        airsim.enable_api_control(true, Some(vehicle_name)).await?;
+        Ok(airsim)
+    }
+
+    /// Connect with the given options, retrying according to its `RetryPolicy`
+    ///
+    /// The versions are checked once connected, and API control is enabled and vehicles are armed
+    /// for every vehicle in the options, if requested
+    pub async fn connect_with(options: &ConnectOptions) -> NetworkResult<Self> {
+        let retry = options.retry();
+
+        let mut attempt = 1;
+        let airsim = loop {
+            let connection = async {
+                let addrs: Vec<SocketAddr> = options.address().to_socket_addrs().await?.collect();
+                Self::open(&addrs, options).await
+            };
+            let result = match timeout(options.connect_timeout(), connection).await {
+                Ok(result) => result,
+                Err(_) => Err(NetworkError::Timeout {
+                    message: format!(
+                        "connecting to {} took longer than {:?}",
+                        options.address(),
+                        options.connect_timeout()
+                    ),
+                }),
+            };
+
+            match result {
+                Ok(airsim) => break airsim,
+                Err(e) if attempt >= retry.max_attempts => return Err(e),
+                Err(e) => {
+                    let delay = retry.delay(attempt);
+                    log::warn!("Connecting to AirSim failed ({e}), retrying in {delay:?}");
+                    task::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        };
+
+        if options.version_check() {
+            airsim.check_version().await?;
+        }
+
+        for vehicle_name in options.vehicle_names() {
+            if options.api_control() {
+                airsim.enable_api_control(true, Some(vehicle_name)).await?;
+            }
+            if options.arm() {
+                airsim.arm_disarm(true, Some(vehicle_name)).await?;
+            }
+        }
+
+        Ok(airsim)
+    }
+
+    /// Open the connections and check the server answers
+    async fn open(addrs: &[SocketAddr], options: &ConnectOptions) -> NetworkResult<Self> {
+        let handlers = Arc::new(Handlers::default());
+        let dedicated = options.dedicated_channels();
+        let tcp_nodelay = options.tcp_nodelay();
+
+        let control = MsgPackClient::connect(addrs, Arc::clone(&handlers), tcp_nodelay).await?;
+        let channel = |c: Channel| {
+            let control = control.clone();
+            let handlers = Arc::clone(&handlers);
+            async move {
+                if dedicated.contains(&c) {
+                    MsgPackClient::connect(addrs, handlers, tcp_nodelay).await
+                } else {
+                    Ok(control)
+                }
+            }
+        };
+
         let airsim = Self {
-            last_request_id: AtomicU32::new(0),
-            client: MsgPackClient::connect(addrs).await?,
+            telemetry: channel(Channel::Telemetry).await?,
+            imagery: channel(Channel::Imagery).await?,
+            control,
+            handlers,
+            rpc_timeout: options.rpc_timeout(),
+            last_request_id: Arc::new(AtomicU32::new(0)),
+            motion: Arc::new(MotionTracker::default()),
         };
         airsim.ping().await?;
-        airsim.enable_api_control(true, Some(vehicle_name)).await?;
         Ok(airsim)
     }
 
-    #[allow(deprecated)]
     fn new_request_id(&self) -> u32 {
-        self.last_request_id
-            // TODO: method below is deprecated
-            .compare_and_swap(u32::max_value(), 0, Ordering::AcqRel);
-        self.last_request_id.fetch_add(1, Ordering::AcqRel)
+        // wraps around to 0 after u32::MAX, by which time the old ids are long answered
+        self.last_request_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub(crate) fn motion_tracker(&self) -> &MotionTracker {
+        &self.motion
+    }
+
+    fn channel(&self, channel: Channel) -> &MsgPackClient {
+        match channel {
+            Channel::Control => &self.control,
+            Channel::Telemetry => &self.telemetry,
+            Channel::Imagery => &self.imagery,
+        }
     }
 
     pub(crate) async fn unary_rpc(&self, method: String, params: Option<Vec<Value>>) -> NetworkResult<Response> {
-        self.client
-            .request(Request {
-                id: self.new_request_id(),
-                method,
-                params: params.unwrap_or_default(),
+        self.send_rpc(method, params).await?.response().await
+    }
+
+    /// Queue a request without waiting for its response
+    ///
+    /// Requests on the same channel reach the server in the order they are queued
+    pub(crate) async fn send_rpc(&self, method: String, params: Option<Vec<Value>>) -> NetworkResult<PendingRpc> {
+        let client = self.channel(Channel::for_method(&method));
+        let request = Request {
+            id: self.new_request_id(),
+            method: method.clone(),
+            params: params.unwrap_or_default(),
+        };
+
+        Ok(PendingRpc {
+            method,
+            receiver: client.send_request(request).await?,
+            rpc_timeout: self.rpc_timeout,
+        })
+    }
+
+    /// Call any RPC method of the AirSim server, including ones this crate does not wrap yet
+    ///
+    /// Errors returned by the server are mapped to `NetworkError::Rpc`
+    ///
+    /// args:
+    ///     method (&str): Name of the RPC method, e.g. `simGetVehiclePose`
+    ///     params (Vec<Value>): Positional parameters of the method
+    pub async fn call_raw(&self, method: &str, params: Vec<Value>) -> NetworkResult<Value> {
+        self.unary_rpc(method.into(), Some(params))
+            .await
+            .and_then(Value::from_response)
+    }
+
+    /// Send a notification, an RPC call the server does not answer
+    ///
+    /// args:
+    ///     method (&str): Name of the method, e.g. one provided by a custom AirSim plugin
+    ///     params (Vec<Value>): Positional parameters of the method
+    pub async fn notify(&self, method: &str, params: Vec<Value>) -> NetworkResult<()> {
+        self.channel(Channel::for_method(method))
+            .notify(Notification {
+                method: method.to_string(),
+                params,
             })
             .await
     }
 
-    /// Get client version
-    fn get_client_version() -> u64 {
-        1
+    /// Handle notifications with the given method name pushed by the server, e.g. by an
+    /// AirSim fork or plugin that publishes events. Replaces an earlier handler for the method.
+    ///
+    /// The handler runs on the connection's background task, so it should return quickly,
+    /// e.g. by forwarding the parameters to a channel.
+    ///
+    /// args:
+    ///     method (&str): Name of the notification method
+    ///     handler (Fn(&[Value])): Called with the parameters of each notification
+    pub fn on_notification(&self, method: &str, handler: impl Fn(&[Value]) + Send + Sync + 'static) {
+        self.handlers.on_notification(method, Arc::new(handler));
+    }
+
+    /// Answer requests with the given method name sent by the server. Replaces an earlier
+    /// handler for the method. Requests without a handler are answered with an error.
+    ///
+    /// The handler runs on the connection's background task, so it should return quickly.
+    ///
+    /// args:
+    ///     method (&str): Name of the request method
+    ///     handler (Fn(&[Value]) -> Result<Value, Value>): Computes the result or error of each request
+    pub fn on_request(&self, method: &str, handler: impl Fn(&[Value]) -> Result<Value, Value> + Send + Sync + 'static) {
+        self.handlers.on_request(method, Arc::new(handler));
+    }
+
+    /// Start a batch of RPC calls that are sent concurrently, see `Batch`
+    pub fn batch(&self) -> Batch<'_> {
+        Batch::new(self)
+    }
+
+    /// Call any RPC method of the AirSim server with serde typed parameters and result
+    ///
+    /// The parameters are positional, so pass a tuple, e.g. `("Drone1",)`, or `()` for none.
+    /// Each tuple element is one parameter, see `RpcParams`.
+    /// Structs are encoded as maps keyed by field name, which matches AirSim's own types when
+    /// the fields are named after AirSim's, e.g. `x_val`.
+    ///
+    /// ```no_run
+    /// # async fn example(client: airsim_client::AirsimClient) -> airsim_client::NetworkResult<()> {
+    /// let vehicles: Vec<String> = client.call("listVehicles", ()).await?;
+    /// client
+    ///     .call::<_, ()>("simSetTimeOfDay", (true, "2022-09-01 12:00:00", false, 1.0, 60.0, true))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// args:
+    ///     method (&str): Name of the RPC method
+    ///     params (P): Tuple of positional parameters
+    #[cfg(feature = "serde")]
+    pub async fn call<P, R>(&self, method: &str, params: P) -> NetworkResult<R>
+    where
+        P: crate::RpcParams,
+        R: serde::de::DeserializeOwned,
+    {
+        let result = self.call_raw(method, params.to_params()?).await?;
+        crate::codec::from_value(&result)
+    }
+
+    /// Get client version, the AirSim RPC API version this client implements
+    pub fn get_client_version() -> u64 {
+        CLIENT_VERSION
     }
 
     /// Get AirSim server version
-    async fn get_server_version(&self) -> NetworkResult<u64> {
-        self.unary_rpc("getServerVersion".to_owned(), None).await.map(|res| {
-            res.result
-                .unwrap_or_else(|_| rmpv::Value::Integer(0.into()))
-                .as_u64()
-                .unwrap_or(0)
-        })
+    pub async fn get_server_version(&self) -> NetworkResult<u64> {
+        self.unary_rpc("getServerVersion".to_owned(), None)
+            .await
+            .and_then(u64::from_response)
     }
 
-    /// Get minimum required client version
-    async fn get_min_required_client_version(&self) -> NetworkResult<u64> {
+    /// Get minimum client version the server supports
+    pub async fn get_min_required_client_version(&self) -> NetworkResult<u64> {
         self.unary_rpc("getMinRequiredClientVersion".to_owned(), None)
             .await
-            .map(|res| {
-                res.result
-                    .unwrap_or_else(|_| rmpv::Value::Integer(0.into()))
-                    .as_u64()
-                    .unwrap_or(0)
-            })
+            .and_then(u64::from_response)
     }
 
+    /// Get minimum server version this client supports
     #[inline]
-    fn get_min_required_server_version() -> u64 {
-        Self::get_client_version()
+    pub fn get_min_required_server_version() -> u64 {
+        MIN_SERVER_VERSION
+    }
+
+    /// Check that the client and server versions support each other
+    ///
+    /// Returns the server version, or `NetworkError::IncompatibleVersion` if either side is too old
+    pub async fn check_version(&self) -> NetworkResult<u64> {
+        let client_v = Self::get_client_version();
+        let client_min_v = self.get_min_required_client_version().await?;
+        let server_v = self.get_server_version().await?;
+        let server_min_v = Self::get_min_required_server_version();
+
+        log::info!("Client version: {} , Min required: {} ", client_v, client_min_v);
+        log::info!("Server version: {} , Min required: {} ", server_v, server_min_v);
+
+        let message = if server_v < server_min_v {
+            "AirSim server is of older version and not supported by this client. Please upgrade!"
+        } else if client_v < client_min_v {
+            "AirSim client is of older version and not supported by this server. Please upgrade!"
+        } else {
+            return Ok(server_v);
+        };
+
+        Err(NetworkError::IncompatibleVersion {
+            client_version: client_v,
+            server_version: server_v,
+            message: message.to_string(),
+        })
+    }
+
+    /// Detect which optional RPC methods (`OPTIONAL_METHODS`) the connected server supports
+    pub async fn probe_capabilities(&self) -> NetworkResult<Capabilities> {
+        self.probe_methods(&OPTIONAL_METHODS).await
+    }
+
+    /// Detect which of the given RPC methods the connected server supports, e.g. extensions of
+    /// an AirSim fork like Colosseum or of a custom plugin
+    ///
+    /// Methods are probed by calling them without parameters, so only probe methods that take
+    /// parameters, otherwise they are executed.
+    ///
+    /// args:
+    ///     methods (&[&str]): Names of the RPC methods to probe
+    pub async fn probe_methods(&self, methods: &[&str]) -> NetworkResult<Capabilities> {
+        let server_version = self.get_server_version().await?;
+        capabilities::probe(self, server_version, methods).await
     }
 
     /// Reset the vehicle to its original starting state
@@ -99,25 +398,15 @@ impl AirsimClient {
     }
 
     /// Checks state of the connection
+    ///
+    /// Fails with `NetworkError::IncompatibleVersion` if the client and server versions do not
+    /// support each other
     pub async fn confirm_connection(&self) -> NetworkResult<bool> {
         let connected = self.ping().await?;
 
         log::info!("Connected to Airsim: {}", connected);
 
-        let client_v = Self::get_client_version();
-        let client_min_v = self.get_min_required_client_version().await?;
-        let server_v = self.get_server_version().await?;
-        let server_min_v = Self::get_min_required_server_version();
-
-        log::info!("Client version: {} , Min required: {} ", client_v, client_min_v);
-        log::info!("Server version: {} , Min required: {} ", server_v, server_min_v);
-
-        if server_v < server_min_v {
-            log::error!("AirSim server is of older version and not supported by this client. Please upgrade!")
-        } else if client_v < client_min_v {
-            log::error!("AirSim client is of older version and not supported by this server. Please upgrade!")
-        }
-
+        self.check_version().await?;
         Ok(connected)
     }
 
@@ -138,12 +427,13 @@ impl AirsimClient {
             .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
     }
 
-    /// Continue the simulation for the specified number of seconds
+    /// Continue (or resume if paused) the simulation for the specified number of seconds,
+    /// after which the simulation will be paused.
     ///
     /// args:
     ///     seconds (f64): Time to run the simulation for
     pub async fn sim_continue_for_time(&self, seconds: f64) -> NetworkResult<()> {
-        self.unary_rpc("simContinueFortime".into(), Some(vec![Value::F64(seconds)]))
+        self.unary_rpc("simContinueForTime".into(), Some(vec![Value::F64(seconds)]))
             .await
             .map(|_| ())
     }
@@ -152,13 +442,37 @@ impl AirsimClient {
     /// after which the simulation will be paused.
     ///
     /// args:
-    ///     frames (i64): Frames to run the simulation for
-    pub async fn sim_continue_for_frames(&self, frames: i64) -> NetworkResult<()> {
-        self.unary_rpc("simContinueFortime".into(), Some(vec![Value::Integer(frames.into())]))
+    ///     frames (u32): Frames to run the simulation for
+    pub async fn sim_continue_for_frames(&self, frames: u32) -> NetworkResult<()> {
+        self.unary_rpc("simContinueForFrames".into(), Some(vec![Value::Integer(frames.into())]))
             .await
             .map(|_| ())
     }
 
+    /// Recording API
+    ///
+    /// Start recording data according to the `Recording` section of settings.json.
+    /// The data is written to `airsim_rec.txt` and an `images` folder, see `Recording::from_dir`
+    pub async fn start_recording(&self) -> NetworkResult<()> {
+        self.unary_rpc("startRecording".into(), None).await.map(|_| ())
+    }
+
+    /// Recording API
+    ///
+    /// Stop the recording started with `start_recording`
+    pub async fn stop_recording(&self) -> NetworkResult<()> {
+        self.unary_rpc("stopRecording".into(), None).await.map(|_| ())
+    }
+
+    /// Recording API
+    ///
+    /// Returns True if recording is currently active
+    pub async fn is_recording(&self) -> NetworkResult<bool> {
+        self.unary_rpc("isRecording".into(), None)
+            .await
+            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    }
+
     /// Light Control APIs
     /// For more documentation: https://github.com/microsoft/AirSim/blob/b272597854f389e03bf7d9b9581666c91f2e24f9/docs/apis.md#light-control-apis
     ///
@@ -188,7 +502,7 @@ impl AirsimClient {
 
         self.unary_rpc("simListSceneObjects".into(), Some(vec![Value::String(name_regex)]))
             .await
-            .map(SceneObjects::from)
+            .and_then(SceneObjects::from_response)
     }
 
     /// The position inside the returned Pose is in the world frame
@@ -200,7 +514,7 @@ impl AirsimClient {
 
         self.unary_rpc("simGetObjectPose".into(), Some(vec![Value::String(name_regex)]))
             .await
-            .map(Pose3::from)
+            .and_then(Pose3::from_response)
     }
 
     /// Removes selected object from the world
@@ -250,15 +564,14 @@ impl AirsimClient {
             Some(vec![
                 Value::String(name_regex),
                 Value::String(asset_name),
-                pose.as_msgpack(),
-                scale.as_msgpack(),
+                pose.to_msgpack(),
+                scale.to_msgpack(),
                 Value::Boolean(physics_enabled),
                 Value::Boolean(is_blueprint),
             ]),
         )
         .await
-        .map(|response| response.result.unwrap())
-        .map(|val| val.as_str().unwrap().to_string())
+        .and_then(String::from_response)
     }
 
     /// Runtime swap texture API
@@ -351,8 +664,10 @@ impl AirsimClient {
     /// Enable Weather effects. Needs to be called before using `sim_set_weather_parameter()` method
     /// args:
     ///     enable (bool): true to enable, false to disable
-    pub async fn sim_enable_weather(&self, _enable: bool) -> NetworkResult<()> {
-        unimplemented!("todo")
+    pub async fn sim_enable_weather(&self, enable: bool) -> NetworkResult<()> {
+        self.unary_rpc("simEnableWeather".into(), Some(vec![Value::Boolean(enable)]))
+            .await
+            .map(|_| ())
     }
 
     /// Weather API
@@ -361,13 +676,14 @@ impl AirsimClient {
     ///
     /// args:
     ///     param (WeatherParameter): Weather effect to be enabled
-    ///     val (f32): Intensity of the effect, Range 0-1
-    pub async fn sim_set_weather_parameter(&self, _param: WeatherParameter, val: f32) -> NetworkResult<()> {
-        if val.is_sign_negative() || val > 1.0 {
-            panic!("val outside of valid range 0.0 to 1.0")
-        }
-
-        unimplemented!("todo")
+    ///     val (UnitInterval): Intensity of the effect, Range 0-1
+    pub async fn sim_set_weather_parameter(&self, param: WeatherParameter, val: UnitInterval) -> NetworkResult<()> {
+        self.unary_rpc(
+            "simSetWeatherParameter".into(),
+            Some(vec![param.to_msgpack(), Value::F32(val.value())]),
+        )
+        .await
+        .map(|_| ())
     }
 }
 
@@ -451,7 +767,7 @@ impl AirsimClient {
 
         self.unary_rpc("getHomeGeoPoint".into(), Some(vec![Value::String(vehicle_name)]))
             .await
-            .map(GeoPoint::from)
+            .and_then(GeoPoint::from_response)
     }
 
     /// Camera API
@@ -481,16 +797,13 @@ impl AirsimClient {
             "simGetImage".into(),
             Some(vec![
                 Value::String(camera_name),
-                image_type.as_msgpack(),
+                image_type.to_msgpack(),
                 Value::String(vehicle_name),
                 Value::Boolean(external),
             ]),
         )
         .await
-        .map(|response| {
-            println!("resp: {response:?}");
-            CompressedImage::from(response)
-        })
+        .and_then(CompressedImage::from_response)
     }
 
     /// Camera API
@@ -516,7 +829,7 @@ impl AirsimClient {
         self.unary_rpc(
             "simGetImages".into(),
             Some(vec![
-                requests.as_msgpack(),
+                requests.to_msgpack(),
                 Value::String(vehicle_name),
                 Value::Boolean(external),
             ]),
@@ -528,3 +841,183 @@ impl AirsimClient {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use async_std::task;
+    use msgpack_rpc::message::{Message, Notification, Request, Response};
+    use rmpv::Value;
+
+    use super::AirsimClient;
+    use crate::clients::test_server::TestServer;
+    use crate::{NetworkError, Pose3, UnitInterval, Vector3, WeatherParameter};
+
+    /// Server that pushes a notification and a request to the client when it receives `trigger`,
+    /// and reports the client's answer and notifications on the returned channel
+    fn pushing_server() -> (String, mpsc::Receiver<Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = stream.try_clone().unwrap();
+
+            while let Ok(message) = Message::decode(&mut reader) {
+                match message {
+                    Message::Request(request) => {
+                        if request.method == "trigger" {
+                            let event = Message::Notification(Notification {
+                                method: "event".to_string(),
+                                params: vec![Value::from(42)],
+                            });
+                            let ask = Message::Request(Request {
+                                id: 99,
+                                method: "ask".to_string(),
+                                params: vec![Value::from(2)],
+                            });
+                            stream.write_all(&event.pack().unwrap()).unwrap();
+                            stream.write_all(&ask.pack().unwrap()).unwrap();
+                        }
+                        let response = Message::Response(Response {
+                            id: request.id,
+                            result: Ok(Value::Boolean(true)),
+                        });
+                        stream.write_all(&response.pack().unwrap()).unwrap();
+                    }
+                    Message::Response(response) => tx.send(response.result.unwrap()).unwrap(),
+                    Message::Notification(notification) => tx.send(Value::from(notification.method)).unwrap(),
+                }
+            }
+        });
+
+        (addr, rx)
+    }
+
+    #[test]
+    fn test_malformed_frames_fail_pending_requests() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = stream.try_clone().unwrap();
+
+            while let Ok(Message::Request(request)) = Message::decode(&mut reader) {
+                match request.method.as_str() {
+                    // an array that is no msgpack-rpc message, its type is 5
+                    "broken" => stream.write_all(&[0x93, 0x05, 0x00, 0x00]).unwrap(),
+                    _ => {
+                        for id in [request.id + 1000, request.id] {
+                            let response = Message::Response(Response {
+                                id,
+                                result: Ok(Value::Boolean(true)),
+                            });
+                            stream.write_all(&response.pack().unwrap()).unwrap();
+                        }
+                    }
+                }
+            }
+        });
+
+        task::block_on(async {
+            let client = AirsimClient::connect(&addr, "").await.unwrap();
+            // a response nobody waits for is dropped, the connection keeps working
+            assert_eq!(
+                client.call_raw("listVehicles", vec![]).await.unwrap(),
+                Value::Boolean(true)
+            );
+
+            assert!(client.call_raw("broken", vec![]).await.is_err());
+            assert!(client.call_raw("listVehicles", vec![]).await.is_err());
+        });
+    }
+
+    #[test]
+    fn test_server_notifications_and_requests() {
+        let (addr, server_rx) = pushing_server();
+        let (event_tx, event_rx) = mpsc::channel();
+
+        task::block_on(async {
+            let client = AirsimClient::connect(addr, "").await.unwrap();
+            // handlers may register handlers, the server asks right after the event
+            let registrar = client.clone();
+            client.on_notification("event", move |params| {
+                registrar.on_request("ask", |params| match params[0].as_i64() {
+                    Some(n) => Ok(Value::from(n * 2)),
+                    None => Err(Value::from("expected an integer")),
+                });
+                event_tx.send(params[0].as_i64()).unwrap();
+            });
+
+            client.call_raw("trigger", vec![]).await.unwrap();
+            client.notify("hello", vec![]).await.unwrap();
+
+            // keep the client connected until the server has seen everything
+            let timeout = Duration::from_secs(5);
+            assert_eq!(event_rx.recv_timeout(timeout).unwrap(), Some(42));
+            assert_eq!(server_rx.recv_timeout(timeout).unwrap(), Value::from(4));
+            assert_eq!(server_rx.recv_timeout(timeout).unwrap(), Value::from("hello"));
+        });
+    }
+
+    #[test]
+    fn test_set_weather() {
+        let (tx, rx) = mpsc::channel();
+        let server = TestServer::spawn(move |request| {
+            tx.send((request.method.clone(), request.params.clone())).unwrap();
+            Ok(Value::Nil)
+        });
+
+        task::block_on(async {
+            let client = AirsimClient::connect(&server.addr, "").await.unwrap();
+            client.sim_enable_weather(true).await.unwrap();
+            let intensity = UnitInterval::try_from(0.25).unwrap();
+            client
+                .sim_set_weather_parameter(WeatherParameter::Rain, intensity)
+                .await
+                .unwrap();
+        });
+
+        let calls: Vec<_> = rx.try_iter().filter(|(method, _)| method.starts_with("sim")).collect();
+        assert_eq!(calls[0], ("simEnableWeather".to_string(), vec![Value::Boolean(true)]));
+        assert_eq!(
+            calls[1],
+            (
+                "simSetWeatherParameter".to_string(),
+                vec![Value::from(0), Value::F32(0.25)]
+            )
+        );
+    }
+
+    #[test]
+    fn test_spawn_object_errors() {
+        let server = TestServer::spawn(|request| match request.params.first().and_then(Value::as_str) {
+            Some("Cube") => Ok(Value::from("Cube_2")),
+            Some("Sphere") => Ok(Value::Boolean(true)),
+            _ => Err(Value::from("unknown asset")),
+        });
+
+        task::block_on(async {
+            let client = AirsimClient::connect(&server.addr, "").await.unwrap();
+            let spawn = |name: &'static str| {
+                let client = client.clone();
+                async move {
+                    client
+                        .sim_spawn_object(name, "Asset", Pose3::default(), Vector3::new(1.0, 1.0, 1.0), None, None)
+                        .await
+                }
+            };
+
+            assert_eq!(spawn("Cube").await.unwrap(), "Cube_2");
+            assert!(matches!(spawn("Sphere").await, Err(NetworkError::Parse { .. })));
+            assert!(matches!(spawn("Cone").await, Err(NetworkError::Rpc { .. })));
+        });
+    }
+}
@@ -0,0 +1,160 @@
+use futures::stream::{self, StreamExt};
+use rmpv::Value;
+
+use crate::error::NetworkResult;
+
+use super::airsim_client::AirsimClient;
+
+/// Default number of requests of a batch that are in flight at the same time
+const DEFAULT_MAX_IN_FLIGHT: usize = 16;
+
+/// Set of RPC calls sent concurrently over a single connection
+///
+/// Instead of awaiting each call before sending the next, all calls are pipelined and the
+/// results are returned in the order the calls were added. At most `max_in_flight` requests of
+/// a batch are outstanding at once, so a large batch does not hold up requests sent by other
+/// tasks on the same connection.
+///
+/// ```no_run
+/// # use airsim_client::{AirsimClient, CompressedImage, FromMsgpack, ImageType, MultiRotorState, NetworkResult, ToMsgpack};
+/// # use rmpv::Value;
+/// # async fn run(client: AirsimClient) -> NetworkResult<()> {
+/// let vehicles = ["Drone1", "Drone2", "Drone3"];
+///
+/// let mut batch = client.batch();
+/// for vehicle in vehicles {
+///     batch.push("getMultirotorState", vec![Value::from(vehicle)]);
+/// }
+/// let image_index = batch.push(
+///     "simGetImage",
+///     vec![Value::from("0"), ImageType::Scene.to_msgpack(), Value::from("Drone1"), Value::from(false)],
+/// );
+///
+/// let mut results = batch.send().await;
+/// let image = CompressedImage::from_msgpack(&results.remove(image_index)?)?;
+/// let states = results
+///     .into_iter()
+///     .map(|result| MultiRotorState::from_msgpack(&result?))
+///     .collect::<NetworkResult<Vec<_>>>()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Batch<'a> {
+    client: &'a AirsimClient,
+    calls: Vec<(String, Vec<Value>)>,
+    max_in_flight: usize,
+}
+
+impl<'a> Batch<'a> {
+    pub fn new(client: &'a AirsimClient) -> Self {
+        Self {
+            client,
+            calls: vec![],
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+        }
+    }
+
+    /// Max number of requests of this batch that are in flight at the same time
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// Add a call to the batch and return the index of its result
+    ///
+    /// args:
+    ///     method (&str): Name of the RPC method, e.g. `getMultirotorState`
+    ///     params (Vec<Value>): Positional parameters of the method
+    pub fn push(&mut self, method: &str, params: Vec<Value>) -> usize {
+        self.calls.push((method.to_string(), params));
+        self.calls.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Send all calls and wait for their results, in the order the calls were added
+    ///
+    /// A failing call does not affect the others, each result carries its own error
+    pub async fn send(self) -> Vec<NetworkResult<Value>> {
+        let client = self.client;
+
+        stream::iter(self.calls)
+            .map(|(method, params)| async move { client.call_raw(&method, params).await })
+            .buffered(self.max_in_flight)
+            .collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+
+    use async_std::task;
+    use msgpack_rpc::message::{Message, Response};
+    use rmpv::Value;
+
+    use crate::AirsimClient;
+
+    /// Answers `echo` calls with their first parameter, holding them back until `n` have arrived
+    /// and then replying in reverse order with a single write
+    fn reverse_echo_server(n: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = stream.try_clone().unwrap();
+            let mut pending = vec![];
+
+            while let Ok(Message::Request(request)) = Message::decode(&mut reader) {
+                let result = request.params.first().cloned().unwrap_or(Value::Boolean(true));
+                let response = Message::Response(Response {
+                    id: request.id,
+                    result: Ok(result),
+                });
+
+                if request.method != "echo" {
+                    stream.write_all(&response.pack().unwrap()).unwrap();
+                    continue;
+                }
+
+                pending.push(response);
+                if pending.len() == n {
+                    let bytes: Vec<u8> = pending.drain(..).rev().flat_map(|r| r.pack().unwrap()).collect();
+                    stream.write_all(&bytes).unwrap();
+                }
+            }
+        });
+
+        addr
+    }
+
+    #[test]
+    fn test_batch_results_in_call_order() {
+        task::block_on(async {
+            let client = AirsimClient::connect(reverse_echo_server(3), "").await.unwrap();
+
+            let mut batch = client.batch();
+            for i in 0..3 {
+                batch.push("echo", vec![Value::from(i)]);
+            }
+            let results: Vec<_> = batch
+                .send()
+                .await
+                .into_iter()
+                .map(|r| r.unwrap().as_i64().unwrap())
+                .collect();
+
+            assert_eq!(results, [0, 1, 2]);
+        });
+    }
+}
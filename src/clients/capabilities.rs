@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use crate::error::NetworkResult;
+use crate::NetworkError;
+
+use super::airsim_client::AirsimClient;
+
+/// Optional RPC methods probed by `AirsimClient::probe_capabilities`
+///
+/// Every method takes parameters, so the probe (a call without any) is rejected before
+/// the method runs and has no side effects.
+pub const OPTIONAL_METHODS: [&str; 6] = [
+    "simGetDetections",
+    "simAddDetectionFilterMeshName",
+    "getLidarData",
+    "simGetLidarSegmentation",
+    "getDistanceSensorData",
+    "simSetWind",
+];
+
+/// Error message of rpclib servers (AirSim and forks like Colosseum) for unknown methods
+const UNKNOWN_METHOD_ERROR: &str = "could not find function";
+
+/// RPC methods supported by the connected server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    pub server_version: u64,
+    methods: BTreeMap<String, bool>,
+}
+
+impl Capabilities {
+    /// Returns true if the method was probed and the server knows it
+    pub fn supports(&self, method: &str) -> bool {
+        self.methods.get(method).copied().unwrap_or(false)
+    }
+
+    /// Probed methods and whether the server supports them
+    pub fn methods(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.methods
+            .iter()
+            .map(|(method, supported)| (method.as_str(), *supported))
+    }
+
+    /// Object detection API, `simGetDetections`
+    pub fn detection(&self) -> bool {
+        self.supports("simGetDetections")
+    }
+
+    /// Lidar point clouds, `getLidarData`
+    pub fn lidar(&self) -> bool {
+        self.supports("getLidarData")
+    }
+
+    /// Lidar segmentation, removed in later AirSim versions
+    pub fn lidar_segmentation(&self) -> bool {
+        self.supports("simGetLidarSegmentation")
+    }
+
+    /// Distance sensor readings, `getDistanceSensorData`
+    pub fn distance_sensor(&self) -> bool {
+        self.supports("getDistanceSensorData")
+    }
+
+    /// Wind simulation, `simSetWind`
+    pub fn wind(&self) -> bool {
+        self.supports("simSetWind")
+    }
+}
+
+/// Probe which of `methods` the server supports, all probes are sent as one batch
+pub(crate) async fn probe(client: &AirsimClient, server_version: u64, methods: &[&str]) -> NetworkResult<Capabilities> {
+    let mut batch = client.batch();
+    for method in methods {
+        batch.push(method, vec![]);
+    }
+
+    let mut probed = BTreeMap::new();
+    for (method, result) in methods.iter().zip(batch.send().await) {
+        let supported = match result {
+            Ok(_) => true,
+            Err(NetworkError::Rpc { message }) => !message.contains(UNKNOWN_METHOD_ERROR),
+            Err(e) => return Err(e),
+        };
+        probed.insert(method.to_string(), supported);
+    }
+
+    Ok(Capabilities {
+        server_version,
+        methods: probed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use async_std::task;
+    use rmpv::Value;
+
+    use super::OPTIONAL_METHODS;
+    use crate::clients::test_server::TestServer;
+    use crate::{AirsimClient, NetworkError};
+
+    #[test]
+    fn test_probe_capabilities() {
+        let server = TestServer::spawn(|request| match request.method.as_str() {
+            "getServerVersion" | "getMinRequiredClientVersion" => Ok(Value::from(1)),
+            "getLidarData" => Err(Value::from(
+                "rpc::server: function 'getLidarData' (taking 2 arg(s)) called with 0 arg(s)",
+            )),
+            method if OPTIONAL_METHODS.contains(&method) => Err(Value::from(format!(
+                "rpc::server: server could not find function '{method}' with argument count 0."
+            ))),
+            _ => Ok(Value::Boolean(true)),
+        });
+
+        task::block_on(async {
+            let client = AirsimClient::connect(&server.addr, "").await.unwrap();
+            let capabilities = client.probe_capabilities().await.unwrap();
+
+            assert_eq!(capabilities.server_version, 1);
+            assert!(capabilities.lidar());
+            assert!(!capabilities.detection());
+            assert!(!capabilities.supports("someUnprobedMethod"));
+            assert_eq!(capabilities.methods().count(), OPTIONAL_METHODS.len());
+        });
+    }
+
+    #[test]
+    fn test_incompatible_version() {
+        let server = TestServer::spawn(|request| match request.method.as_str() {
+            "getServerVersion" => Ok(Value::from(1)),
+            "getMinRequiredClientVersion" => Ok(Value::from(4)),
+            _ => Ok(Value::Boolean(true)),
+        });
+
+        task::block_on(async {
+            let client = AirsimClient::connect(&server.addr, "").await.unwrap();
+            assert!(matches!(
+                client.confirm_connection().await,
+                Err(NetworkError::IncompatibleVersion { server_version: 1, .. })
+            ));
+        });
+    }
+}
@@ -5,21 +5,111 @@ use rmpv::Value;
 use crate::error::NetworkResult;
 
 use super::airsim_client::AirsimClient;
+use super::capabilities::Capabilities;
+use super::channel::Channel;
+use super::connect_options::ConnectOptions;
+use super::shutdown::{ReleaseAction, ShutdownGuard};
 
+#[derive(Clone)]
 pub struct CarClient {
     airsim_client: AirsimClient,
-    vehicle_name: &'static str,
+    vehicle_name: String,
 }
 
 impl CarClient {
-    pub async fn connect(addrs: impl ToSocketAddrs, vehicle_name: &'static str) -> NetworkResult<Self> {
+    pub async fn connect(addrs: impl ToSocketAddrs, vehicle_name: &str) -> NetworkResult<Self> {
         let airsim_client = AirsimClient::connect(addrs, vehicle_name).await?;
         Ok(Self {
             airsim_client,
-            vehicle_name,
+            vehicle_name: vehicle_name.to_string(),
         })
     }
 
+    /// Connect with a dedicated TCP connection for each of the given channels,
+    /// see `AirsimClient::connect_with_channels`
+    pub async fn connect_with_channels(
+        addrs: impl ToSocketAddrs,
+        vehicle_name: &str,
+        dedicated: &[Channel],
+    ) -> NetworkResult<Self> {
+        let airsim_client = AirsimClient::connect_with_channels(addrs, vehicle_name, dedicated).await?;
+        Ok(Self {
+            airsim_client,
+            vehicle_name: vehicle_name.to_string(),
+        })
+    }
+
+    /// Connect with the given options, see `AirsimClient::connect_with`
+    ///
+    /// Commands are sent to the first vehicle of the options
+    pub async fn connect_with(options: &ConnectOptions) -> NetworkResult<Self> {
+        let airsim_client = AirsimClient::connect_with(options).await?;
+        Ok(Self {
+            airsim_client,
+            vehicle_name: options.vehicle_names().first().cloned().unwrap_or_default(),
+        })
+    }
+
+    /// Access the underlying `AirsimClient`, e.g. to drive a `SteppedSim`
+    #[inline(always)]
+    pub fn airsim_client(&self) -> &AirsimClient {
+        &self.airsim_client
+    }
+
+    /// Name of the vehicle this client sends its commands to
+    #[inline(always)]
+    pub fn vehicle_name(&self) -> &str {
+        &self.vehicle_name
+    }
+
+    /// Guard that releases this client's vehicle on drop or signal, see `ShutdownGuard`
+    ///
+    /// args:
+    ///     action (ReleaseAction): What to do with the vehicle before disarming it, usually `ReleaseAction::Stop`
+    pub fn shutdown_guard(&self, action: ReleaseAction) -> ShutdownGuard {
+        ShutdownGuard::new(&self.airsim_client, &[&self.vehicle_name], action)
+    }
+
+    /// Call any RPC method of the AirSim server, see `AirsimClient::call_raw`
+    ///
+    /// The vehicle name is not added to the parameters, pass `vehicle_name()` where the method expects it
+    #[inline(always)]
+    pub async fn call_raw(&self, method: &str, params: Vec<Value>) -> NetworkResult<Value> {
+        self.airsim_client.call_raw(method, params).await
+    }
+
+    /// Call any RPC method of the AirSim server with serde typed parameters and result,
+    /// see `AirsimClient::call`
+    ///
+    /// The vehicle name is not added to the parameters, pass `vehicle_name()` where the method expects it
+    #[cfg(feature = "serde")]
+    #[inline(always)]
+    pub async fn call<P, R>(&self, method: &str, params: P) -> NetworkResult<R>
+    where
+        P: crate::RpcParams,
+        R: serde::de::DeserializeOwned,
+    {
+        self.airsim_client.call(method, params).await
+    }
+
+    /// Send a notification, see `AirsimClient::notify`
+    #[inline(always)]
+    pub async fn notify(&self, method: &str, params: Vec<Value>) -> NetworkResult<()> {
+        self.airsim_client.notify(method, params).await
+    }
+
+    /// Handle notifications pushed by the server, see `AirsimClient::on_notification`
+    #[inline(always)]
+    pub fn on_notification(&self, method: &str, handler: impl Fn(&[Value]) + Send + Sync + 'static) {
+        self.airsim_client.on_notification(method, handler)
+    }
+
+    /// Answer requests sent by the server, see `AirsimClient::on_request`
+    #[inline(always)]
+    pub fn on_request(&self, method: &str, handler: impl Fn(&[Value]) -> Result<Value, Value> + Send + Sync + 'static) {
+        self.airsim_client.on_request(method, handler)
+    }
+
     /// Reset the vehicle to its original starting state
     ///
     /// Note that you must call `enable_ap, Some(vehicle_name)i_control` and `arm_disarm` again after the call to reset
@@ -40,6 +130,42 @@ impl CarClient {
         self.airsim_client.confirm_connection().await
     }
 
+    /// Get AirSim server version
+    #[inline(always)]
+    pub async fn get_server_version(&self) -> NetworkResult<u64> {
+        self.airsim_client.get_server_version().await
+    }
+
+    /// Check that the client and server versions support each other, see `AirsimClient::check_version`
+    #[inline(always)]
+    pub async fn check_version(&self) -> NetworkResult<u64> {
+        self.airsim_client.check_version().await
+    }
+
+    /// Detect which optional RPC methods the connected server supports
+    #[inline(always)]
+    pub async fn probe_capabilities(&self) -> NetworkResult<Capabilities> {
+        self.airsim_client.probe_capabilities().await
+    }
+
+    /// Start recording data according to the `Recording` section of settings.json
+    #[inline(always)]
+    pub async fn start_recording(&self) -> NetworkResult<()> {
+        self.airsim_client.start_recording().await
+    }
+
+    /// Stop the recording started with `start_recording`
+    #[inline(always)]
+    pub async fn stop_recording(&self) -> NetworkResult<()> {
+        self.airsim_client.stop_recording().await
+    }
+
+    /// Returns True if recording is currently active
+    #[inline(always)]
+    pub async fn is_recording(&self) -> NetworkResult<bool> {
+        self.airsim_client.is_recording().await
+    }
+
     /// Enables or disables API control for vehicle corresponding to vehicle_name
     ///
     /// args:
@@ -48,7 +174,7 @@ impl CarClient {
     #[inline(always)]
     pub async fn enable_api_control(&self, is_enabled: bool) -> NetworkResult<bool> {
         self.airsim_client
-            .enable_api_control(is_enabled, Some(self.vehicle_name))
+            .enable_api_control(is_enabled, Some(&self.vehicle_name))
             .await
     }
 
@@ -62,14 +188,14 @@ impl CarClient {
     #[inline(always)]
     pub async fn is_api_control_enabled(&self, is_enabled: bool) -> NetworkResult<bool> {
         self.airsim_client
-            .is_api_control_enabled(is_enabled, Some(self.vehicle_name))
+            .is_api_control_enabled(is_enabled, Some(&self.vehicle_name))
             .await
     }
 
     /// Cancel previous Async task
     #[inline(always)]
     pub async fn cancel_last_task(&self) -> NetworkResult<bool> {
-        self.airsim_client.cancel_last_task(Some(self.vehicle_name)).await
+        self.airsim_client.cancel_last_task(Some(&self.vehicle_name)).await
     }
 
     /// Returns true if API control is established.
@@ -82,7 +208,7 @@ impl CarClient {
     ///     vehicle_name (Option<String>): Name of the vehicle to send this command to
     #[inline(always)]
     pub async fn arm_disarm(&self, arm: bool) -> NetworkResult<bool> {
-        self.airsim_client.arm_disarm(arm, Some(self.vehicle_name)).await
+        self.airsim_client.arm_disarm(arm, Some(&self.vehicle_name)).await
     }
 
     /// Takeoff vehicle to 3m above ground. Vehicle should not be moving when this API is used
@@ -91,7 +217,7 @@ impl CarClient {
     ///     timeout_sec (Option<u64>): Timeout for the vehicle to reach desired altitude
     ///     vehicle_name (Option<String>): Name of the vehicle to send this command to
     pub async fn take_off_async(&self, timeout_sec: u64) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
 
         self.airsim_client
             .unary_rpc(
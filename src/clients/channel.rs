@@ -0,0 +1,74 @@
+/// Category of an RPC call, used to route it to a connection
+///
+/// By default all categories share one connection. Connecting with dedicated channels
+/// (see `AirsimClient::connect_with_channels`) gives each listed category its own TCP
+/// connection, so e.g. large image responses no longer delay time critical commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// Commands that change the simulation or a vehicle, e.g. `move_by_velocity_async`
+    Control,
+    /// Small state queries, e.g. `get_multirotor_state` or `sim_is_pause`
+    Telemetry,
+    /// Bulk sensor data, e.g. `sim_get_image` or lidar point clouds
+    Imagery,
+}
+
+/// RPC methods that return bulk sensor data
+const IMAGERY_METHODS: [&str; 5] = [
+    "simGetImage",
+    "simGetImages",
+    "simGetMeshPositionVertexBuffers",
+    "getLidarData",
+    "simGetLidarSegmentation",
+];
+
+/// Method name prefixes of RPCs that only read state
+const TELEMETRY_PREFIXES: [&str; 7] = ["get", "simGet", "simIs", "is", "list", "simList", "ping"];
+
+impl Channel {
+    /// Channel an RPC method is routed to
+    pub fn for_method(method: &str) -> Self {
+        if IMAGERY_METHODS.contains(&method) {
+            Channel::Imagery
+        } else if TELEMETRY_PREFIXES.iter().any(|prefix| method.starts_with(prefix)) {
+            Channel::Telemetry
+        } else {
+            Channel::Control
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_std::task;
+
+    use super::Channel;
+    use crate::clients::test_server::TestServer;
+    use crate::AirsimClient;
+
+    #[test]
+    fn test_channel_for_method() {
+        assert_eq!(Channel::for_method("simGetImages"), Channel::Imagery);
+        assert_eq!(Channel::for_method("getMultirotorState"), Channel::Telemetry);
+        assert_eq!(Channel::for_method("simIsPause"), Channel::Telemetry);
+        assert_eq!(Channel::for_method("moveByVelocity"), Channel::Control);
+        assert_eq!(Channel::for_method("simPause"), Channel::Control);
+    }
+
+    #[test]
+    fn test_dedicated_channels_open_connections() {
+        task::block_on(async {
+            let shared = TestServer::spawn_default();
+            let client = AirsimClient::connect(&shared.addr, "").await.unwrap();
+            client.sim_is_pause().await.unwrap();
+            assert_eq!(shared.connections(), 1);
+
+            let dedicated = TestServer::spawn_default();
+            let client = AirsimClient::connect_with_channels(&dedicated.addr, "", &[Channel::Imagery])
+                .await
+                .unwrap();
+            client.call_raw("simGetImages", vec![]).await.unwrap();
+            assert_eq!(dedicated.connections(), 2);
+        });
+    }
+}
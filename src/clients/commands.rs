@@ -0,0 +1,614 @@
+use std::time::Duration;
+
+use crate::error::NetworkResult;
+use crate::{DrivetrainType, GeoPoint, NetworkError, Path, Position3, Vector3, Velocity2, Velocity3, YawMode};
+
+use super::motion::MotionTask;
+use super::multi_rotor_client::MultiRotorClient;
+use super::safe_multi_rotor::SafeMultiRotor;
+use sealed::{Start, Validated};
+
+/// Speed of the path following commands if none is given, in m/s
+pub const DEFAULT_VELOCITY: f32 = 5.0;
+
+/// Duration of the velocity commands if none is given
+pub const DEFAULT_DURATION: Duration = Duration::from_secs(1);
+
+/// Timeout AirSim uses when none is given, effectively no timeout
+const NO_TIMEOUT_SEC: f32 = 3e38;
+
+/// A motion command that can be sent with `MultiRotorClient::execute` or `SafeMultiRotor::execute`
+///
+/// The trait is sealed, so a command can only be sent by `execute`, after `validate` succeeded.
+pub trait MotionCommand: sealed::Start {
+    /// Check the parameters, nothing is sent if this fails
+    fn validate(&self) -> NetworkResult<()>;
+}
+
+pub(crate) mod sealed {
+    use crate::error::NetworkResult;
+    use crate::NetworkError;
+
+    use super::{MotionTask, MultiRotorClient, SafeMultiRotor};
+
+    /// Proof that `MotionCommand::validate` succeeded, only `execute` creates it
+    pub struct Validated(pub(crate) ());
+
+    /// Sending of a `MotionCommand`, it takes a `Validated` so a command is not sent unvalidated
+    pub trait Start {
+        /// Send the command, called by `execute` after `validate` succeeded
+        fn start(&self, client: &MultiRotorClient, validated: Validated) -> MotionTask;
+
+        /// Check the command against the limits and send it, called by `SafeMultiRotor::execute`
+        /// after `validate` succeeded
+        ///
+        /// Commands that do not implement this are rejected, as their targets cannot be checked.
+        fn start_within(&self, _safe: &SafeMultiRotor, _validated: Validated) -> NetworkResult<MotionTask> {
+            Err(NetworkError::SafetyViolation {
+                message: "the command cannot be checked against the safety limits".to_string(),
+            })
+        }
+    }
+}
+
+fn invalid(message: String) -> NetworkError {
+    NetworkError::ValidationError { message }
+}
+
+fn check_finite(name: &str, values: &[f32]) -> NetworkResult<()> {
+    match values.iter().all(|v| v.is_finite()) {
+        true => Ok(()),
+        false => Err(invalid(format!("{name} must be finite, got {values:?}"))),
+    }
+}
+
+fn check_positive(name: &str, value: f32) -> NetworkResult<()> {
+    match value.is_finite() && value > 0.0 {
+        true => Ok(()),
+        false => Err(invalid(format!("{name} must be positive, got {value}"))),
+    }
+}
+
+/// Options shared by the commands that follow a path with the position controller
+#[derive(Debug, Clone, Copy)]
+struct PathOptions {
+    velocity: f32,
+    timeout_sec: f32,
+    drivetrain: DrivetrainType,
+    yaw_mode: YawMode,
+    lookahead: f32,
+    adaptive_lookahead: f32,
+}
+
+impl Default for PathOptions {
+    fn default() -> Self {
+        Self {
+            velocity: DEFAULT_VELOCITY,
+            timeout_sec: NO_TIMEOUT_SEC,
+            drivetrain: DrivetrainType::MaxDegreeOfFreedom,
+            yaw_mode: YawMode::new(true, 0.0),
+            lookahead: -1.0,
+            adaptive_lookahead: 1.0,
+        }
+    }
+}
+
+impl PathOptions {
+    fn within(self, safe: &SafeMultiRotor) -> NetworkResult<Self> {
+        Ok(Self {
+            velocity: safe.check_speed(self.velocity)?,
+            ..self
+        })
+    }
+
+    fn validate(&self) -> NetworkResult<()> {
+        check_positive("velocity", self.velocity)?;
+        check_positive("timeout", self.timeout_sec)?;
+        check_finite("yaw", &[self.yaw_mode.yaw_or_rate()])?;
+        if self.lookahead != -1.0 {
+            check_positive("lookahead", self.lookahead)?;
+        }
+        match (0.0..=1.0).contains(&self.adaptive_lookahead) {
+            true => Ok(()),
+            false => Err(invalid(format!(
+                "adaptive lookahead must be between 0.0 and 1.0, got {}",
+                self.adaptive_lookahead
+            ))),
+        }
+    }
+}
+
+/// Setters for the commands that embed `PathOptions` as `options`
+macro_rules! path_options {
+    ($command:ident) => {
+        impl $command {
+            /// Speed along the path in m/s, defaults to `DEFAULT_VELOCITY`
+            pub fn velocity(mut self, velocity: f32) -> Self {
+                self.options.velocity = velocity;
+                self
+            }
+
+            /// Time to reach the goal, defaults to no timeout
+            pub fn timeout(mut self, timeout: Duration) -> Self {
+                self.options.timeout_sec = timeout.as_secs_f32();
+                self
+            }
+
+            /// Where the vehicle faces, defaults to holding its current yaw
+            pub fn yaw(mut self, yaw_mode: YawMode) -> Self {
+                self.options.yaw_mode = yaw_mode;
+                self
+            }
+
+            /// Distance to look ahead on the path in meters, defaults to `-1`, which lets AirSim pick it
+            pub fn lookahead(mut self, lookahead: f32) -> Self {
+                self.options.lookahead = lookahead;
+                self
+            }
+
+            /// Factor between 0.0 and 1.0 that adapts the lookahead to the speed, defaults to `1`
+            pub fn adaptive_lookahead(mut self, adaptive_lookahead: f32) -> Self {
+                self.options.adaptive_lookahead = adaptive_lookahead;
+                self
+            }
+        }
+    };
+    ($command:ident, drivetrain) => {
+        path_options!($command);
+
+        impl $command {
+            /// Defaults to `DrivetrainType::MaxDegreeOfFreedom`
+            pub fn drivetrain(mut self, drivetrain: DrivetrainType) -> Self {
+                self.options.drivetrain = drivetrain;
+                self
+            }
+        }
+    };
+}
+
+/// Fly to a position in the local NED frame, see `MultiRotorClient::move_to_position_async`
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use airsim_client::{MoveToPosition, MultiRotorClient, NetworkResult, Position3, YawMode};
+/// # async fn run(client: MultiRotorClient) -> NetworkResult<()> {
+/// let command = MoveToPosition::new(Position3::new(10.0, 0.0, -20.0))
+///     .velocity(3.0)
+///     .timeout(Duration::from_secs(30))
+///     .yaw(YawMode::new(false, 90.0));
+/// client.execute(&command)?.await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct MoveToPosition {
+    position: Position3,
+    options: PathOptions,
+}
+
+impl MoveToPosition {
+    pub fn new(position: Position3) -> Self {
+        Self {
+            position,
+            options: PathOptions::default(),
+        }
+    }
+}
+
+path_options!(MoveToPosition, drivetrain);
+
+impl MotionCommand for MoveToPosition {
+    fn validate(&self) -> NetworkResult<()> {
+        check_finite("position", &[self.position.x, self.position.y, self.position.z])?;
+        self.options.validate()
+    }
+}
+
+impl Start for MoveToPosition {
+    fn start(&self, client: &MultiRotorClient, _validated: Validated) -> MotionTask {
+        let o = self.options;
+        client.move_to_position_async(
+            self.position,
+            o.velocity,
+            o.timeout_sec,
+            o.drivetrain,
+            o.yaw_mode,
+            Some(o.lookahead),
+            Some(o.adaptive_lookahead),
+        )
+    }
+
+    fn start_within(&self, safe: &SafeMultiRotor, validated: Validated) -> NetworkResult<MotionTask> {
+        let command = Self {
+            position: safe.check_position(self.position)?,
+            options: self.options.within(safe)?,
+        };
+        Ok(command.start(safe.client(), validated))
+    }
+}
+
+/// Fly along waypoints in the local NED frame, see `MultiRotorClient::move_on_path_async`
+#[derive(Debug, Clone)]
+pub struct MoveOnPath {
+    path: Path,
+    options: PathOptions,
+}
+
+impl MoveOnPath {
+    pub fn new(path: Path) -> Self {
+        Self {
+            path,
+            options: PathOptions::default(),
+        }
+    }
+}
+
+path_options!(MoveOnPath, drivetrain);
+
+impl MotionCommand for MoveOnPath {
+    fn validate(&self) -> NetworkResult<()> {
+        if self.path.0.is_empty() {
+            return Err(invalid("path must have at least one waypoint".to_string()));
+        }
+        for Vector3 { x, y, z } in &self.path.0 {
+            check_finite("waypoint", &[*x, *y, *z])?;
+        }
+        self.options.validate()
+    }
+}
+
+impl Start for MoveOnPath {
+    fn start(&self, client: &MultiRotorClient, _validated: Validated) -> MotionTask {
+        let o = self.options;
+        client.move_on_path_async(
+            self.path.clone(),
+            o.velocity,
+            o.timeout_sec,
+            o.drivetrain,
+            o.yaw_mode,
+            Some(o.lookahead),
+            Some(o.adaptive_lookahead),
+        )
+    }
+
+    /// Only the waypoints are checked, see `SafeMultiRotor::move_on_path_async`
+    fn start_within(&self, safe: &SafeMultiRotor, validated: Validated) -> NetworkResult<MotionTask> {
+        let waypoints = self
+            .path
+            .0
+            .iter()
+            .map(|waypoint| safe.check_position((*waypoint).into()).map(Vector3::from))
+            .collect::<NetworkResult<_>>()?;
+        let command = Self {
+            path: Path(waypoints),
+            options: self.options.within(safe)?,
+        };
+        Ok(command.start(safe.client(), validated))
+    }
+}
+
+/// Fly to a GPS location, see `MultiRotorClient::move_to_gps_async`
+#[derive(Debug, Clone, Copy)]
+pub struct MoveToGps {
+    geopoint: GeoPoint,
+    options: PathOptions,
+}
+
+impl MoveToGps {
+    pub fn new(geopoint: GeoPoint) -> Self {
+        Self {
+            geopoint,
+            options: PathOptions::default(),
+        }
+    }
+}
+
+path_options!(MoveToGps, drivetrain);
+
+impl MotionCommand for MoveToGps {
+    fn validate(&self) -> NetworkResult<()> {
+        let GeoPoint {
+            latitude,
+            longitude,
+            altitude,
+        } = self.geopoint;
+        check_finite("altitude", &[altitude])?;
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(invalid(format!(
+                "latitude must be within ±90° and longitude within ±180°, got {latitude}, {longitude}"
+            )));
+        }
+        self.options.validate()
+    }
+}
+
+impl Start for MoveToGps {
+    fn start(&self, client: &MultiRotorClient, _validated: Validated) -> MotionTask {
+        let o = self.options;
+        client.move_to_gps_async(
+            self.geopoint,
+            o.velocity,
+            o.timeout_sec,
+            o.drivetrain,
+            o.yaw_mode,
+            Some(o.lookahead),
+            Some(o.adaptive_lookahead),
+        )
+    }
+
+    fn start_within(&self, safe: &SafeMultiRotor, validated: Validated) -> NetworkResult<MotionTask> {
+        let command = Self {
+            geopoint: safe.check_geopoint(self.geopoint)?,
+            options: self.options.within(safe)?,
+        };
+        Ok(command.start(safe.client(), validated))
+    }
+}
+
+/// Climb or descend to an altitude in the local NED frame, see `MultiRotorClient::move_to_z_async`
+#[derive(Debug, Clone, Copy)]
+pub struct MoveToZ {
+    z: f32,
+    options: PathOptions,
+}
+
+impl MoveToZ {
+    pub fn new(z: f32) -> Self {
+        Self {
+            z,
+            options: PathOptions::default(),
+        }
+    }
+}
+
+path_options!(MoveToZ);
+
+impl MotionCommand for MoveToZ {
+    fn validate(&self) -> NetworkResult<()> {
+        check_finite("z", &[self.z])?;
+        self.options.validate()
+    }
+}
+
+impl Start for MoveToZ {
+    fn start(&self, client: &MultiRotorClient, _validated: Validated) -> MotionTask {
+        let o = self.options;
+        client.move_to_z_async(
+            self.z,
+            o.velocity,
+            o.timeout_sec,
+            o.yaw_mode,
+            Some(o.lookahead),
+            Some(o.adaptive_lookahead),
+        )
+    }
+
+    fn start_within(&self, safe: &SafeMultiRotor, validated: Validated) -> NetworkResult<MotionTask> {
+        let command = Self {
+            z: safe.check_z(self.z)?,
+            options: self.options.within(safe)?,
+        };
+        Ok(command.start(safe.client(), validated))
+    }
+}
+
+/// Fly with a velocity for some time, see `MultiRotorClient::move_by_velocity_async`
+#[derive(Debug, Clone, Copy)]
+pub struct MoveByVelocity {
+    velocity: Velocity3,
+    duration: Duration,
+    drivetrain: DrivetrainType,
+    yaw_mode: YawMode,
+    body_frame: bool,
+}
+
+impl MoveByVelocity {
+    /// Velocity in the local NED frame, or in the body frame with `body_frame`
+    pub fn new(velocity: Velocity3) -> Self {
+        Self {
+            velocity,
+            duration: DEFAULT_DURATION,
+            drivetrain: DrivetrainType::MaxDegreeOfFreedom,
+            yaw_mode: YawMode::new(true, 0.0),
+            body_frame: false,
+        }
+    }
+
+    /// How long to fly with the velocity, defaults to `DEFAULT_DURATION`
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Defaults to `DrivetrainType::MaxDegreeOfFreedom`
+    pub fn drivetrain(mut self, drivetrain: DrivetrainType) -> Self {
+        self.drivetrain = drivetrain;
+        self
+    }
+
+    /// Where the vehicle faces, defaults to holding its current yaw
+    pub fn yaw(mut self, yaw_mode: YawMode) -> Self {
+        self.yaw_mode = yaw_mode;
+        self
+    }
+
+    /// Interpret the velocity in the vehicle's body frame instead of the local NED frame
+    pub fn body_frame(mut self) -> Self {
+        self.body_frame = true;
+        self
+    }
+}
+
+impl MotionCommand for MoveByVelocity {
+    fn validate(&self) -> NetworkResult<()> {
+        check_finite("velocity", &[self.velocity.vx, self.velocity.vy, self.velocity.vz])?;
+        check_positive("duration", self.duration.as_secs_f32())?;
+        check_finite("yaw", &[self.yaw_mode.yaw_or_rate()])
+    }
+}
+
+impl Start for MoveByVelocity {
+    fn start(&self, client: &MultiRotorClient, _validated: Validated) -> MotionTask {
+        let duration = self.duration.as_secs_f32();
+        match self.body_frame {
+            true => client.move_by_velocity_body_frame_async(self.velocity, duration, self.drivetrain, self.yaw_mode),
+            false => client.move_by_velocity_async(self.velocity, duration, self.drivetrain, self.yaw_mode),
+        }
+    }
+
+    fn start_within(&self, safe: &SafeMultiRotor, validated: Validated) -> NetworkResult<MotionTask> {
+        let command = Self {
+            velocity: safe.check_velocity(self.velocity.into())?.into(),
+            ..*self
+        };
+        Ok(command.start(safe.client(), validated))
+    }
+}
+
+/// Fly with a horizontal velocity at an altitude, see `MultiRotorClient::move_by_velocity_z_async`
+#[derive(Debug, Clone, Copy)]
+pub struct MoveByVelocityZ {
+    velocity: Velocity2,
+    z: f32,
+    duration: Duration,
+    drivetrain: DrivetrainType,
+    yaw_mode: YawMode,
+    body_frame: bool,
+}
+
+impl MoveByVelocityZ {
+    /// Horizontal velocity in the local NED frame, or in the body frame with `body_frame`
+    pub fn new(velocity: Velocity2, z: f32) -> Self {
+        Self {
+            velocity,
+            z,
+            duration: DEFAULT_DURATION,
+            drivetrain: DrivetrainType::MaxDegreeOfFreedom,
+            yaw_mode: YawMode::new(true, 0.0),
+            body_frame: false,
+        }
+    }
+
+    /// How long to fly with the velocity, defaults to `DEFAULT_DURATION`
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Defaults to `DrivetrainType::MaxDegreeOfFreedom`
+    pub fn drivetrain(mut self, drivetrain: DrivetrainType) -> Self {
+        self.drivetrain = drivetrain;
+        self
+    }
+
+    /// Where the vehicle faces, defaults to holding its current yaw
+    pub fn yaw(mut self, yaw_mode: YawMode) -> Self {
+        self.yaw_mode = yaw_mode;
+        self
+    }
+
+    /// Interpret the velocity in the vehicle's body frame instead of the local NED frame
+    pub fn body_frame(mut self) -> Self {
+        self.body_frame = true;
+        self
+    }
+}
+
+impl MotionCommand for MoveByVelocityZ {
+    fn validate(&self) -> NetworkResult<()> {
+        check_finite("velocity", &[self.velocity.vx, self.velocity.vy, self.z])?;
+        check_positive("duration", self.duration.as_secs_f32())?;
+        check_finite("yaw", &[self.yaw_mode.yaw_or_rate()])
+    }
+}
+
+impl Start for MoveByVelocityZ {
+    fn start(&self, client: &MultiRotorClient, _validated: Validated) -> MotionTask {
+        let duration = self.duration.as_secs_f32();
+        match self.body_frame {
+            true => client.move_by_velocity_z_body_frame_async(
+                self.velocity,
+                self.z,
+                duration,
+                self.drivetrain,
+                self.yaw_mode,
+            ),
+            false => client.move_by_velocity_z_async(self.velocity, self.z, duration, self.drivetrain, self.yaw_mode),
+        }
+    }
+
+    fn start_within(&self, safe: &SafeMultiRotor, validated: Validated) -> NetworkResult<MotionTask> {
+        let limited = safe.check_velocity(Vector3::new(self.velocity.vx, self.velocity.vy, 0.0))?;
+        let command = Self {
+            velocity: Velocity2::new(limited.x, limited.y),
+            z: safe.check_z(self.z)?,
+            ..*self
+        };
+        Ok(command.start(safe.client(), validated))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use async_std::task;
+    use rmpv::Value;
+
+    use super::{MotionCommand, MoveByVelocity, MoveOnPath, MoveToGps, MoveToPosition};
+    use crate::clients::test_server::TestServer;
+    use crate::{GeoPoint, MultiRotorClient, NetworkError, Path, Position3, Velocity3};
+
+    #[test]
+    fn test_validation() {
+        let position = Position3::new(1.0, 2.0, -3.0);
+        assert!(MoveToPosition::new(position).validate().is_ok());
+        assert!(MoveToPosition::new(position).velocity(0.0).validate().is_err());
+        assert!(MoveToPosition::new(position)
+            .adaptive_lookahead(2.0)
+            .validate()
+            .is_err());
+        assert!(MoveToPosition::new(Position3::new(f32::NAN, 0.0, 0.0))
+            .validate()
+            .is_err());
+        assert!(MoveOnPath::new(Path(vec![])).validate().is_err());
+        assert!(MoveToGps::new(GeoPoint::new(91.0, 0.0, 100.0)).validate().is_err());
+        assert!(MoveByVelocity::new(Velocity3::new(1.0, 0.0, 0.0))
+            .duration(Duration::ZERO)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_execute_applies_defaults() {
+        let params = Arc::new(Mutex::new(vec![]));
+        let recorded = Arc::clone(&params);
+        let server = TestServer::spawn(move |request| {
+            if request.method == "moveToPosition" {
+                *recorded.lock().unwrap() = request.params.clone();
+            }
+            Ok(Value::Boolean(true))
+        });
+
+        task::block_on(async {
+            let client = MultiRotorClient::connect(&server.addr, "Drone1").await.unwrap();
+
+            let invalid = MoveToPosition::new(Position3::new(1.0, 2.0, -3.0)).velocity(-1.0);
+            assert!(matches!(
+                client.execute(&invalid),
+                Err(NetworkError::ValidationError { .. })
+            ));
+
+            let command = MoveToPosition::new(Position3::new(1.0, 2.0, -3.0)).velocity(3.0);
+            assert!(client.execute(&command).unwrap().await.unwrap());
+        });
+
+        let params = params.lock().unwrap();
+        assert_eq!(params[3], Value::F32(3.0));
+        // lookahead and adaptive lookahead
+        assert_eq!(params[7], Value::F32(-1.0));
+        assert_eq!(params[8], Value::F32(1.0));
+        assert_eq!(params[9], Value::from("Drone1"));
+    }
+}
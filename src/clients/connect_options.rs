@@ -0,0 +1,406 @@
+use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::error::NetworkResult;
+use crate::NetworkError;
+
+use super::airsim_client::AirsimClient;
+use super::channel::Channel;
+
+/// Address of the AirSim RPC server when running on the same machine
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:41451";
+
+/// How often and how fast connecting is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// total number of connection attempts, at least 1
+    pub max_attempts: u32,
+    /// delay before the first retry, doubled for every further retry
+    pub backoff: Duration,
+    /// upper bound of the delay between retries
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Connect once and fail on the first error
+    pub fn none() -> Self {
+        Self::new(1, Duration::ZERO)
+    }
+
+    pub fn new(max_attempts: u32, backoff: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            backoff,
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+
+    /// Delay before retry number `retry`, starting at 1
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(retry.saturating_sub(1));
+        self.backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+/// Options for connecting an `AirsimClient`
+///
+/// Start from `ConnectOptions::default()` or from `ConnectOptions::from_env()`, which reads
+/// these environment variables on top of the defaults:
+///
+/// | Variable                    | Option                    | Example              |
+/// |-----------------------------|---------------------------|----------------------|
+/// | `AIRSIM_ADDRESS`            | `with_address`            | `192.168.1.10:41451` |
+/// | `AIRSIM_VEHICLES`           | `with_vehicle_names`      | `Drone1,Drone2`      |
+/// | `AIRSIM_CONNECT_TIMEOUT`    | `with_connect_timeout`    | `5` (seconds)        |
+/// | `AIRSIM_RPC_TIMEOUT`        | `with_rpc_timeout`        | `0.5` (seconds)      |
+/// | `AIRSIM_CONNECT_RETRIES`    | `with_retry`              | `5` (attempts)       |
+/// | `AIRSIM_RETRY_BACKOFF`      | `with_retry`              | `0.5` (seconds)      |
+/// | `AIRSIM_ENABLE_API_CONTROL` | `with_api_control`        | `true`               |
+/// | `AIRSIM_ARM`                | `with_arm`                | `false`              |
+/// | `AIRSIM_TCP_NODELAY`        | `with_tcp_nodelay`        | `true`               |
+/// | `AIRSIM_DEDICATED_CHANNELS` | `with_dedicated_channels` | `imagery,telemetry`  |
+/// | `AIRSIM_VERSION_CHECK`      | `with_version_check`      | `true`               |
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use airsim_client::{ConnectOptions, NetworkResult};
+/// # async fn run() -> NetworkResult<()> {
+/// let client = ConnectOptions::from_env()?
+///     .with_rpc_timeout(Some(Duration::from_secs(2)))
+///     .connect()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+    address: String,
+    vehicle_names: Vec<String>,
+    connect_timeout: Duration,
+    rpc_timeout: Option<Duration>,
+    retry: RetryPolicy,
+    enable_api_control: bool,
+    arm: bool,
+    tcp_nodelay: bool,
+    dedicated_channels: Vec<Channel>,
+    version_check: bool,
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        Self {
+            address: DEFAULT_ADDRESS.to_string(),
+            vehicle_names: vec![String::new()],
+            connect_timeout: Duration::from_secs(10),
+            rpc_timeout: None,
+            retry: RetryPolicy::none(),
+            enable_api_control: true,
+            arm: false,
+            tcp_nodelay: true,
+            dedicated_channels: vec![],
+            version_check: false,
+        }
+    }
+}
+
+impl ConnectOptions {
+    /// Default options overridden by the `AIRSIM_*` environment variables
+    pub fn from_env() -> NetworkResult<Self> {
+        Self::from_vars(|name| env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> NetworkResult<Self> {
+        let mut options = Self::default();
+
+        if let Some(address) = var("AIRSIM_ADDRESS") {
+            options.address = address;
+        }
+        if let Some(vehicles) = var("AIRSIM_VEHICLES") {
+            options.vehicle_names = vehicles.split(',').map(|v| v.trim().to_string()).collect();
+        }
+        if let Some(secs) = parse_var::<f64>(&var, "AIRSIM_CONNECT_TIMEOUT")? {
+            options.connect_timeout = seconds("AIRSIM_CONNECT_TIMEOUT", secs)?;
+        }
+        if let Some(secs) = parse_var::<f64>(&var, "AIRSIM_RPC_TIMEOUT")? {
+            options.rpc_timeout = Some(seconds("AIRSIM_RPC_TIMEOUT", secs)?);
+        }
+        if let Some(attempts) = parse_var::<u32>(&var, "AIRSIM_CONNECT_RETRIES")? {
+            options.retry = RetryPolicy::new(attempts, options.retry.backoff);
+        }
+        if let Some(secs) = parse_var::<f64>(&var, "AIRSIM_RETRY_BACKOFF")? {
+            options.retry.backoff = seconds("AIRSIM_RETRY_BACKOFF", secs)?;
+        }
+        if let Some(enable) = parse_var(&var, "AIRSIM_ENABLE_API_CONTROL")? {
+            options.enable_api_control = enable;
+        }
+        if let Some(arm) = parse_var(&var, "AIRSIM_ARM")? {
+            options.arm = arm;
+        }
+        if let Some(nodelay) = parse_var(&var, "AIRSIM_TCP_NODELAY")? {
+            options.tcp_nodelay = nodelay;
+        }
+        if let Some(channels) = var("AIRSIM_DEDICATED_CHANNELS") {
+            options.dedicated_channels = channels
+                .split(',')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(|c| match c.to_ascii_lowercase().as_str() {
+                    "control" => Ok(Channel::Control),
+                    "telemetry" => Ok(Channel::Telemetry),
+                    "imagery" => Ok(Channel::Imagery),
+                    _ => Err(env_error("AIRSIM_DEDICATED_CHANNELS", c)),
+                })
+                .collect::<NetworkResult<_>>()?;
+        }
+        if let Some(version_check) = parse_var(&var, "AIRSIM_VERSION_CHECK")? {
+            options.version_check = version_check;
+        }
+
+        Ok(options)
+    }
+
+    /// Address of the AirSim RPC server, e.g. `127.0.0.1:41451`
+    pub fn with_address(mut self, address: &str) -> Self {
+        self.address = address.to_string();
+        self
+    }
+
+    /// Single vehicle to enable API control for and, if enabled, arm
+    pub fn with_vehicle_name(self, vehicle_name: &str) -> Self {
+        self.with_vehicle_names(&[vehicle_name])
+    }
+
+    /// Vehicles to enable API control for and, if enabled, arm. The first one is used by
+    /// `MultiRotorClient::connect_with` and `CarClient::connect_with`
+    pub fn with_vehicle_names(mut self, vehicle_names: &[&str]) -> Self {
+        self.vehicle_names = vehicle_names.iter().map(|v| v.to_string()).collect();
+        self
+    }
+
+    /// Max duration of a single connection attempt, including the initial ping
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Max duration of each RPC call, or `None` to wait as long as the server takes
+    pub fn with_rpc_timeout(mut self, rpc_timeout: Option<Duration>) -> Self {
+        self.rpc_timeout = rpc_timeout;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Enable API control for all vehicles after connecting
+    pub fn with_api_control(mut self, enable_api_control: bool) -> Self {
+        self.enable_api_control = enable_api_control;
+        self
+    }
+
+    /// Arm all vehicles after connecting
+    pub fn with_arm(mut self, arm: bool) -> Self {
+        self.arm = arm;
+        self
+    }
+
+    /// Disable Nagle's algorithm, so small commands are sent without delay
+    pub fn with_tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.tcp_nodelay = tcp_nodelay;
+        self
+    }
+
+    /// Channels that get their own connection, see `AirsimClient::connect_with_channels`
+    pub fn with_dedicated_channels(mut self, channels: &[Channel]) -> Self {
+        self.dedicated_channels = channels.to_vec();
+        self
+    }
+
+    /// Check that the client and server versions support each other after connecting, see
+    /// `AirsimClient::check_version`
+    pub fn with_version_check(mut self, version_check: bool) -> Self {
+        self.version_check = version_check;
+        self
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn vehicle_names(&self) -> &[String] {
+        &self.vehicle_names
+    }
+
+    pub fn connect_timeout(&self) -> Duration {
+        self.connect_timeout
+    }
+
+    pub fn rpc_timeout(&self) -> Option<Duration> {
+        self.rpc_timeout
+    }
+
+    pub fn retry(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    pub fn api_control(&self) -> bool {
+        self.enable_api_control
+    }
+
+    pub fn arm(&self) -> bool {
+        self.arm
+    }
+
+    pub fn tcp_nodelay(&self) -> bool {
+        self.tcp_nodelay
+    }
+
+    pub fn dedicated_channels(&self) -> &[Channel] {
+        &self.dedicated_channels
+    }
+
+    pub fn version_check(&self) -> bool {
+        self.version_check
+    }
+
+    /// Connect an `AirsimClient` with these options
+    pub async fn connect(&self) -> NetworkResult<AirsimClient> {
+        AirsimClient::connect_with(self).await
+    }
+}
+
+fn parse_var<T: FromStr>(var: &impl Fn(&str) -> Option<String>, name: &str) -> NetworkResult<Option<T>> {
+    match var(name) {
+        Some(value) => value.trim().parse().map(Some).map_err(|_| env_error(name, &value)),
+        None => Ok(None),
+    }
+}
+
+fn seconds(name: &str, secs: f64) -> NetworkResult<Duration> {
+    if secs.is_finite() && secs >= 0.0 {
+        Ok(Duration::from_secs_f64(secs))
+    } else {
+        Err(env_error(name, &secs.to_string()))
+    }
+}
+
+fn env_error(name: &str, value: &str) -> NetworkError {
+    NetworkError::Parse {
+        message: format!("invalid value {value:?} for environment variable {name}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use async_std::task;
+    use rmpv::Value;
+
+    use super::{ConnectOptions, RetryPolicy, DEFAULT_ADDRESS};
+    use crate::clients::test_server::TestServer;
+    use crate::{Channel, NetworkError};
+
+    fn from_vars(vars: &[(&str, &str)]) -> crate::NetworkResult<ConnectOptions> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        ConnectOptions::from_vars(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_options_from_env() {
+        let options = from_vars(&[]).unwrap();
+        assert_eq!(options.address(), DEFAULT_ADDRESS);
+        assert_eq!(options.vehicle_names(), [String::new()]);
+
+        let options = from_vars(&[
+            ("AIRSIM_ADDRESS", "10.0.0.2:41451"),
+            ("AIRSIM_VEHICLES", "Drone1, Drone2"),
+            ("AIRSIM_RPC_TIMEOUT", "0.5"),
+            ("AIRSIM_CONNECT_RETRIES", "3"),
+            ("AIRSIM_ARM", "true"),
+            ("AIRSIM_DEDICATED_CHANNELS", "imagery"),
+            ("AIRSIM_VERSION_CHECK", "true"),
+        ])
+        .unwrap();
+        assert_eq!(options.address(), "10.0.0.2:41451");
+        assert_eq!(options.vehicle_names(), ["Drone1", "Drone2"]);
+        assert_eq!(options.rpc_timeout(), Some(Duration::from_millis(500)));
+        assert_eq!(options.retry().max_attempts, 3);
+        assert!(options.arm());
+        assert_eq!(options.dedicated_channels(), [Channel::Imagery]);
+        assert!(options.version_check());
+
+        assert!(from_vars(&[("AIRSIM_ARM", "yes please")]).is_err());
+        assert!(from_vars(&[("AIRSIM_RPC_TIMEOUT", "-1")]).is_err());
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let retry = RetryPolicy::new(5, Duration::from_millis(100));
+        assert_eq!(retry.delay(1), Duration::from_millis(100));
+        assert_eq!(retry.delay(3), Duration::from_millis(400));
+        assert_eq!(retry.delay(40), retry.max_backoff);
+    }
+
+    #[test]
+    fn test_connect_with_options() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let server_calls = Arc::clone(&calls);
+        let server = TestServer::spawn(move |request| {
+            if request.method == "slow" {
+                thread::sleep(Duration::from_millis(500));
+            }
+            server_calls.lock().unwrap().push(request.method.clone());
+            Ok(Value::Boolean(true))
+        });
+
+        let options = ConnectOptions::default()
+            .with_address(&server.addr)
+            .with_vehicle_names(&["Drone1", "Drone2"])
+            .with_arm(true)
+            .with_rpc_timeout(Some(Duration::from_millis(50)));
+
+        task::block_on(async {
+            let client = options.connect().await.unwrap();
+            assert_eq!(
+                *calls.lock().unwrap(),
+                ["ping", "enableApiControl", "armDisarm", "enableApiControl", "armDisarm"]
+            );
+
+            assert!(matches!(
+                client.call_raw("slow", vec![]).await,
+                Err(NetworkError::Timeout { .. })
+            ));
+        });
+    }
+
+    #[test]
+    fn test_connect_checks_version() {
+        let server = TestServer::spawn(|request| match request.method.as_str() {
+            "getServerVersion" => Ok(Value::from(1)),
+            "getMinRequiredClientVersion" => Ok(Value::from(2)),
+            _ => Ok(Value::Boolean(true)),
+        });
+        let options = ConnectOptions::default().with_address(&server.addr);
+
+        task::block_on(async {
+            assert!(options.connect().await.is_ok());
+            assert!(matches!(
+                options.with_version_check(true).connect().await,
+                Err(NetworkError::IncompatibleVersion { server_version: 1, .. })
+            ));
+        });
+    }
+}
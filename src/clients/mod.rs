@@ -1,3 +1,16 @@
 pub(crate) mod airsim_client;
+pub(crate) mod batch;
+pub(crate) mod capabilities;
 pub(crate) mod car_client;
+pub(crate) mod channel;
+pub(crate) mod commands;
+pub(crate) mod connect_options;
+pub(crate) mod motion;
 pub(crate) mod multi_rotor_client;
+pub(crate) mod offboard;
+pub(crate) mod safe_multi_rotor;
+pub(crate) mod shutdown;
+pub(crate) mod stepped_sim;
+#[cfg(test)]
+pub(crate) mod test_server;
+pub(crate) mod watchdog;
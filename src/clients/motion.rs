@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use async_std::task::{self, JoinHandle};
+use futures::channel::oneshot;
+use futures::future::{FutureExt, Shared};
+use rmpv::Value;
+
+use crate::error::NetworkResult;
+
+use super::airsim_client::AirsimClient;
+
+/// Resolves once a motion command or cancellation is queued, or was dropped without being queued
+type Queued = Shared<oneshot::Receiver<()>>;
+
+/// Tracks the motion command each vehicle of a client is executing
+///
+/// Motion commands and cancellations are queued in the order they are created, each waits for
+/// its predecessor before sending, so the server sees them in the order of the calls.
+#[derive(Default)]
+pub(crate) struct MotionTracker {
+    next_id: AtomicU64,
+    last_queued: Mutex<Option<Queued>>,
+    current: Mutex<HashMap<String, u64>>,
+}
+
+impl MotionTracker {
+    /// Take a place in the queue, returns the predecessor to wait for and the sender that
+    /// marks this place as queued
+    fn enqueue(&self) -> (Option<Queued>, oneshot::Sender<()>, Queued) {
+        let (sender, receiver) = oneshot::channel();
+        let queued = receiver.shared();
+        let previous = self.last_queued.lock().unwrap().replace(queued.clone());
+        (previous, sender, queued)
+    }
+
+    fn is_current(&self, vehicle_name: &str, id: u64) -> bool {
+        self.current.lock().unwrap().get(vehicle_name) == Some(&id)
+    }
+}
+
+#[derive(Default)]
+struct TaskState {
+    queued: AtomicBool,
+    finished: AtomicBool,
+}
+
+/// Handle to a running `*_async` motion command, like `take_off_async` or `move_to_position_async`
+///
+/// The command is sent as soon as the handle is created. Awaiting the handle returns the result
+/// of the command once the simulator finishes it, as the command itself used to. The RPC timeout
+/// of the client does not apply, the command's own timeout bounds it. Dropping the handle does
+/// not stop the vehicle.
+///
+/// A vehicle executes one motion command at a time. Starting another motion command for the same
+/// vehicle supersedes the running one: the simulator stops it, its handle resolves and
+/// `is_superseded` returns true. Cancelling a finished or superseded task does nothing, so it
+/// never stops the command that replaced it.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use airsim_client::{MultiRotorClient, NetworkResult, YawMode};
+/// # use async_std::task;
+/// # async fn run(client: MultiRotorClient) -> NetworkResult<()> {
+/// client.take_off_async(20.0).await?;
+///
+/// let climb = client.move_to_z_async(-50.0, 2.0, 60.0, YawMode::new(false, 0.0), None, None);
+/// task::sleep(Duration::from_secs(5)).await;
+/// if !climb.is_finished() {
+///     climb.cancel().await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct MotionTask {
+    id: u64,
+    method: String,
+    vehicle_name: String,
+    client: AirsimClient,
+    state: Arc<TaskState>,
+    queued: Queued,
+    handle: JoinHandle<NetworkResult<bool>>,
+}
+
+impl MotionTask {
+    pub(crate) fn start(client: &AirsimClient, vehicle_name: &str, method: &str, params: Vec<Value>) -> Self {
+        let tracker = client.motion_tracker();
+        let id = tracker.next_id.fetch_add(1, Ordering::Relaxed);
+        let (previous, queued_sender, queued) = tracker.enqueue();
+        let state = Arc::new(TaskState::default());
+
+        let handle = task::spawn({
+            let client = client.clone();
+            let vehicle_name = vehicle_name.to_string();
+            let method = method.to_string();
+            let state = Arc::clone(&state);
+
+            async move {
+                if let Some(previous) = previous {
+                    let _ = previous.await;
+                }
+
+                let result = async {
+                    let pending = client.send_rpc(method, Some(params)).await?;
+                    client.motion_tracker().current.lock().unwrap().insert(vehicle_name, id);
+                    state.queued.store(true, Ordering::Release);
+                    let _ = queued_sender.send(());
+
+                    // the command runs as long as its own timeout, not bounded by the RPC timeout
+                    pending.completion().await
+                }
+                .await
+                .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true));
+
+                state.finished.store(true, Ordering::Release);
+                result
+            }
+        });
+
+        Self {
+            id,
+            method: method.to_string(),
+            vehicle_name: vehicle_name.to_string(),
+            client: client.clone(),
+            state,
+            queued,
+            handle,
+        }
+    }
+
+    /// Name of the RPC method of the command, e.g. `takeoff`
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn vehicle_name(&self) -> &str {
+        &self.vehicle_name
+    }
+
+    /// Returns true once the simulator answered the command
+    pub fn is_finished(&self) -> bool {
+        self.state.finished.load(Ordering::Acquire)
+    }
+
+    /// Returns true if a later motion command for the same vehicle was sent
+    pub fn is_superseded(&self) -> bool {
+        self.state.queued.load(Ordering::Acquire)
+            && !self.client.motion_tracker().is_current(&self.vehicle_name, self.id)
+    }
+
+    /// Wait until the command was written to the connection, returns false if it could not be sent
+    pub(crate) async fn sent(&self) -> bool {
+        self.queued.clone().await.is_ok()
+    }
+
+    /// Stop the command with `cancelLastTask` and wait until the simulator stopped it
+    ///
+    /// Returns false without sending anything if the command already finished, was superseded or
+    /// could not be sent.
+    pub async fn cancel(self) -> NetworkResult<bool> {
+        let tracker = self.client.motion_tracker();
+        let (previous, queued_sender, _) = tracker.enqueue();
+        if let Some(previous) = previous {
+            let _ = previous.await;
+        }
+
+        let is_running =
+            self.queued.clone().await.is_ok() && !self.is_finished() && tracker.is_current(&self.vehicle_name, self.id);
+        if !is_running {
+            return Ok(false);
+        }
+
+        let vehicle_name = Value::from(self.vehicle_name.as_str());
+        let pending = self
+            .client
+            .send_rpc("cancelLastTask".into(), Some(vec![vehicle_name]))
+            .await;
+        let _ = queued_sender.send(());
+
+        let cancelled = pending?.response().await.map(|response| response.result.is_ok())?;
+        // the command returns once the simulator stopped it, its result no longer matters
+        let _ = self.handle.await;
+        Ok(cancelled)
+    }
+}
+
+impl Future for MotionTask {
+    type Output = NetworkResult<bool>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.handle).poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use async_std::task;
+    use msgpack_rpc::message::{Message, Response};
+    use rmpv::Value;
+
+    use crate::{ConnectOptions, MultiRotorClient, YawMode};
+
+    fn respond(stream: &mut TcpStream, id: u32, result: bool) {
+        let response = Message::Response(Response {
+            id,
+            result: Ok(Value::Boolean(result)),
+        });
+        stream.write_all(&response.pack().unwrap()).unwrap();
+    }
+
+    /// Holds motion commands until the next one or a cancellation arrives, which stops them
+    /// with `false` like the simulator does, and records the methods of both
+    fn simulator() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let calls = Arc::new(Mutex::new(vec![]));
+
+        let recorded = Arc::clone(&calls);
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = stream.try_clone().unwrap();
+            let mut running = None;
+
+            while let Ok(Message::Request(request)) = Message::decode(&mut reader) {
+                match request.method.as_str() {
+                    "takeoff" | "moveToZ" | "cancelLastTask" => {
+                        recorded.lock().unwrap().push(request.method.clone());
+                        if let Some(id) = running.take() {
+                            respond(&mut stream, id, false);
+                        }
+                        if request.method == "cancelLastTask" {
+                            respond(&mut stream, request.id, true);
+                        } else {
+                            running = Some(request.id);
+                        }
+                    }
+                    _ => respond(&mut stream, request.id, true),
+                }
+            }
+        });
+
+        (addr, calls)
+    }
+
+    #[test]
+    fn test_cancel_running_task() {
+        let (addr, calls) = simulator();
+
+        task::block_on(async {
+            let options = ConnectOptions::default()
+                .with_address(&addr)
+                .with_vehicle_name("Drone1")
+                .with_rpc_timeout(Some(Duration::from_millis(200)));
+            let client = MultiRotorClient::connect_with(&options).await.unwrap();
+            let take_off = client.take_off_async(20.0);
+            assert_eq!(take_off.method(), "takeoff");
+
+            // still flying after the RPC timeout
+            task::sleep(Duration::from_millis(400)).await;
+            assert!(!take_off.is_finished());
+
+            assert!(take_off.cancel().await.unwrap());
+        });
+
+        assert_eq!(*calls.lock().unwrap(), ["takeoff", "cancelLastTask"]);
+    }
+
+    #[test]
+    fn test_superseded_task_is_not_cancelled() {
+        let (addr, calls) = simulator();
+
+        task::block_on(async {
+            let client = MultiRotorClient::connect(&addr, "Drone1").await.unwrap();
+            let mut take_off = client.take_off_async(20.0);
+            let climb = client.move_to_z_async(-50.0, 2.0, 60.0, YawMode::new(false, 0.0), None, None);
+
+            assert!(!(&mut take_off).await.unwrap());
+            assert!(take_off.is_finished() && take_off.is_superseded());
+            assert!(!take_off.cancel().await.unwrap());
+            assert!(!climb.is_superseded());
+            assert!(climb.cancel().await.unwrap());
+        });
+
+        assert_eq!(*calls.lock().unwrap(), ["takeoff", "moveToZ", "cancelLastTask"]);
+    }
+}
@@ -1,9 +1,9 @@
-use core::panic;
-
 use msgpack_rpc::Utf8String;
 use rmpv::Value;
 
+use crate::codec::{decode_error, FromMsgpack, ToMsgpack};
 use crate::types::drive_train::DrivetrainType;
+use crate::types::frame::LocalFrame;
 use crate::types::gains::AngularControllerGains;
 use crate::types::geopoint::GeoPoint;
 use crate::types::image::ImageRequests;
@@ -11,26 +11,116 @@ use crate::types::multi_rotor_state::MultiRotorState;
 use crate::types::pose::{Orientation2, Orientation3, Position3, Velocity3};
 use crate::types::pwm::PWM;
 use crate::types::rc_data::RCData;
+use crate::types::unit_interval::UnitInterval;
 use crate::types::yaw_mode::YawMode;
 use crate::{error::NetworkResult, NetworkError};
 use crate::{CompressedImage, ImageType, LinearControllerGains, Path, RotorStates, Velocity2};
 
 use super::airsim_client::AirsimClient;
-
+use super::capabilities::Capabilities;
+use super::channel::Channel;
+use super::commands::sealed::Validated;
+use super::commands::MotionCommand;
+use super::connect_options::ConnectOptions;
+use super::motion::MotionTask;
+use super::shutdown::{ReleaseAction, ShutdownGuard};
+
+#[derive(Clone)]
 pub struct MultiRotorClient {
     airsim_client: AirsimClient,
-    vehicle_name: &'static str,
+    vehicle_name: String,
 }
 
 impl MultiRotorClient {
-    pub async fn connect(addrs: &str, vehicle_name: &'static str) -> NetworkResult<Self> {
+    pub async fn connect(addrs: &str, vehicle_name: &str) -> NetworkResult<Self> {
         let airsim_client = AirsimClient::connect(addrs, vehicle_name).await?;
         Ok(Self {
             airsim_client,
-            vehicle_name,
+            vehicle_name: vehicle_name.to_string(),
+        })
+    }
+
+    /// Connect with a dedicated TCP connection for each of the given channels,
+    /// see `AirsimClient::connect_with_channels`
+    pub async fn connect_with_channels(addrs: &str, vehicle_name: &str, dedicated: &[Channel]) -> NetworkResult<Self> {
+        let airsim_client = AirsimClient::connect_with_channels(addrs, vehicle_name, dedicated).await?;
+        Ok(Self {
+            airsim_client,
+            vehicle_name: vehicle_name.to_string(),
+        })
+    }
+
+    /// Connect with the given options, see `AirsimClient::connect_with`
+    ///
+    /// Commands are sent to the first vehicle of the options
+    pub async fn connect_with(options: &ConnectOptions) -> NetworkResult<Self> {
+        let airsim_client = AirsimClient::connect_with(options).await?;
+        Ok(Self {
+            airsim_client,
+            vehicle_name: options.vehicle_names().first().cloned().unwrap_or_default(),
         })
     }
 
+    /// Access the underlying `AirsimClient`, e.g. to drive a `SteppedSim`
+    #[inline(always)]
+    pub fn airsim_client(&self) -> &AirsimClient {
+        &self.airsim_client
+    }
+
+    /// Name of the vehicle this client sends its commands to
+    #[inline(always)]
+    pub fn vehicle_name(&self) -> &str {
+        &self.vehicle_name
+    }
+
+    /// Guard that releases this client's vehicle on drop or signal, see `ShutdownGuard`
+    ///
+    /// args:
+    ///     action (ReleaseAction): What to do with the vehicle before disarming it, e.g. `ReleaseAction::Hover`
+    pub fn shutdown_guard(&self, action: ReleaseAction) -> ShutdownGuard {
+        ShutdownGuard::new(&self.airsim_client, &[&self.vehicle_name], action)
+    }
+
+    /// Call any RPC method of the AirSim server, see `AirsimClient::call_raw`
+    ///
+    /// The vehicle name is not added to the parameters, pass `vehicle_name()` where the method expects it
+    #[inline(always)]
+    pub async fn call_raw(&self, method: &str, params: Vec<Value>) -> NetworkResult<Value> {
+        self.airsim_client.call_raw(method, params).await
+    }
+
+    /// Call any RPC method of the AirSim server with serde typed parameters and result,
+    /// see `AirsimClient::call`
+    ///
+    /// The vehicle name is not added to the parameters, pass `vehicle_name()` where the method expects it
+    #[cfg(feature = "serde")]
+    #[inline(always)]
+    pub async fn call<P, R>(&self, method: &str, params: P) -> NetworkResult<R>
+    where
+        P: crate::RpcParams,
+        R: serde::de::DeserializeOwned,
+    {
+        self.airsim_client.call(method, params).await
+    }
+
+    /// Send a notification, see `AirsimClient::notify`
+    #[inline(always)]
+    pub async fn notify(&self, method: &str, params: Vec<Value>) -> NetworkResult<()> {
+        self.airsim_client.notify(method, params).await
+    }
+
+    /// Handle notifications pushed by the server, see `AirsimClient::on_notification`
+    #[inline(always)]
+    pub fn on_notification(&self, method: &str, handler: impl Fn(&[Value]) + Send + Sync + 'static) {
+        self.airsim_client.on_notification(method, handler)
+    }
+
+    /// Answer requests sent by the server, see `AirsimClient::on_request`
+    #[inline(always)]
+    pub fn on_request(&self, method: &str, handler: impl Fn(&[Value]) -> Result<Value, Value> + Send + Sync + 'static) {
+        self.airsim_client.on_request(method, handler)
+    }
+
     /// Reset the vehicle to its original starting state
     ///
     /// Note that you must call `enable_api_control` and `arm_disarm` again after the call to reset
@@ -51,6 +141,42 @@ impl MultiRotorClient {
         self.airsim_client.confirm_connection().await
     }
 
+    /// Get AirSim server version
+    #[inline(always)]
+    pub async fn get_server_version(&self) -> NetworkResult<u64> {
+        self.airsim_client.get_server_version().await
+    }
+
+    /// Check that the client and server versions support each other, see `AirsimClient::check_version`
+    #[inline(always)]
+    pub async fn check_version(&self) -> NetworkResult<u64> {
+        self.airsim_client.check_version().await
+    }
+
+    /// Detect which optional RPC methods the connected server supports
+    #[inline(always)]
+    pub async fn probe_capabilities(&self) -> NetworkResult<Capabilities> {
+        self.airsim_client.probe_capabilities().await
+    }
+
+    /// Start recording data according to the `Recording` section of settings.json
+    #[inline(always)]
+    pub async fn start_recording(&self) -> NetworkResult<()> {
+        self.airsim_client.start_recording().await
+    }
+
+    /// Stop the recording started with `start_recording`
+    #[inline(always)]
+    pub async fn stop_recording(&self) -> NetworkResult<()> {
+        self.airsim_client.stop_recording().await
+    }
+
+    /// Returns True if recording is currently active
+    #[inline(always)]
+    pub async fn is_recording(&self) -> NetworkResult<bool> {
+        self.airsim_client.is_recording().await
+    }
+
     /// Enables or disables API control for vehicle corresponding to vehicle_name
     ///
     /// args:
@@ -59,7 +185,7 @@ impl MultiRotorClient {
     #[inline(always)]
     pub async fn enable_api_control(&self, is_enabled: bool) -> NetworkResult<bool> {
         self.airsim_client
-            .enable_api_control(is_enabled, Some(self.vehicle_name))
+            .enable_api_control(is_enabled, Some(&self.vehicle_name))
             .await
     }
 
@@ -73,14 +199,14 @@ impl MultiRotorClient {
     #[inline(always)]
     pub async fn is_api_control_enabled(&self, is_enabled: bool) -> NetworkResult<bool> {
         self.airsim_client
-            .is_api_control_enabled(is_enabled, Some(self.vehicle_name))
+            .is_api_control_enabled(is_enabled, Some(&self.vehicle_name))
             .await
     }
 
     /// Cancel previous Async task
     #[inline(always)]
     pub async fn cancel_last_task(&self) -> NetworkResult<bool> {
-        self.airsim_client.cancel_last_task(Some(self.vehicle_name)).await
+        self.airsim_client.cancel_last_task(Some(&self.vehicle_name)).await
     }
 
     /// Returns true if API control is established.
@@ -93,24 +219,40 @@ impl MultiRotorClient {
     ///     vehicle_name (Option<String>): Name of the vehicle to send this command to
     #[inline(always)]
     pub async fn arm_disarm(&self, arm: bool) -> NetworkResult<bool> {
-        self.airsim_client.arm_disarm(arm, Some(self.vehicle_name)).await
+        self.airsim_client.arm_disarm(arm, Some(&self.vehicle_name)).await
     }
 
     /// High level control API
     ///
     /// Hover the vehicle in place
-    pub async fn hover_async(&self) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
+    pub fn hover_async(&self) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "hover",
+            vec![Value::String(vehicle_name)],
+        )
+    }
 
-        self.airsim_client
-            .unary_rpc("hover".into(), Some(vec![Value::String(vehicle_name)]))
-            .await
-            .map(|response| response.result.is_ok())
+    /// Validate and send a motion command, e.g. a `MoveToPosition`
+    ///
+    /// Nothing is sent if the command is invalid, see `MotionCommand::validate`
+    pub fn execute(&self, command: &impl MotionCommand) -> NetworkResult<MotionTask> {
+        command.validate()?;
+        Ok(command.start(self, Validated(())))
     }
 
     /// Get the Home location of the vehicle
     pub async fn get_home_geo_point(&self) -> Result<GeoPoint, NetworkError> {
-        self.airsim_client.get_home_geo_point(Some(self.vehicle_name)).await
+        self.airsim_client.get_home_geo_point(Some(&self.vehicle_name)).await
+    }
+
+    /// Local NED frame anchored at the Home location of the vehicle, used to convert between
+    /// `GeoPoint` targets and local `Position3` targets
+    pub async fn get_home_local_frame(&self) -> Result<LocalFrame, NetworkError> {
+        self.get_home_geo_point().await.map(LocalFrame::new)
     }
 
     /// High level control API
@@ -119,16 +261,15 @@ impl MultiRotorClient {
     ///
     /// Args:
     ///     timeout_sec (Option<f32>): Timeout for the vehicle to reach desired altitude
-    pub async fn take_off_async(&self, timeout_sec: f32) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "takeoff".into(),
-                Some(vec![Value::F32(timeout_sec), Value::String(vehicle_name)]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    pub fn take_off_async(&self, timeout_sec: f32) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "takeoff",
+            vec![Value::F32(timeout_sec), Value::String(vehicle_name)],
+        )
     }
 
     /// High level control API
@@ -138,16 +279,15 @@ impl MultiRotorClient {
     ///
     /// Args:
     ///     timeout_sec (Option<f32>): Timeout for the vehicle to land
-    pub async fn land_async(&self, timeout_sec: f32) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "land".into(),
-                Some(vec![Value::F32(timeout_sec), Value::String(vehicle_name)]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    pub fn land_async(&self, timeout_sec: f32) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "land",
+            vec![Value::F32(timeout_sec), Value::String(vehicle_name)],
+        )
     }
 
     /// High level control API
@@ -158,16 +298,15 @@ impl MultiRotorClient {
     ///
     /// Args:
     ///     timeout_sec (Option<f32>): Timeout for the vehicle to reach desired altitude
-    pub async fn go_home_async(&self, timeout_sec: f32) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "goHome".into(),
-                Some(vec![Value::F32(timeout_sec), Value::String(vehicle_name)]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    pub fn go_home_async(&self, timeout_sec: f32) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "goHome",
+            vec![Value::F32(timeout_sec), Value::String(vehicle_name)],
+        )
     }
 
     /// High level control API
@@ -179,30 +318,29 @@ impl MultiRotorClient {
     ///     duration (f32): Desired amount of time (seconds), to send this command for
     ///     drivetrain (DrivetrainType): when ForwardOnly, vehicle rotates itself so that its front is always facing the direction of travel. If MaxDegreeOfFreedom then it doesn't do that (crab-like movement)
     ///     yaw_mode (YawMode, Degree): Specifies if vehicle should face at given angle (is_rate=False) or should be rotating around its axis at given rate (is_rate=True)
-    pub async fn move_by_velocity_body_frame_async(
+    pub fn move_by_velocity_body_frame_async(
         &self,
         velocity: Velocity3,
         duration: f32,
         drivetrain: DrivetrainType,
         yaw_mode: YawMode,
-    ) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "moveByVelocityBodyFrame".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(velocity.vx),
-                    msgpack_rpc::Value::F32(velocity.vy),
-                    msgpack_rpc::Value::F32(velocity.vz),
-                    msgpack_rpc::Value::F32(duration),
-                    drivetrain.as_msgpack(),
-                    yaw_mode.as_msgpack(),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    ) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveByVelocityBodyFrame",
+            vec![
+                msgpack_rpc::Value::F32(velocity.vx),
+                msgpack_rpc::Value::F32(velocity.vy),
+                msgpack_rpc::Value::F32(velocity.vz),
+                msgpack_rpc::Value::F32(duration),
+                drivetrain.to_msgpack(),
+                yaw_mode.to_msgpack(),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// High level control API
@@ -215,31 +353,30 @@ impl MultiRotorClient {
     ///     duration (f32): desired amount of time (seconds), to send this command for
     ///     drivetrain (DrivetrainType): when ForwardOnly, vehicle rotates itself so that its front is always facing the direction of travel. If MaxDegreeOfFreedom then it doesn't do that (crab-like movement)
     ///     yaw_mode (YawMode, Degree): specifies if vehicle should face at given angle (is_rate=False) or should be rotating around its axis at given rate (is_rate=True)
-    pub async fn move_by_velocity_z_body_frame_async(
+    pub fn move_by_velocity_z_body_frame_async(
         &self,
         velocity: Velocity2,
         z: f32,
         duration: f32,
         drivetrain: DrivetrainType,
         yaw_mode: YawMode,
-    ) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "moveByVelocityZBodyFrame".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(velocity.vx),
-                    msgpack_rpc::Value::F32(velocity.vy),
-                    msgpack_rpc::Value::F32(z),
-                    msgpack_rpc::Value::F32(duration),
-                    drivetrain.as_msgpack(),
-                    yaw_mode.as_msgpack(),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    ) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveByVelocityZBodyFrame",
+            vec![
+                msgpack_rpc::Value::F32(velocity.vx),
+                msgpack_rpc::Value::F32(velocity.vy),
+                msgpack_rpc::Value::F32(z),
+                msgpack_rpc::Value::F32(duration),
+                drivetrain.to_msgpack(),
+                yaw_mode.to_msgpack(),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// Set PID gains for the velocity controller, move_by_velocity_async().
@@ -258,7 +395,7 @@ impl MultiRotorClient {
         self.airsim_client
             .unary_rpc(
                 "setVelocityControllerGains".into(),
-                Some(velocity_gains.as_msgpack(self.vehicle_name)),
+                Some(velocity_gains.rpc_params(&self.vehicle_name)),
             )
             .await
             .map(|response| response.result.is_ok())
@@ -273,30 +410,29 @@ impl MultiRotorClient {
     ///     duration (f32): desired amount of time (seconds), to send this command for
     ///     drivetrain (DrivetrainType): when ForwardOnly, vehicle rotates itself so that its front is always facing the direction of travel. If MaxDegreeOfFreedom then it doesn't do that (crab-like movement)
     ///     yaw_mode (YawMode, Degree): specifies if vehicle should face at given angle (is_rate=False) or should be rotating around its axis at given rate (is_rate=True)
-    pub async fn move_by_velocity_async(
+    pub fn move_by_velocity_async(
         &self,
         velocity: Velocity3,
         duration: f32,
         drivetrain: DrivetrainType,
         yaw_mode: YawMode,
-    ) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "moveByVelocity".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(velocity.vx),
-                    msgpack_rpc::Value::F32(velocity.vy),
-                    msgpack_rpc::Value::F32(velocity.vz),
-                    msgpack_rpc::Value::F32(duration),
-                    drivetrain.as_msgpack(),
-                    yaw_mode.as_msgpack(),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    ) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveByVelocity",
+            vec![
+                msgpack_rpc::Value::F32(velocity.vx),
+                msgpack_rpc::Value::F32(velocity.vy),
+                msgpack_rpc::Value::F32(velocity.vz),
+                msgpack_rpc::Value::F32(duration),
+                drivetrain.to_msgpack(),
+                yaw_mode.to_msgpack(),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// High level control API
@@ -309,31 +445,30 @@ impl MultiRotorClient {
     ///     duration (f32): desired amount of time (seconds), to send this command for
     ///     drivetrain (DrivetrainType): when ForwardOnly, vehicle rotates itself so that its front is always facing the direction of travel. If MaxDegreeOfFreedom then it doesn't do that (crab-like movement)
     ///     yaw_mode (YawMode, Degree): specifies if vehicle should face at given angle (is_rate=False) or should be rotating around its axis at given rate (is_rate=True)
-    pub async fn move_by_velocity_z_async(
+    pub fn move_by_velocity_z_async(
         &self,
         velocity: Velocity2,
         z: f32,
         duration: f32,
         drivetrain: DrivetrainType,
         yaw_mode: YawMode,
-    ) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "moveByVelocityZ".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(velocity.vx),
-                    msgpack_rpc::Value::F32(velocity.vy),
-                    msgpack_rpc::Value::F32(z),
-                    msgpack_rpc::Value::F32(duration),
-                    drivetrain.as_msgpack(),
-                    yaw_mode.as_msgpack(),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    ) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveByVelocityZ",
+            vec![
+                msgpack_rpc::Value::F32(velocity.vx),
+                msgpack_rpc::Value::F32(velocity.vy),
+                msgpack_rpc::Value::F32(z),
+                msgpack_rpc::Value::F32(duration),
+                drivetrain.to_msgpack(),
+                yaw_mode.to_msgpack(),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// Set PID gains for the position controller, move_to_position_async()
@@ -348,7 +483,7 @@ impl MultiRotorClient {
         self.airsim_client
             .unary_rpc(
                 "setPositionControllerGains".into(),
-                Some(position_gains.as_msgpack(self.vehicle_name)),
+                Some(position_gains.rpc_params(&self.vehicle_name)),
             )
             .await
             .map(|response| response.result.is_ok())
@@ -367,7 +502,7 @@ impl MultiRotorClient {
     ///     lookahead (Option<i32>): defaults to `-1`
     ///     adaptive_lookahead (Option<i32>): defaults to `0`
     #[allow(clippy::too_many_arguments)]
-    pub async fn move_to_position_async(
+    pub fn move_to_position_async(
         &self,
         position: Position3,
         velocity: f32,
@@ -376,29 +511,28 @@ impl MultiRotorClient {
         yaw_mode: YawMode,
         lookahead: Option<f32>,
         adaptive_lookahead: Option<f32>,
-    ) -> NetworkResult<bool> {
+    ) -> MotionTask {
         let lookahead = lookahead.unwrap_or(-1.0);
         let adaptive_lookahead = adaptive_lookahead.unwrap_or(1.0);
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "moveToPosition".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(position.x),
-                    msgpack_rpc::Value::F32(position.y),
-                    msgpack_rpc::Value::F32(position.z),
-                    msgpack_rpc::Value::F32(velocity),
-                    msgpack_rpc::Value::F32(timeout_sec),
-                    drivetrain.as_msgpack(),
-                    yaw_mode.as_msgpack(),
-                    msgpack_rpc::Value::F32(lookahead),
-                    msgpack_rpc::Value::F32(adaptive_lookahead),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveToPosition",
+            vec![
+                msgpack_rpc::Value::F32(position.x),
+                msgpack_rpc::Value::F32(position.y),
+                msgpack_rpc::Value::F32(position.z),
+                msgpack_rpc::Value::F32(velocity),
+                msgpack_rpc::Value::F32(timeout_sec),
+                drivetrain.to_msgpack(),
+                yaw_mode.to_msgpack(),
+                msgpack_rpc::Value::F32(lookahead),
+                msgpack_rpc::Value::F32(adaptive_lookahead),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// High level control API
@@ -414,7 +548,7 @@ impl MultiRotorClient {
     ///     lookahead (Option<i32>): defaults to `-1`
     ///     adaptive_lookahead (Option<i32>): defaults to `0`
     #[allow(clippy::too_many_arguments)]
-    pub async fn move_on_path_async(
+    pub fn move_on_path_async(
         &self,
         path: Path,
         velocity: f32,
@@ -423,27 +557,26 @@ impl MultiRotorClient {
         yaw_mode: YawMode,
         lookahead: Option<f32>,
         adaptive_lookahead: Option<f32>,
-    ) -> NetworkResult<bool> {
+    ) -> MotionTask {
         let lookahead = lookahead.unwrap_or(-1.0);
         let adaptive_lookahead = adaptive_lookahead.unwrap_or(1.0);
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "moveOnPath".into(),
-                Some(vec![
-                    path.as_msgpack(),
-                    msgpack_rpc::Value::F32(velocity),
-                    msgpack_rpc::Value::F32(timeout_sec),
-                    drivetrain.as_msgpack(),
-                    yaw_mode.as_msgpack(),
-                    msgpack_rpc::Value::F32(lookahead),
-                    msgpack_rpc::Value::F32(adaptive_lookahead),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveOnPath",
+            vec![
+                path.to_msgpack(),
+                msgpack_rpc::Value::F32(velocity),
+                msgpack_rpc::Value::F32(timeout_sec),
+                drivetrain.to_msgpack(),
+                yaw_mode.to_msgpack(),
+                msgpack_rpc::Value::F32(lookahead),
+                msgpack_rpc::Value::F32(adaptive_lookahead),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// High level control API
@@ -459,7 +592,7 @@ impl MultiRotorClient {
     ///     lookahead (Option<i32>): defaults to `-1`
     ///     adaptive_lookahead (Option<i32>): defaults to `0`
     #[allow(clippy::too_many_arguments)]
-    pub async fn move_to_gps_async(
+    pub fn move_to_gps_async(
         &self,
         geopoint: GeoPoint,
         velocity: f32,
@@ -468,29 +601,28 @@ impl MultiRotorClient {
         yaw_mode: YawMode,
         lookahead: Option<f32>,
         adaptive_lookahead: Option<f32>,
-    ) -> NetworkResult<bool> {
+    ) -> MotionTask {
         let lookahead = lookahead.unwrap_or(-1.0);
         let adaptive_lookahead = adaptive_lookahead.unwrap_or(1.0);
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "moveToGPS".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(geopoint.latitude),
-                    msgpack_rpc::Value::F32(geopoint.longitude),
-                    msgpack_rpc::Value::F32(geopoint.altitude),
-                    msgpack_rpc::Value::F32(velocity),
-                    msgpack_rpc::Value::F32(timeout_sec),
-                    drivetrain.as_msgpack(),
-                    yaw_mode.as_msgpack(),
-                    msgpack_rpc::Value::F32(lookahead),
-                    msgpack_rpc::Value::F32(adaptive_lookahead),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveToGPS",
+            vec![
+                msgpack_rpc::Value::F32(geopoint.latitude),
+                msgpack_rpc::Value::F32(geopoint.longitude),
+                msgpack_rpc::Value::F32(geopoint.altitude),
+                msgpack_rpc::Value::F32(velocity),
+                msgpack_rpc::Value::F32(timeout_sec),
+                drivetrain.to_msgpack(),
+                yaw_mode.to_msgpack(),
+                msgpack_rpc::Value::F32(lookahead),
+                msgpack_rpc::Value::F32(adaptive_lookahead),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// High level control API
@@ -505,7 +637,7 @@ impl MultiRotorClient {
     ///     lookahead (Option<i32>): defaults to `-1`
     ///     adaptive_lookahead (Option<i32>): defaults to `0`
     #[allow(clippy::too_many_arguments)]
-    pub async fn move_to_z_async(
+    pub fn move_to_z_async(
         &self,
         z: f32,
         velocity: f32,
@@ -513,26 +645,25 @@ impl MultiRotorClient {
         yaw_mode: YawMode,
         lookahead: Option<f32>,
         adaptive_lookahead: Option<f32>,
-    ) -> NetworkResult<bool> {
+    ) -> MotionTask {
         let lookahead = lookahead.unwrap_or(-1.0);
         let adaptive_lookahead = adaptive_lookahead.unwrap_or(1.0);
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "moveToZ".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(z),
-                    msgpack_rpc::Value::F32(velocity),
-                    msgpack_rpc::Value::F32(timeout_sec),
-                    yaw_mode.as_msgpack(),
-                    msgpack_rpc::Value::F32(lookahead),
-                    msgpack_rpc::Value::F32(adaptive_lookahead),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveToZ",
+            vec![
+                msgpack_rpc::Value::F32(z),
+                msgpack_rpc::Value::F32(velocity),
+                msgpack_rpc::Value::F32(timeout_sec),
+                yaw_mode.to_msgpack(),
+                msgpack_rpc::Value::F32(lookahead),
+                msgpack_rpc::Value::F32(adaptive_lookahead),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// Low level control API
@@ -549,31 +680,30 @@ impl MultiRotorClient {
     ///     duration (f32): after this duration vehicle would switch back to non-manual mode
     ///     drivetrain (DrivetrainType): when ForwardOnly, vehicle rotates itself so that its front is always facing the direction of travel. If MaxDegreeOfFreedom then it doesn't do that (crab-like movement)
     ///     yaw_mode (YawMode, Degree): specifies if vehicle should face at given angle (is_rate=False) or should be rotating around its axis at given rate (is_rate=True)
-    pub async fn move_by_manual_async(
+    pub fn move_by_manual_async(
         &self,
         v_max: Velocity3,
         z_min: f32,
         duration: f32,
         drivetrain: DrivetrainType,
         yaw_mode: YawMode,
-    ) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "moveByManual".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(v_max.vx),
-                    msgpack_rpc::Value::F32(v_max.vy),
-                    msgpack_rpc::Value::F32(z_min),
-                    msgpack_rpc::Value::F32(duration),
-                    drivetrain.as_msgpack(),
-                    yaw_mode.as_msgpack(),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    ) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveByManual",
+            vec![
+                msgpack_rpc::Value::F32(v_max.vx),
+                msgpack_rpc::Value::F32(v_max.vy),
+                msgpack_rpc::Value::F32(z_min),
+                msgpack_rpc::Value::F32(duration),
+                drivetrain.to_msgpack(),
+                yaw_mode.to_msgpack(),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// Low level control API
@@ -583,19 +713,18 @@ impl MultiRotorClient {
     /// args:
     ///     rc_data (RCData): remote control commands
     pub async fn move_by_rc(&self, rc_data: RCData) -> NetworkResult<()> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
 
         self.airsim_client
             .unary_rpc(
                 "moveByRC".into(),
-                Some(vec![rc_data.as_msgpack(), Value::String(vehicle_name)]),
+                Some(vec![rc_data.to_msgpack(), Value::String(vehicle_name)]),
             )
             .await
-            .map(|response| response.result.unwrap())
-            .map(|value| {
-                if !value.is_nil() {
-                    panic!("Value {} is not Nil", value)
-                }
+            .and_then(Value::from_response)
+            .and_then(|value| match value {
+                Value::Nil => Ok(()),
+                value => Err(decode_error("nil", &value)),
             })
     }
 
@@ -604,33 +733,49 @@ impl MultiRotorClient {
     /// Directly control the motors using PWM values
     /// convert thrust to pwm: https://github.com/microsoft/AirSim/issues/2592
     ///
+    /// Stock AirSim only accepts 4 motors, returns a `NetworkError::ValidationError` for any other
+    /// motor count, see `move_by_n_motor_pwms_async` for frames with more motors
+    ///
     /// args:
-    ///     pwm (PWM): pwm signals for each indivual rotor (4 rotors in total)
+    ///     pwm (PWM): pwm signals for each indivual rotor, in the order of the vehicle's `RotorLayout`
     ///     duration (f32): desired amount of time (seconds), to send this command for
-    pub async fn move_by_motor_pwms_async(&self, pwm: PWM, duration: f32) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
+    pub fn move_by_motor_pwms_async(&self, pwm: PWM, duration: f32) -> NetworkResult<MotionTask> {
+        if pwm.motor_count() != 4 {
+            return Err(NetworkError::ValidationError {
+                message: format!("moveByMotorPWMs takes 4 motors, got {}", pwm.motor_count()),
+            });
+        }
+        Ok(self.motor_pwms("moveByMotorPWMs", pwm, duration))
+    }
 
-        self.airsim_client
-            .unary_rpc(
-                "moveByMotorPWMs".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(pwm.front_right_pwm),
-                    msgpack_rpc::Value::F32(pwm.rear_left_pwm),
-                    msgpack_rpc::Value::F32(pwm.front_left_pwm),
-                    msgpack_rpc::Value::F32(pwm.rear_right_pwm),
-                    msgpack_rpc::Value::F32(duration),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    /// Low level control API
+    ///
+    /// Directly control any number of motors using PWM values, through a method of a server that
+    /// takes one value per motor followed by the duration and the vehicle name
+    ///
+    /// args:
+    ///     method (&str): Name of the RPC method of the server, stock AirSim has none for more than 4 motors
+    ///     pwm (PWM): pwm signals for each indivual rotor, in the order of the vehicle's `RotorLayout`
+    ///     duration (f32): desired amount of time (seconds), to send this command for
+    pub fn move_by_n_motor_pwms_async(&self, method: &str, pwm: PWM, duration: f32) -> MotionTask {
+        self.motor_pwms(method, pwm, duration)
+    }
+
+    fn motor_pwms(&self, method: &str, pwm: PWM, duration: f32) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        let mut params: Vec<Value> = pwm.motors().iter().map(|pwm| Value::F32(pwm.value())).collect();
+        params.push(Value::F32(duration));
+        params.push(Value::String(vehicle_name));
+
+        MotionTask::start(&self.airsim_client, &self.vehicle_name, method, params)
     }
 
     /// Set PID gains for the angle rate controller
     ///
     /// - Modifying these gains will have an affect on *ALL* move*() APIs.
-    ///     This is because any velocity setpoint is converted to an angle level setpoint which is tracked with an angle level controllers.
-    ///     That angle level setpoint is itself tracked with and angle rate controller.
+    ///   This is because any velocity setpoint is converted to an angle level setpoint which is tracked with an angle level controllers.
+    ///   That angle level setpoint is itself tracked with and angle rate controller.
     /// - This function should only be called if the default angle rate control PID gains need to be modified.
     ///
     /// args:
@@ -644,7 +789,7 @@ impl MultiRotorClient {
         self.airsim_client
             .unary_rpc(
                 "setAngleRateControllerGains".into(),
-                Some(angle_rate_gains.as_msgpack(self.vehicle_name)),
+                Some(angle_rate_gains.rpc_params(&self.vehicle_name)),
             )
             .await
             .map(|response| response.result.is_ok())
@@ -655,7 +800,7 @@ impl MultiRotorClient {
     /// - Sets angle level controller gains (used by any API setting angle references - for ex: move_by_roll_pitch_yaw_z_async(),
     ///   move_by_roll_pitch_yaw_throttle_async(), etc)
     /// - Modifying these gains will also affect the behaviour of move_by_velocity_async() API.
-    ///     This is because the AirSim flight controller will track velocity setpoints by converting them to angle set points.
+    ///   This is because the AirSim flight controller will track velocity setpoints by converting them to angle set points.
     /// - This function should only be called if the default angle level control PID gains need to be modified.
     /// - Passing AngularControllerGains() sets gains to default airsim values.
     ///
@@ -670,7 +815,7 @@ impl MultiRotorClient {
         self.airsim_client
             .unary_rpc(
                 "setAngleLevelControllerGains".into(),
-                Some(angle_level_gains.as_msgpack(self.vehicle_name)),
+                Some(angle_level_gains.rpc_params(&self.vehicle_name)),
             )
             .await
             .map(|response| response.result.is_ok())
@@ -684,28 +829,22 @@ impl MultiRotorClient {
     ///     rotation (Orientation3): Roll angle, pitch angle, and yaw angle set points are given in `radians`, in the ENU body frame.
     ///     z (f32): altitude z is given in local NED frame of the vehicle.
     ///     duration (f32): Desired amount of time (seconds), to send this command for
-    pub async fn move_by_roll_pitch_yaw_z_async(
-        &self,
-        rotation: Orientation3,
-        z: f32,
-        duration: f32,
-    ) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "moveByRollPitchYawZ".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(rotation.roll),
-                    msgpack_rpc::Value::F32(-rotation.pitch),
-                    msgpack_rpc::Value::F32(-rotation.yaw),
-                    msgpack_rpc::Value::F32(z),
-                    msgpack_rpc::Value::F32(duration),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    pub fn move_by_roll_pitch_yaw_z_async(&self, rotation: Orientation3, z: f32, duration: f32) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveByRollPitchYawZ",
+            vec![
+                msgpack_rpc::Value::F32(rotation.roll),
+                msgpack_rpc::Value::F32(-rotation.pitch),
+                msgpack_rpc::Value::F32(-rotation.yaw),
+                msgpack_rpc::Value::F32(z),
+                msgpack_rpc::Value::F32(duration),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// Low level control API
@@ -714,34 +853,29 @@ impl MultiRotorClient {
     ///
     /// args:
     ///     rotation (Orientation3): Roll angle, pitch angle, and yaw angle set points are given in `radians`, in the ENU body frame.
-    ///     throttle_z (f32): Desired throttle (between 0.0 to 1.0) in Z
+    ///     throttle_z (UnitInterval): Desired throttle (between 0.0 to 1.0) in Z
     ///     duration (f32): Desired amount of time (seconds), to send this command for
-    pub async fn move_by_roll_pitch_yaw_throttle_async(
+    pub fn move_by_roll_pitch_yaw_throttle_async(
         &self,
         rotation: Orientation3,
-        throttle_z: f32,
+        throttle_z: UnitInterval,
         duration: f32,
-    ) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        if throttle_z.is_sign_negative() || throttle_z > 1.0 {
-            panic!("throttle_z outside of valid range 0.0 to 1.0")
-        }
-
-        self.airsim_client
-            .unary_rpc(
-                "moveByRollPitchYawThrottle".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(rotation.roll),
-                    msgpack_rpc::Value::F32(-rotation.pitch),
-                    msgpack_rpc::Value::F32(-rotation.yaw),
-                    msgpack_rpc::Value::F32(throttle_z),
-                    msgpack_rpc::Value::F32(duration),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    ) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveByRollPitchYawThrottle",
+            vec![
+                msgpack_rpc::Value::F32(rotation.roll),
+                msgpack_rpc::Value::F32(-rotation.pitch),
+                msgpack_rpc::Value::F32(-rotation.yaw),
+                msgpack_rpc::Value::F32(throttle_z.value()),
+                msgpack_rpc::Value::F32(duration),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// Low level control API
@@ -751,34 +885,30 @@ impl MultiRotorClient {
     /// args:
     ///     rotation (Orientation2): Desired roll and pitch angle set points are given in `radians`, in the ENU body frame.
     ///     yaw_rate (f32): Desired yaw rate, in radian per second.
-    ///     throttle_z (f32): Desired throttle (between 0.0 to 1.0) in Z
+    ///     throttle_z (UnitInterval): Desired throttle (between 0.0 to 1.0) in Z
     ///     duration (f32): Desired amount of time (seconds), to send this command for
-    pub async fn move_by_roll_pitch_yawrate_throttle_async(
+    pub fn move_by_roll_pitch_yawrate_throttle_async(
         &self,
         rotation: Orientation2,
         yaw_rate: f32,
-        throttle_z: f32,
+        throttle_z: UnitInterval,
         duration: f32,
-    ) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-        if throttle_z.is_sign_negative() || throttle_z > 1.0 {
-            panic!("throttle_z outside of valid range 0.0 to 1.0")
-        }
-
-        self.airsim_client
-            .unary_rpc(
-                "moveByRollPitchYawrateThrottle".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(rotation.roll),
-                    msgpack_rpc::Value::F32(-rotation.pitch),
-                    msgpack_rpc::Value::F32(-yaw_rate),
-                    msgpack_rpc::Value::F32(throttle_z),
-                    msgpack_rpc::Value::F32(duration),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    ) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveByRollPitchYawrateThrottle",
+            vec![
+                msgpack_rpc::Value::F32(rotation.roll),
+                msgpack_rpc::Value::F32(-rotation.pitch),
+                msgpack_rpc::Value::F32(-yaw_rate),
+                msgpack_rpc::Value::F32(throttle_z.value()),
+                msgpack_rpc::Value::F32(duration),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// Low level control API
@@ -790,29 +920,28 @@ impl MultiRotorClient {
     ///     yaw_rate (f32): Desired yaw rate, in radian per second.
     ///     z (f32): altitude z is given in local NED frame of the vehicle.
     ///     duration (f32): Desired amount of time (seconds), to send this command for
-    pub async fn move_by_roll_pitch_yawrate_z_async(
+    pub fn move_by_roll_pitch_yawrate_z_async(
         &self,
         rotation: Orientation2,
         yaw_rate: f32,
         z: f32,
         duration: f32,
-    ) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "moveByRollPitchYawrateZ".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(rotation.roll),
-                    msgpack_rpc::Value::F32(-rotation.pitch),
-                    msgpack_rpc::Value::F32(-yaw_rate),
-                    msgpack_rpc::Value::F32(z),
-                    msgpack_rpc::Value::F32(duration),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    ) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveByRollPitchYawrateZ",
+            vec![
+                msgpack_rpc::Value::F32(rotation.roll),
+                msgpack_rpc::Value::F32(-rotation.pitch),
+                msgpack_rpc::Value::F32(-yaw_rate),
+                msgpack_rpc::Value::F32(z),
+                msgpack_rpc::Value::F32(duration),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// Low level control API
@@ -824,28 +953,22 @@ impl MultiRotorClient {
     ///     yaw_rate (f32): Desired yaw rate, in radian per second.
     ///     z (f32): altitude z is given in local NED frame of the vehicle.
     ///     duration (f32): Desired amount of time (seconds), to send this command for
-    pub async fn move_by_angle_rates_z_async(
-        &self,
-        rotation_rates: Orientation3,
-        z: f32,
-        duration: f32,
-    ) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-
-        self.airsim_client
-            .unary_rpc(
-                "moveByAngleRatesZ".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(rotation_rates.roll),
-                    msgpack_rpc::Value::F32(-rotation_rates.pitch),
-                    msgpack_rpc::Value::F32(-rotation_rates.yaw),
-                    msgpack_rpc::Value::F32(z),
-                    msgpack_rpc::Value::F32(duration),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    pub fn move_by_angle_rates_z_async(&self, rotation_rates: Orientation3, z: f32, duration: f32) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveByAngleRatesZ",
+            vec![
+                msgpack_rpc::Value::F32(rotation_rates.roll),
+                msgpack_rpc::Value::F32(-rotation_rates.pitch),
+                msgpack_rpc::Value::F32(-rotation_rates.yaw),
+                msgpack_rpc::Value::F32(z),
+                msgpack_rpc::Value::F32(duration),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// Low level control API
@@ -855,52 +978,48 @@ impl MultiRotorClient {
     /// args:
     ///     rotation_rates (Orientation2): Roll rate, pitch rate, and yaw rate set points are given in `radians`, in the body frame.
     ///     yaw_rate (f32): Desired yaw rate, in radian per second.
-    ///     throttle (f32): Desired throttle (between 0.0 to 1.0)
+    ///     throttle (UnitInterval): Desired throttle (between 0.0 to 1.0)
     ///     duration (f32): Desired amount of time (seconds), to send this command for
-    pub async fn move_by_angle_rates_throttle_async(
+    pub fn move_by_angle_rates_throttle_async(
         &self,
         rotation_rates: Orientation3,
-        throttle: f32,
+        throttle: UnitInterval,
         duration: f32,
-    ) -> NetworkResult<bool> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
-        if throttle.is_sign_negative() || throttle > 1.0 {
-            panic!("throttle outside of valid range 0.0 to 1.0")
-        }
-
-        self.airsim_client
-            .unary_rpc(
-                "moveByAngleRatesThrottle".into(),
-                Some(vec![
-                    msgpack_rpc::Value::F32(rotation_rates.roll),
-                    msgpack_rpc::Value::F32(-rotation_rates.pitch),
-                    msgpack_rpc::Value::F32(-rotation_rates.yaw),
-                    msgpack_rpc::Value::F32(throttle),
-                    msgpack_rpc::Value::F32(duration),
-                    Value::String(vehicle_name),
-                ]),
-            )
-            .await
-            .map(|response| response.result.is_ok() && response.result.unwrap().as_bool() == Some(true))
+    ) -> MotionTask {
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
+
+        MotionTask::start(
+            &self.airsim_client,
+            &self.vehicle_name,
+            "moveByAngleRatesThrottle",
+            vec![
+                msgpack_rpc::Value::F32(rotation_rates.roll),
+                msgpack_rpc::Value::F32(-rotation_rates.pitch),
+                msgpack_rpc::Value::F32(-rotation_rates.yaw),
+                msgpack_rpc::Value::F32(throttle.value()),
+                msgpack_rpc::Value::F32(duration),
+                Value::String(vehicle_name),
+            ],
+        )
     }
 
     /// Get the kinematic state of the multirotor vehicle
     pub async fn get_multirotor_state(&self) -> NetworkResult<MultiRotorState> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
         self.airsim_client
             .unary_rpc("getMultirotorState".into(), Some(vec![Value::String(vehicle_name)]))
             .await
-            .map(MultiRotorState::from)
+            .and_then(MultiRotorState::from_response)
     }
 
     /// Used to obtain the current state of all a multirotor's rotors. The state includes the speeds,
     /// thrusts and torques for all rotors.
     pub async fn get_rotor_states(&self) -> NetworkResult<RotorStates> {
-        let vehicle_name: Utf8String = self.vehicle_name.into();
+        let vehicle_name: Utf8String = self.vehicle_name.as_str().into();
         self.airsim_client
             .unary_rpc("getRotorStates".into(), Some(vec![Value::String(vehicle_name)]))
             .await
-            .map(RotorStates::from)
+            .and_then(RotorStates::from_response)
     }
 
     /// Camera API
@@ -923,7 +1042,7 @@ impl MultiRotorClient {
         external: Option<bool>,
     ) -> Result<CompressedImage, NetworkError> {
         self.airsim_client
-            .sim_get_image(Some(self.vehicle_name), camera_name, image_type, external)
+            .sim_get_image(Some(&self.vehicle_name), camera_name, image_type, external)
             .await
     }
 
@@ -939,8 +1058,83 @@ impl MultiRotorClient {
     #[inline(always)]
     pub async fn sim_get_images(&self, _requests: ImageRequests, _external: Option<bool>) -> Result<(), NetworkError> {
         // self.airsim_client
-        //     .sim_get_images(requests, Some(self.vehicle_name), external)
+        //     .sim_get_images(requests, Some(&self.vehicle_name), external)
         //     .await
         unimplemented!("todo");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_std::task;
+    use rmpv::Value;
+
+    use crate::clients::test_server::TestServer;
+    use crate::types::unit_interval::UnitInterval;
+    use crate::{MultiRotorClient, NetworkError, Orientation3, RCData, PWM};
+
+    #[test]
+    fn test_motor_pwms_motor_count() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let server_calls = Arc::clone(&calls);
+        let server = TestServer::spawn(move |request| {
+            server_calls
+                .lock()
+                .unwrap()
+                .push((request.method.clone(), request.params.len()));
+            Ok(Value::Boolean(true))
+        });
+
+        task::block_on(async {
+            let client = MultiRotorClient::connect(&server.addr, "Drone1").await.unwrap();
+            let hexa = PWM::uniform(6, UnitInterval::new(0.5).unwrap());
+            calls.lock().unwrap().clear();
+
+            assert!(matches!(
+                client.move_by_motor_pwms_async(hexa.clone(), 0.02),
+                Err(NetworkError::ValidationError { .. })
+            ));
+            client
+                .move_by_n_motor_pwms_async("moveByMotorPWMsN", hexa, 0.02)
+                .await
+                .unwrap();
+            let quad = PWM::uniform(4, UnitInterval::new(0.5).unwrap());
+            client.move_by_motor_pwms_async(quad, 0.02).unwrap().await.unwrap();
+        });
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [("moveByMotorPWMsN".to_string(), 8), ("moveByMotorPWMs".to_string(), 6)]
+        );
+    }
+
+    #[test]
+    fn test_move_by_rc_errors() {
+        let server = TestServer::spawn(|request| match (request.method.as_str(), request.params.last()) {
+            ("moveByRC", Some(name)) if name.as_str() == Some("Drone1") => Ok(Value::Nil),
+            ("moveByRC", Some(name)) if name.as_str() == Some("Drone2") => Ok(Value::Boolean(true)),
+            ("moveByRC", _) => Err(Value::from("vehicle not found")),
+            _ => Ok(Value::Boolean(true)),
+        });
+        let rc_data = RCData::new(0, Orientation3::new(0.0, 0.0, 0.0), 0.5, None, true, true);
+
+        task::block_on(async {
+            let client = MultiRotorClient::connect(&server.addr, "Drone1").await.unwrap();
+            client.move_by_rc(rc_data).await.unwrap();
+
+            let client = MultiRotorClient::connect(&server.addr, "Drone2").await.unwrap();
+            assert!(matches!(
+                client.move_by_rc(rc_data).await,
+                Err(NetworkError::Parse { .. })
+            ));
+
+            let client = MultiRotorClient::connect(&server.addr, "Drone3").await.unwrap();
+            assert!(matches!(
+                client.move_by_rc(rc_data).await,
+                Err(NetworkError::Rpc { .. })
+            ));
+        });
+    }
+}
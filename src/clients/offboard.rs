@@ -0,0 +1,265 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_std::task::{self, JoinHandle};
+
+use crate::error::{NetworkError, NetworkResult};
+use crate::{DrivetrainType, Orientation2, Velocity3, YawMode};
+
+use super::motion::MotionTask;
+use super::multi_rotor_client::MultiRotorClient;
+
+/// Each command lasts this many periods, so a late send does not leave the vehicle without one
+const COMMAND_PERIODS: f32 = 3.0;
+
+/// Lowest stream rate in Hz, slower streams cannot react to the control loop
+const MIN_RATE_HZ: f32 = 1.0;
+
+/// Target streamed by an `OffboardStreamer`
+#[derive(Debug, Clone, Copy)]
+pub enum Setpoint {
+    /// Sent with `move_by_velocity_async`
+    Velocity {
+        velocity: Velocity3,
+        drivetrain: DrivetrainType,
+        yaw_mode: YawMode,
+    },
+    /// Sent with `move_by_roll_pitch_yawrate_z_async`
+    Attitude {
+        rotation: Orientation2,
+        yaw_rate: f32,
+        z: f32,
+    },
+}
+
+impl Setpoint {
+    fn start(&self, client: &MultiRotorClient, duration: f32) -> MotionTask {
+        match *self {
+            Setpoint::Velocity {
+                velocity,
+                drivetrain,
+                yaw_mode,
+            } => client.move_by_velocity_async(velocity, duration, drivetrain, yaw_mode),
+            Setpoint::Attitude { rotation, yaw_rate, z } => {
+                client.move_by_roll_pitch_yawrate_z_async(rotation, yaw_rate, z, duration)
+            }
+        }
+    }
+}
+
+/// Timing of the setpoints sent by an `OffboardStreamer`
+///
+/// The latencies measure the client side only: the simulator's answers to the setpoints are not
+/// awaited, as each one is superseded by the next.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StreamStats {
+    /// setpoints sent, hover commands excluded
+    pub sent: u64,
+    /// periods in which sending took too long to keep the rate
+    pub overruns: u64,
+    /// time until a setpoint is queued on the connection, waiting for the commands before it
+    pub last_latency: Duration,
+    pub mean_latency: Duration,
+    pub max_latency: Duration,
+}
+
+impl StreamStats {
+    fn record(&mut self, latency: Duration) {
+        self.sent += 1;
+        self.last_latency = latency;
+        self.max_latency = self.max_latency.max(latency);
+        let mean = self.mean_latency.as_secs_f64();
+        self.mean_latency = Duration::from_secs_f64(mean + (latency.as_secs_f64() - mean) / self.sent as f64);
+    }
+}
+
+struct Shared {
+    setpoint: Mutex<Option<(Setpoint, Instant)>>,
+    stats: Mutex<StreamStats>,
+    hovering: AtomicBool,
+    running: AtomicBool,
+}
+
+/// Streams the most recent setpoint to a multirotor at a fixed rate, for control loops
+///
+/// The control loop calls `set` whenever it has a new setpoint, at any rate; the streamer sends
+/// the latest one every period as a short motion command. If `set` is not called for longer than
+/// the stale timeout, the streamer hovers the vehicle once and resumes with the next setpoint.
+/// The streamer stops when dropped, the last command then expires after a few periods.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use airsim_client::{DrivetrainType, MultiRotorClient, NetworkResult, OffboardStreamer, Setpoint, Velocity3, YawMode};
+/// # async fn run(client: MultiRotorClient) -> NetworkResult<()> {
+/// let streamer = OffboardStreamer::start(&client, 100.0, Duration::from_millis(200))?;
+/// loop {
+///     streamer.set(Setpoint::Velocity {
+///         velocity: Velocity3::new(1.0, 0.0, 0.0), // computed by the controller
+///         drivetrain: DrivetrainType::MaxDegreeOfFreedom,
+///         yaw_mode: YawMode::new(true, 0.0),
+///     });
+///     log::debug!("{:?}", streamer.stats());
+/// #   break;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct OffboardStreamer {
+    shared: Arc<Shared>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl OffboardStreamer {
+    /// Start the background task, nothing is sent until the first `set`
+    ///
+    /// args:
+    ///     client (&MultiRotorClient): Client of the vehicle to control
+    ///     rate_hz (f32): Setpoints sent per second, at least 1, typically 50 to 200
+    ///     stale_timeout (Duration): Hover when no setpoint was set for this long
+    pub fn start(client: &MultiRotorClient, rate_hz: f32, stale_timeout: Duration) -> NetworkResult<Self> {
+        if !(rate_hz.is_finite() && rate_hz >= MIN_RATE_HZ) {
+            return Err(NetworkError::ValidationError {
+                message: format!("stream rate {rate_hz} Hz is below {MIN_RATE_HZ} Hz"),
+            });
+        }
+
+        let shared = Arc::new(Shared {
+            setpoint: Mutex::new(None),
+            stats: Mutex::new(StreamStats::default()),
+            hovering: AtomicBool::new(false),
+            running: AtomicBool::new(true),
+        });
+        let period = Duration::from_secs_f32(1.0 / rate_hz);
+        let duration = COMMAND_PERIODS / rate_hz;
+
+        let handle = task::spawn({
+            let client = client.clone();
+            let shared = Arc::clone(&shared);
+
+            async move {
+                let mut next = Instant::now();
+                while shared.running.load(Ordering::Acquire) {
+                    let latest = *shared.setpoint.lock().unwrap();
+                    match latest {
+                        Some((setpoint, updated)) if updated.elapsed() <= stale_timeout => {
+                            shared.hovering.store(false, Ordering::Release);
+                            let sent_at = Instant::now();
+                            if setpoint.start(&client, duration).sent().await {
+                                shared.stats.lock().unwrap().record(sent_at.elapsed());
+                            } else {
+                                log::warn!("Could not send setpoint {setpoint:?}");
+                            }
+                        }
+                        Some((_, updated)) if !shared.hovering.swap(true, Ordering::AcqRel) => {
+                            log::warn!("No setpoint for {:?}, hovering", updated.elapsed());
+                            if !client.hover_async().sent().await {
+                                log::error!("Could not send hover");
+                            }
+                        }
+                        _ => {}
+                    }
+
+                    next += period;
+                    let now = Instant::now();
+                    if next > now {
+                        task::sleep(next - now).await;
+                    } else {
+                        // skip the missed periods instead of sending a burst to catch up
+                        shared.stats.lock().unwrap().overruns += 1;
+                        next = now;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            shared,
+            handle: Some(handle),
+        })
+    }
+
+    /// Replace the setpoint, it is sent from the next period on
+    pub fn set(&self, setpoint: Setpoint) {
+        *self.shared.setpoint.lock().unwrap() = Some((setpoint, Instant::now()));
+    }
+
+    pub fn stats(&self) -> StreamStats {
+        *self.shared.stats.lock().unwrap()
+    }
+
+    /// Returns true if the setpoints went stale and the vehicle was told to hover
+    pub fn is_hovering(&self) -> bool {
+        self.shared.hovering.load(Ordering::Acquire)
+    }
+
+    /// Stop streaming and wait for the background task to end
+    pub async fn stop(mut self) {
+        self.shared.running.store(false, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.await;
+        }
+    }
+}
+
+impl Drop for OffboardStreamer {
+    fn drop(&mut self) {
+        self.shared.running.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use async_std::task;
+    use rmpv::Value;
+
+    use super::{OffboardStreamer, Setpoint};
+    use crate::clients::test_server::TestServer;
+    use crate::{MultiRotorClient, Orientation2};
+
+    #[test]
+    fn test_streams_latest_setpoint_and_hovers_when_stale() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let recorded = Arc::clone(&calls);
+        let server = TestServer::spawn(move |request| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((request.method.clone(), request.params.get(3).cloned()));
+            Ok(Value::Boolean(true))
+        });
+        let count = |method: &str| calls.lock().unwrap().iter().filter(|(m, _)| m == method).count();
+
+        task::block_on(async {
+            let client = MultiRotorClient::connect(&server.addr, "").await.unwrap();
+            assert!(OffboardStreamer::start(&client, 0.0, Duration::from_millis(100)).is_err());
+            assert!(OffboardStreamer::start(&client, 1e-30, Duration::from_millis(100)).is_err());
+
+            let streamer = OffboardStreamer::start(&client, 100.0, Duration::from_millis(100)).unwrap();
+            task::sleep(Duration::from_millis(50)).await;
+            assert_eq!(count("moveByRollPitchYawrateZ"), 0);
+
+            for z in [-5.0, -10.0] {
+                streamer.set(Setpoint::Attitude {
+                    rotation: Orientation2::new(0.0, 0.0),
+                    yaw_rate: 0.0,
+                    z,
+                });
+                task::sleep(Duration::from_millis(60)).await;
+            }
+            let sent = count("moveByRollPitchYawrateZ");
+            assert!(sent >= 5, "only {sent} setpoints sent");
+            let last = calls.lock().unwrap().last().cloned().unwrap();
+            assert_eq!(last.1, Some(Value::F32(-10.0)));
+
+            task::sleep(Duration::from_millis(250)).await;
+            assert!(streamer.is_hovering());
+            assert_eq!(count("hover"), 1);
+            assert_eq!(streamer.stats().sent as usize, count("moveByRollPitchYawrateZ"));
+            streamer.stop().await;
+        });
+    }
+}
@@ -0,0 +1,669 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_std::task::{self, JoinHandle};
+
+use crate::error::NetworkResult;
+use crate::{
+    DrivetrainType, GeoPoint, Geofence, KinematicsState, LocalFrame, NetworkError, Orientation2, Orientation3, Path,
+    Position3, UnitInterval, Vector3, Velocity2, Velocity3, YawMode,
+};
+
+use super::commands::sealed::Validated;
+use super::commands::MotionCommand;
+use super::motion::MotionTask;
+use super::multi_rotor_client::MultiRotorClient;
+
+/// Distance in meters a vehicle may be outside the fence before it counts as a breach, so
+/// targets clamped onto the fence do not trigger the breach action
+const FENCE_TOLERANCE: f32 = 0.5;
+
+/// Factor by which the live velocity and tilt may exceed their limits before it counts as a
+/// breach, as the controllers briefly overshoot their setpoints
+const LIMIT_TOLERANCE: f32 = 1.1;
+
+/// Time the monitor gives the breach action before starting it again, if none is configured
+const DEFAULT_BREACH_HOLDOFF: Duration = Duration::from_secs(3);
+
+/// What to do with a command that violates the limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitPolicy {
+    /// Return `NetworkError::SafetyViolation` without sending the command
+    Reject,
+    /// Move targets into the fence and scale velocities and angles down to the limits
+    Clamp,
+}
+
+/// Command that brings a multirotor into a safe state, on a breach of the limits of a
+/// `SafeMultiRotor` or when a `Watchdog` is not fed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BreachAction {
+    Hover,
+    Land { timeout_sec: f32 },
+    GoHome { timeout_sec: f32 },
+}
+
+impl BreachAction {
+    /// Start the action, it supersedes the motion command the vehicle is executing
+    pub fn start(&self, client: &MultiRotorClient) -> MotionTask {
+        match *self {
+            BreachAction::Hover => client.hover_async(),
+            BreachAction::Land { timeout_sec } => client.land_async(timeout_sec),
+            BreachAction::GoHome { timeout_sec } => client.go_home_async(timeout_sec),
+        }
+    }
+}
+
+/// A limit the vehicle violated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Breach {
+    /// Position outside the fence polygon or altitude range
+    Geofence(Position3),
+    /// Speed in m/s
+    Velocity(f32),
+    /// Roll or pitch in radians
+    Tilt(f32),
+}
+
+/// `MultiRotorClient` wrapper that keeps commands within a geofence, a max velocity and a max tilt
+///
+/// The `move_*` methods check their targets against the limits and reject or clamp them
+/// according to the `LimitPolicy`. Use `monitor` to also watch the live state, which catches
+/// breaches from commands sent around this wrapper, from wind, or from paths that cut corners of
+/// a concave fence.
+///
+/// The angle rate commands, `move_by_manual_async` and `move_by_motor_pwms_async` are not
+/// wrapped, as the tilt they lead to cannot be checked before flying; send them through `client`
+/// and rely on `monitor`.
+///
+/// ```no_run
+/// # use std::time::{Duration, Instant};
+/// # use airsim_client::{BreachAction, DrivetrainType, Geofence, MultiRotorClient, NetworkResult, Position3, SafeMultiRotor, YawMode};
+/// # async fn run(client: MultiRotorClient) -> NetworkResult<()> {
+/// let safe = SafeMultiRotor::new(client, Geofence::square(100.0, 2.0, 60.0)?)
+///     .with_max_velocity(8.0)
+///     .with_max_tilt(0.5)
+///     .with_breach_action(BreachAction::Land { timeout_sec: 60.0 });
+/// let _monitor = safe.monitor(Duration::from_millis(200));
+///
+/// let target = Position3::new(50.0, 0.0, -20.0);
+/// safe.move_to_position_async(target, 5.0, 60.0, DrivetrainType::MaxDegreeOfFreedom, YawMode::new(true, 0.0))?
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SafeMultiRotor {
+    client: MultiRotorClient,
+    geofence: Arc<Geofence>,
+    max_velocity: f32,
+    max_tilt: f32,
+    policy: LimitPolicy,
+    breach_action: BreachAction,
+    breach_holdoff: Duration,
+    frame: Option<LocalFrame>,
+}
+
+impl SafeMultiRotor {
+    /// Wrap a client, by default only the geofence is enforced and violating commands are rejected
+    pub fn new(client: MultiRotorClient, geofence: Geofence) -> Self {
+        Self {
+            client,
+            geofence: Arc::new(geofence),
+            max_velocity: f32::INFINITY,
+            max_tilt: f32::INFINITY,
+            policy: LimitPolicy::Reject,
+            breach_action: BreachAction::Hover,
+            breach_holdoff: DEFAULT_BREACH_HOLDOFF,
+            frame: None,
+        }
+    }
+
+    /// Max speed in m/s
+    pub fn with_max_velocity(mut self, max_velocity: f32) -> Self {
+        self.max_velocity = max_velocity;
+        self
+    }
+
+    /// Max roll and pitch in radians
+    pub fn with_max_tilt(mut self, max_tilt: f32) -> Self {
+        self.max_tilt = max_tilt;
+        self
+    }
+
+    pub fn with_policy(mut self, policy: LimitPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// What the monitor does on a breach, defaults to `BreachAction::Hover`
+    pub fn with_breach_action(mut self, breach_action: BreachAction) -> Self {
+        self.breach_action = breach_action;
+        self
+    }
+
+    /// How long the monitor waits for the breach action to end a breach before starting it again,
+    /// defaults to 3 seconds
+    pub fn with_breach_holdoff(mut self, breach_holdoff: Duration) -> Self {
+        self.breach_holdoff = breach_holdoff;
+        self
+    }
+
+    /// Local frame of the geofence, needed to check GPS targets, e.g. the frame the fence was
+    /// built with by `Geofence::from_geo`
+    pub fn with_local_frame(mut self, frame: LocalFrame) -> Self {
+        self.frame = Some(frame);
+        self
+    }
+
+    /// The wrapped client, commands sent through it directly are only checked by `monitor`
+    pub fn client(&self) -> &MultiRotorClient {
+        &self.client
+    }
+
+    pub fn geofence(&self) -> &Geofence {
+        &self.geofence
+    }
+
+    fn violation(&self, message: String) -> NetworkResult<()> {
+        match self.policy {
+            LimitPolicy::Reject => Err(NetworkError::SafetyViolation { message }),
+            LimitPolicy::Clamp => {
+                log::warn!("Clamping command: {message}");
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn check_position(&self, position: Position3) -> NetworkResult<Position3> {
+        if !self.geofence.contains(&position) {
+            self.violation(format!("target {position:?} is outside the geofence"))?;
+        }
+        Ok(self.geofence.clamp(&position))
+    }
+
+    /// A NaN, infinite or negative speed is a violation, clamped to 0.0
+    pub(crate) fn check_speed(&self, speed: f32) -> NetworkResult<f32> {
+        if !speed.is_finite() || speed < 0.0 {
+            self.violation(format!("velocity {speed} m/s is not a valid speed"))?;
+            return Ok(0.0);
+        }
+        if speed > self.max_velocity {
+            self.violation(format!("velocity {speed} m/s is above {} m/s", self.max_velocity))?;
+        }
+        Ok(speed.min(self.max_velocity))
+    }
+
+    /// Scale a velocity vector down to the max velocity, keeping its direction, a velocity with a
+    /// NaN or infinite component is clamped to zero
+    pub(crate) fn check_velocity(&self, velocity: Vector3) -> NetworkResult<Vector3> {
+        let speed = velocity.norm();
+        let limited = self.check_speed(speed)?;
+        Ok(if limited > 0.0 {
+            velocity * (limited / speed)
+        } else {
+            Vector3::default()
+        })
+    }
+
+    pub(crate) fn check_geopoint(&self, geopoint: GeoPoint) -> NetworkResult<GeoPoint> {
+        let frame = self.frame.ok_or_else(|| NetworkError::SafetyViolation {
+            message: "GPS targets need the local frame of the geofence, see `with_local_frame`".to_string(),
+        })?;
+        let position = frame.geo_to_ned(&geopoint);
+        if self.geofence.contains(&position) {
+            return Ok(geopoint);
+        }
+        self.check_position(position)
+            .map(|position| frame.ned_to_geo(&position))
+    }
+
+    pub(crate) fn check_tilt(&self, angle: f32) -> NetworkResult<f32> {
+        if angle.is_nan() {
+            self.violation(format!("tilt {angle} rad is not a valid angle"))?;
+            return Ok(0.0);
+        }
+        if angle.abs() > self.max_tilt {
+            self.violation(format!("tilt {angle} rad is above {} rad", self.max_tilt))?;
+        }
+        Ok(angle.clamp(-self.max_tilt, self.max_tilt))
+    }
+
+    pub(crate) fn check_z(&self, z: f32) -> NetworkResult<f32> {
+        let altitude = -z;
+        if !(self.geofence.min_altitude()..=self.geofence.max_altitude()).contains(&altitude) {
+            self.violation(format!("altitude {altitude} m is outside the geofence"))?;
+        }
+        Ok(z.clamp(-self.geofence.max_altitude(), -self.geofence.min_altitude()))
+    }
+
+    /// Fly to a position, see `MultiRotorClient::move_to_position_async`
+    pub fn move_to_position_async(
+        &self,
+        position: Position3,
+        velocity: f32,
+        timeout_sec: f32,
+        drivetrain: DrivetrainType,
+        yaw_mode: YawMode,
+    ) -> NetworkResult<MotionTask> {
+        let position = self.check_position(position)?;
+        let velocity = self.check_speed(velocity)?;
+        Ok(self
+            .client
+            .move_to_position_async(position, velocity, timeout_sec, drivetrain, yaw_mode, None, None))
+    }
+
+    /// Fly to a GPS location, see `MultiRotorClient::move_to_gps_async`
+    ///
+    /// Needs the local frame of the geofence, see `with_local_frame`
+    pub fn move_to_gps_async(
+        &self,
+        geopoint: GeoPoint,
+        velocity: f32,
+        timeout_sec: f32,
+        drivetrain: DrivetrainType,
+        yaw_mode: YawMode,
+    ) -> NetworkResult<MotionTask> {
+        let geopoint = self.check_geopoint(geopoint)?;
+        let velocity = self.check_speed(velocity)?;
+        Ok(self
+            .client
+            .move_to_gps_async(geopoint, velocity, timeout_sec, drivetrain, yaw_mode, None, None))
+    }
+
+    /// Fly along waypoints, see `MultiRotorClient::move_on_path_async`
+    ///
+    /// Only the waypoints are checked, the monitor catches paths cutting corners of a concave fence
+    pub fn move_on_path_async(
+        &self,
+        path: Path,
+        velocity: f32,
+        timeout_sec: f32,
+        drivetrain: DrivetrainType,
+        yaw_mode: YawMode,
+    ) -> NetworkResult<MotionTask> {
+        let waypoints = path
+            .0
+            .into_iter()
+            .map(|waypoint| self.check_position(waypoint.into()).map(Vector3::from))
+            .collect::<NetworkResult<_>>()?;
+        let velocity = self.check_speed(velocity)?;
+        Ok(self
+            .client
+            .move_on_path_async(Path(waypoints), velocity, timeout_sec, drivetrain, yaw_mode, None, None))
+    }
+
+    /// Climb or descend to an altitude, see `MultiRotorClient::move_to_z_async`
+    pub fn move_to_z_async(
+        &self,
+        z: f32,
+        velocity: f32,
+        timeout_sec: f32,
+        yaw_mode: YawMode,
+    ) -> NetworkResult<MotionTask> {
+        let z = self.check_z(z)?;
+        let velocity = self.check_speed(velocity)?;
+        Ok(self
+            .client
+            .move_to_z_async(z, velocity, timeout_sec, yaw_mode, None, None))
+    }
+
+    /// Fly with a velocity in the local NED frame, see `MultiRotorClient::move_by_velocity_async`
+    pub fn move_by_velocity_async(
+        &self,
+        velocity: Velocity3,
+        duration: f32,
+        drivetrain: DrivetrainType,
+        yaw_mode: YawMode,
+    ) -> NetworkResult<MotionTask> {
+        let velocity = self.check_velocity(velocity.into())?;
+        Ok(self
+            .client
+            .move_by_velocity_async(velocity.into(), duration, drivetrain, yaw_mode))
+    }
+
+    /// Fly with a horizontal velocity at an altitude, see `MultiRotorClient::move_by_velocity_z_async`
+    pub fn move_by_velocity_z_async(
+        &self,
+        velocity: Velocity2,
+        z: f32,
+        duration: f32,
+        drivetrain: DrivetrainType,
+        yaw_mode: YawMode,
+    ) -> NetworkResult<MotionTask> {
+        let z = self.check_z(z)?;
+        let limited = self.check_velocity(Vector3::new(velocity.vx, velocity.vy, 0.0))?;
+        Ok(self.client.move_by_velocity_z_async(
+            Velocity2::new(limited.x, limited.y),
+            z,
+            duration,
+            drivetrain,
+            yaw_mode,
+        ))
+    }
+
+    /// Fly with a velocity in the body frame, see `MultiRotorClient::move_by_velocity_body_frame_async`
+    pub fn move_by_velocity_body_frame_async(
+        &self,
+        velocity: Velocity3,
+        duration: f32,
+        drivetrain: DrivetrainType,
+        yaw_mode: YawMode,
+    ) -> NetworkResult<MotionTask> {
+        let velocity = self.check_velocity(velocity.into())?;
+        Ok(self
+            .client
+            .move_by_velocity_body_frame_async(velocity.into(), duration, drivetrain, yaw_mode))
+    }
+
+    /// Fly with a horizontal velocity in the body frame at an altitude, see
+    /// `MultiRotorClient::move_by_velocity_z_body_frame_async`
+    pub fn move_by_velocity_z_body_frame_async(
+        &self,
+        velocity: Velocity2,
+        z: f32,
+        duration: f32,
+        drivetrain: DrivetrainType,
+        yaw_mode: YawMode,
+    ) -> NetworkResult<MotionTask> {
+        let z = self.check_z(z)?;
+        let limited = self.check_velocity(Vector3::new(velocity.vx, velocity.vy, 0.0))?;
+        Ok(self.client.move_by_velocity_z_body_frame_async(
+            Velocity2::new(limited.x, limited.y),
+            z,
+            duration,
+            drivetrain,
+            yaw_mode,
+        ))
+    }
+
+    /// Hold an attitude at an altitude, see `MultiRotorClient::move_by_roll_pitch_yaw_z_async`
+    pub fn move_by_roll_pitch_yaw_z_async(
+        &self,
+        rotation: Orientation3,
+        z: f32,
+        duration: f32,
+    ) -> NetworkResult<MotionTask> {
+        let rotation = Orientation3::new(
+            self.check_tilt(rotation.roll)?,
+            self.check_tilt(rotation.pitch)?,
+            rotation.yaw,
+        );
+        let z = self.check_z(z)?;
+        Ok(self.client.move_by_roll_pitch_yaw_z_async(rotation, z, duration))
+    }
+
+    /// Hold roll, pitch and a yaw rate at an altitude, see `MultiRotorClient::move_by_roll_pitch_yawrate_z_async`
+    pub fn move_by_roll_pitch_yawrate_z_async(
+        &self,
+        rotation: Orientation2,
+        yaw_rate: f32,
+        z: f32,
+        duration: f32,
+    ) -> NetworkResult<MotionTask> {
+        let rotation = Orientation2::new(self.check_tilt(rotation.roll)?, self.check_tilt(rotation.pitch)?);
+        let z = self.check_z(z)?;
+        Ok(self
+            .client
+            .move_by_roll_pitch_yawrate_z_async(rotation, yaw_rate, z, duration))
+    }
+
+    /// Hold an attitude with a throttle, see `MultiRotorClient::move_by_roll_pitch_yaw_throttle_async`
+    pub fn move_by_roll_pitch_yaw_throttle_async(
+        &self,
+        rotation: Orientation3,
+        throttle: UnitInterval,
+        duration: f32,
+    ) -> NetworkResult<MotionTask> {
+        let rotation = Orientation3::new(
+            self.check_tilt(rotation.roll)?,
+            self.check_tilt(rotation.pitch)?,
+            rotation.yaw,
+        );
+        Ok(self
+            .client
+            .move_by_roll_pitch_yaw_throttle_async(rotation, throttle, duration))
+    }
+
+    /// Hold roll, pitch and a yaw rate with a throttle, see
+    /// `MultiRotorClient::move_by_roll_pitch_yawrate_throttle_async`
+    pub fn move_by_roll_pitch_yawrate_throttle_async(
+        &self,
+        rotation: Orientation2,
+        yaw_rate: f32,
+        throttle: UnitInterval,
+        duration: f32,
+    ) -> NetworkResult<MotionTask> {
+        let rotation = Orientation2::new(self.check_tilt(rotation.roll)?, self.check_tilt(rotation.pitch)?);
+        Ok(self
+            .client
+            .move_by_roll_pitch_yawrate_throttle_async(rotation, yaw_rate, throttle, duration))
+    }
+
+    /// Validate a motion command and send it within the limits, e.g. a `MoveToPosition`
+    ///
+    /// Commands whose targets cannot be checked against the limits are rejected
+    pub fn execute(&self, command: &impl MotionCommand) -> NetworkResult<MotionTask> {
+        command.validate()?;
+        command.start_within(self, Validated(()))
+    }
+
+    /// First limit the kinematic state violates, if any
+    pub fn breach(&self, kinematics: &KinematicsState) -> Option<Breach> {
+        let position = kinematics.position;
+        if (self.geofence.clamp(&position) - position).norm() > FENCE_TOLERANCE {
+            return Some(Breach::Geofence(position));
+        }
+
+        let speed = kinematics.linear_velocity.norm();
+        if speed > self.max_velocity * LIMIT_TOLERANCE {
+            return Some(Breach::Velocity(speed));
+        }
+
+        let tilt = kinematics.roll().abs().max(kinematics.pitch().abs());
+        if tilt > self.max_tilt * LIMIT_TOLERANCE {
+            return Some(Breach::Tilt(tilt));
+        }
+
+        None
+    }
+
+    /// Check the live state once and start the breach action if a limit is violated
+    pub async fn check(&self) -> NetworkResult<Option<Breach>> {
+        let state = self.client.get_multirotor_state().await?;
+        let breach = self.breach(&state.kinematics_estimated);
+        if let Some(breach) = breach {
+            self.start_breach_action(breach);
+        }
+        Ok(breach)
+    }
+
+    fn start_breach_action(&self, breach: Breach) {
+        log::warn!("Safety breach: {breach:?}, starting {:?}", self.breach_action);
+        // the breach action supersedes whatever the vehicle was doing
+        drop(self.breach_action.start(&self.client));
+    }
+
+    /// Check the live state every `period` in the background, until the returned monitor is dropped
+    ///
+    /// The breach action is started when a breach begins and again every breach hold-off while it
+    /// lasts, e.g. when wind pushes a hovering vehicle further out or a command sent around this
+    /// wrapper superseded the breach action.
+    pub fn monitor(&self, period: Duration) -> SafetyMonitor {
+        let running = Arc::new(AtomicBool::new(true));
+        let safe = self.clone();
+        let keep_running = Arc::clone(&running);
+
+        let handle = task::spawn(async move {
+            let mut last_action: Option<Instant> = None;
+            while keep_running.load(Ordering::Acquire) {
+                match safe.client.get_multirotor_state().await {
+                    Ok(state) => match safe.breach(&state.kinematics_estimated) {
+                        Some(breach) if last_action.map_or(true, |t| t.elapsed() >= safe.breach_holdoff) => {
+                            safe.start_breach_action(breach);
+                            last_action = Some(Instant::now());
+                        }
+                        Some(_) => {}
+                        None => last_action = None,
+                    },
+                    Err(e) => log::error!("Could not read the vehicle state: {e}"),
+                }
+                task::sleep(period).await;
+            }
+        });
+
+        SafetyMonitor {
+            running,
+            handle: Some(handle),
+        }
+    }
+}
+
+/// Background check of a `SafeMultiRotor`, stops when dropped
+pub struct SafetyMonitor {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl SafetyMonitor {
+    /// Stop monitoring and wait for the current check to finish
+    pub async fn stop(mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.await;
+        }
+    }
+}
+
+impl Drop for SafetyMonitor {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_std::task;
+    use rmpv::Value;
+
+    use super::{Breach, LimitPolicy, SafeMultiRotor};
+    use crate::clients::test_server::TestServer;
+    use crate::{
+        DrivetrainType, GeoPoint, Geofence, KinematicsState, MoveToPosition, MultiRotorClient, NetworkError, Position3,
+        Quaternion, Vector3, YawMode,
+    };
+
+    /// North and velocity of the last recorded `moveToPosition`
+    fn recorded_params(params: &Mutex<Vec<Value>>) -> Vec<Value> {
+        let params = params.lock().unwrap();
+        vec![params[0].clone(), params[3].clone()]
+    }
+
+    fn kinematics(position: Position3, velocity: Vector3) -> KinematicsState {
+        let zero = Vector3::default();
+        KinematicsState::new(position, Quaternion::identity(), velocity, zero, zero, zero)
+    }
+
+    #[test]
+    fn test_reject_and_clamp() {
+        let params = Arc::new(Mutex::new(vec![]));
+        let recorded = Arc::clone(&params);
+        let server = TestServer::spawn(move |request| {
+            if request.method == "moveToPosition" {
+                *recorded.lock().unwrap() = request.params.clone();
+            }
+            Ok(Value::Boolean(true))
+        });
+
+        task::block_on(async {
+            let client = MultiRotorClient::connect(&server.addr, "").await.unwrap();
+            let safe = SafeMultiRotor::new(client, Geofence::square(10.0, 2.0, 20.0).unwrap()).with_max_velocity(5.0);
+            let outside = Position3::new(30.0, 0.0, -10.0);
+            let (drivetrain, yaw_mode) = (DrivetrainType::MaxDegreeOfFreedom, YawMode::new(true, 0.0));
+
+            assert!(matches!(
+                safe.move_to_position_async(outside, 3.0, 10.0, drivetrain, yaw_mode),
+                Err(NetworkError::SafetyViolation { .. })
+            ));
+
+            // GPS targets cannot be checked without the frame of the fence
+            assert!(matches!(
+                safe.move_to_gps_async(GeoPoint::new(47.0, 8.0, 500.0), 3.0, 10.0, drivetrain, yaw_mode),
+                Err(NetworkError::SafetyViolation { .. })
+            ));
+
+            let safe = safe.with_policy(LimitPolicy::Clamp);
+            safe.move_to_position_async(outside, 8.0, 10.0, drivetrain, yaw_mode)
+                .unwrap()
+                .await
+                .unwrap();
+            assert_eq!(recorded_params(&params), [Value::F32(10.0), Value::F32(5.0)]);
+
+            // builders are clamped the same way
+            params.lock().unwrap().clear();
+            safe.execute(&MoveToPosition::new(outside).velocity(8.0))
+                .unwrap()
+                .await
+                .unwrap();
+            assert_eq!(recorded_params(&params), [Value::F32(10.0), Value::F32(5.0)]);
+        });
+    }
+
+    #[test]
+    fn test_invalid_speed() {
+        let server = TestServer::spawn_default();
+        let client = task::block_on(MultiRotorClient::connect(&server.addr, "")).unwrap();
+        let safe = SafeMultiRotor::new(client, Geofence::square(10.0, 2.0, 20.0).unwrap()).with_max_velocity(5.0);
+
+        for speed in [f32::NAN, f32::INFINITY, -1.0] {
+            assert!(matches!(
+                safe.check_speed(speed),
+                Err(NetworkError::SafetyViolation { .. })
+            ));
+        }
+        let nan = Vector3::new(f32::NAN, 0.0, 0.0);
+        assert!(matches!(
+            safe.check_velocity(nan),
+            Err(NetworkError::SafetyViolation { .. })
+        ));
+        assert!(safe.check_tilt(f32::NAN).is_err());
+        assert_eq!(safe.check_velocity(Vector3::default()).unwrap(), Vector3::default());
+
+        let safe = safe.with_policy(LimitPolicy::Clamp);
+        assert_eq!(safe.check_speed(f32::NAN).unwrap(), 0.0);
+        assert_eq!(safe.check_speed(-1.0).unwrap(), 0.0);
+        assert_eq!(safe.check_velocity(nan).unwrap(), Vector3::default());
+        assert_eq!(
+            safe.check_velocity(Vector3::new(0.0, 8.0, 6.0)).unwrap(),
+            Vector3::new(0.0, 4.0, 3.0)
+        );
+    }
+
+    #[test]
+    fn test_breach() {
+        task::block_on(async {
+            let server = TestServer::spawn_default();
+            let client = MultiRotorClient::connect(&server.addr, "").await.unwrap();
+            let safe = SafeMultiRotor::new(client, Geofence::square(10.0, 2.0, 20.0).unwrap()).with_max_velocity(5.0);
+
+            let inside = Position3::new(0.0, 0.0, -10.0);
+            assert_eq!(safe.breach(&kinematics(inside, Vector3::new(5.2, 0.0, 0.0))), None);
+            assert_eq!(
+                safe.breach(&kinematics(inside, Vector3::new(8.0, 0.0, 0.0))),
+                Some(Breach::Velocity(8.0))
+            );
+            // on the fence, e.g. after flying to a clamped target
+            assert_eq!(
+                safe.breach(&kinematics(Position3::new(10.0, 0.0, -10.0), Vector3::default())),
+                None
+            );
+            assert!(matches!(
+                safe.breach(&kinematics(Position3::new(12.0, 0.0, -10.0), Vector3::default())),
+                Some(Breach::Geofence(_))
+            ));
+        });
+    }
+}
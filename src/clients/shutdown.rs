@@ -0,0 +1,360 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use async_std::{future, task};
+#[cfg(unix)]
+use futures::future::Either;
+use rmpv::Value;
+
+use crate::codec::FromMsgpack;
+use crate::error::NetworkResult;
+use crate::NetworkError;
+
+use super::airsim_client::AirsimClient;
+
+/// Exit code of a process terminated by SIGINT
+const SIGINT_EXIT_CODE: i32 = 130;
+
+/// Exit code of a process terminated by SIGTERM
+#[cfg(unix)]
+const SIGTERM_EXIT_CODE: i32 = 143;
+
+/// How long dropping a guard waits for the release, on top of the landing timeout
+const DROP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a `ShutdownGuard` does with a vehicle before disarming it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReleaseAction {
+    /// Hover in place, for multirotors
+    Hover,
+    /// Land in place and wait up to `timeout_sec` for the landing, for multirotors
+    Land { timeout_sec: f32 },
+    /// Only cancel the last task, e.g. for cars
+    Stop,
+}
+
+struct Inner {
+    client: AirsimClient,
+    vehicle_names: Vec<String>,
+    action: ReleaseAction,
+    released: AtomicBool,
+}
+
+impl Inner {
+    /// Upper bound of the release when the guard is dropped
+    fn drop_timeout(&self) -> Duration {
+        match self.action {
+            ReleaseAction::Land { timeout_sec } if timeout_sec.is_finite() && timeout_sec > 0.0 => {
+                DROP_TIMEOUT + Duration::from_secs_f32(timeout_sec.min(3600.0))
+            }
+            _ => DROP_TIMEOUT,
+        }
+    }
+
+    /// Send the hover or land command and wait for it to complete, for at most the drop timeout
+    async fn apply(&self, method: &str, params: Vec<Value>) -> NetworkResult<()> {
+        let drop_timeout = self.drop_timeout();
+        let completion = async {
+            let pending = self.client.send_rpc(method.into(), Some(params)).await?;
+            pending.completion().await.and_then(Value::from_response)
+        };
+        match future::timeout(drop_timeout, completion).await {
+            Ok(result) => result.map(|_| ()),
+            Err(_) => Err(NetworkError::Timeout {
+                message: format!("{method} did not complete within {drop_timeout:?}"),
+            }),
+        }
+    }
+
+    async fn release(&self) -> NetworkResult<()> {
+        if self.released.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        // keep going after an error, so every vehicle gets as far as possible
+        let mut first_error = None;
+        for vehicle_name in &self.vehicle_names {
+            log::info!("Releasing vehicle {vehicle_name:?}");
+            let name = Value::from(vehicle_name.as_str());
+
+            if let Err(e) = self.client.call_raw("cancelLastTask", vec![name.clone()]).await {
+                log::error!("Releasing vehicle {vehicle_name:?} failed at cancelLastTask: {e}");
+                first_error.get_or_insert(e);
+            }
+
+            let action = match self.action {
+                ReleaseAction::Hover => Some(("hover", vec![name.clone()])),
+                ReleaseAction::Land { timeout_sec } => Some(("land", vec![Value::F32(timeout_sec), name.clone()])),
+                ReleaseAction::Stop => None,
+            };
+            if let Some((method, params)) = action {
+                // a landing takes longer than the RPC timeout, only the drop timeout bounds it
+                if let Err(e) = self.apply(method, params).await {
+                    // leave a vehicle that is not safely hovering or landed armed and under API control
+                    log::error!("Releasing vehicle {vehicle_name:?} failed at {method}, keeping it armed: {e}");
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            }
+
+            let steps = [
+                ("armDisarm", vec![Value::Boolean(false), name.clone()]),
+                ("enableApiControl", vec![Value::Boolean(false), name]),
+            ];
+            for (method, params) in steps {
+                if let Err(e) = self.client.call_raw(method, params).await {
+                    log::error!("Releasing vehicle {vehicle_name:?} failed at {method}: {e}");
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Brings vehicles into a safe state when the program ends
+///
+/// On `release`, on drop and, with `with_signal_handler`, on SIGINT/SIGTERM, each vehicle's last
+/// task is cancelled, the `ReleaseAction` is applied, and the vehicle is disarmed and API control
+/// is disabled. A vehicle whose hover or landing fails stays armed and under API control. Vehicles
+/// are released only once, whichever comes first.
+///
+/// Dropping the guard blocks the current thread until the vehicles are released, for at most 10
+/// seconds plus the landing timeout, prefer calling `release` at the end of an async program.
+///
+/// ```no_run
+/// # use airsim_client::{MultiRotorClient, NetworkResult, ReleaseAction, ShutdownGuard};
+/// # async fn run(client: MultiRotorClient) -> NetworkResult<()> {
+/// let guard = ShutdownGuard::new(client.airsim_client(), &[client.vehicle_name()], ReleaseAction::Hover)
+///     .with_signal_handler()?;
+///
+/// client.take_off_async(20.0).await?;
+/// // fly, a ctrl-c from here on leaves the drone hovering and disarmed
+///
+/// guard.release().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ShutdownGuard {
+    inner: Arc<Inner>,
+}
+
+impl ShutdownGuard {
+    /// Guard the given vehicles of a client
+    ///
+    /// args:
+    ///     client (&AirsimClient): Client used to release the vehicles, the guard keeps its own handle
+    ///     vehicle_names (&[&str]): Vehicles to release
+    ///     action (ReleaseAction): What to do with each vehicle before disarming it
+    pub fn new(client: &AirsimClient, vehicle_names: &[&str], action: ReleaseAction) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                client: client.clone(),
+                vehicle_names: vehicle_names.iter().map(|v| v.to_string()).collect(),
+                action,
+                released: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Release the vehicles on SIGINT (ctrl-c) or SIGTERM and then exit the process, with exit code
+    /// 130 after SIGINT and 143 after SIGTERM like a process killed by the signal
+    ///
+    /// The signals are handled on a background thread with its own tokio runtime, so this works
+    /// with any async runtime.
+    pub fn with_signal_handler(self) -> NetworkResult<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let inner = Arc::clone(&self.inner);
+
+        thread::Builder::new().name("airsim-shutdown".into()).spawn(move || {
+            let exit_code = match runtime.block_on(shutdown_signal()) {
+                Ok(exit_code) => exit_code,
+                Err(e) => {
+                    log::error!("Could not listen for shutdown signals: {e}");
+                    return;
+                }
+            };
+
+            log::warn!("Shutdown signal received, releasing vehicles");
+            if let Err(e) = task::block_on(inner.release()) {
+                log::error!("Releasing vehicles failed: {e}");
+            }
+            std::process::exit(exit_code);
+        })?;
+
+        Ok(self)
+    }
+
+    /// Returns true once the vehicles have been released
+    pub fn is_released(&self) -> bool {
+        self.inner.released.load(Ordering::Acquire)
+    }
+
+    /// Release the vehicles now, later calls and the drop of the guard do nothing
+    pub async fn release(&self) -> NetworkResult<()> {
+        self.inner.release().await
+    }
+}
+
+impl Drop for ShutdownGuard {
+    fn drop(&mut self) {
+        if self.is_released() {
+            return;
+        }
+        let drop_timeout = self.inner.drop_timeout();
+        match task::block_on(future::timeout(drop_timeout, self.inner.release())) {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => log::error!("Releasing vehicles failed: {e}"),
+            Err(_) => log::error!("Releasing vehicles took longer than {drop_timeout:?}, giving up"),
+        }
+    }
+}
+
+/// Wait for SIGINT or SIGTERM, returns the exit code for the received signal
+async fn shutdown_signal() -> Result<i32, NetworkError> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        let interrupt = Box::pin(tokio::signal::ctrl_c());
+        let terminated = Box::pin(terminate.recv());
+        let exit_code = match futures::future::select(interrupt, terminated).await {
+            Either::Left((result, _)) => result.map(|_| SIGINT_EXIT_CODE)?,
+            Either::Right(_) => SIGTERM_EXIT_CODE,
+        };
+        Ok(exit_code)
+    }
+
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await?;
+        Ok(SIGINT_EXIT_CODE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use async_std::task;
+    use rmpv::Value;
+
+    use super::{ReleaseAction, ShutdownGuard};
+    use crate::clients::test_server::TestServer;
+    use crate::{AirsimClient, ConnectOptions};
+
+    #[test]
+    fn test_release_once() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let server_calls = Arc::clone(&calls);
+        let server = TestServer::spawn(move |request| {
+            server_calls.lock().unwrap().push(request.method.clone());
+            Ok(Value::Boolean(true))
+        });
+
+        task::block_on(async {
+            let client = AirsimClient::connect(&server.addr, "").await.unwrap();
+            let guard = ShutdownGuard::new(&client, &["Drone1"], ReleaseAction::Land { timeout_sec: 10.0 });
+            calls.lock().unwrap().clear();
+
+            guard.release().await.unwrap();
+            assert!(guard.is_released());
+            drop(guard);
+        });
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            ["cancelLastTask", "land", "armDisarm", "enableApiControl"]
+        );
+    }
+
+    #[test]
+    fn test_release_on_drop() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let server_calls = Arc::clone(&calls);
+        let server = TestServer::spawn(move |request| {
+            server_calls.lock().unwrap().push(request.method.clone());
+            Ok(Value::Boolean(true))
+        });
+
+        let client = task::block_on(AirsimClient::connect(&server.addr, "")).unwrap();
+        drop(ShutdownGuard::new(&client, &["Car1", "Car2"], ReleaseAction::Stop));
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.iter().filter(|m| *m == "armDisarm").count(), 2);
+        assert!(!calls.contains(&"hover".to_string()));
+    }
+
+    #[test]
+    fn test_landing_outlasts_rpc_timeout() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let server_calls = Arc::clone(&calls);
+        let server = TestServer::spawn(move |request| {
+            if request.method == "land" {
+                thread::sleep(Duration::from_millis(300));
+            }
+            server_calls.lock().unwrap().push(request.method.clone());
+            Ok(Value::Boolean(true))
+        });
+
+        task::block_on(async {
+            let options = ConnectOptions::default()
+                .with_address(&server.addr)
+                .with_rpc_timeout(Some(Duration::from_millis(50)));
+            let client = AirsimClient::connect_with(&options).await.unwrap();
+            let guard = ShutdownGuard::new(&client, &["Drone1"], ReleaseAction::Land { timeout_sec: 10.0 });
+            calls.lock().unwrap().clear();
+
+            guard.release().await.unwrap();
+        });
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            ["cancelLastTask", "land", "armDisarm", "enableApiControl"]
+        );
+    }
+
+    #[test]
+    fn test_failed_hover_keeps_vehicle_armed() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let server_calls = Arc::clone(&calls);
+        let server = TestServer::spawn(move |request| {
+            server_calls.lock().unwrap().push(request.method.clone());
+            match (request.method.as_str(), request.params.first()) {
+                ("hover", Some(Value::String(name))) if name.as_str() == Some("Drone1") => {
+                    Err(Value::from("vehicle not found"))
+                }
+                _ => Ok(Value::Boolean(true)),
+            }
+        });
+
+        task::block_on(async {
+            let client = AirsimClient::connect(&server.addr, "").await.unwrap();
+            let guard = ShutdownGuard::new(&client, &["Drone1", "Drone2"], ReleaseAction::Hover);
+            calls.lock().unwrap().clear();
+
+            assert!(guard.release().await.is_err());
+        });
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                "cancelLastTask",
+                "hover",
+                "cancelLastTask",
+                "hover",
+                "armDisarm",
+                "enableApiControl"
+            ]
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::time::Duration;
+
+use async_std::task;
+
+use crate::error::NetworkResult;
+use crate::NetworkError;
+
+use super::airsim_client::AirsimClient;
+
+/// Default interval between `simIsPause` polls while waiting for a step to finish
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Default wall-clock time a single step is allowed to take
+const DEFAULT_STEP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Lockstep controller for the simulation clock
+///
+/// The simulation is paused when the controller is created and only advances through
+/// `step_time` or `step_frames`. Each step returns once the simulation is paused again, so
+/// observations read between steps (e.g. `get_multirotor_state`, `sim_get_image`) always
+/// belong to the same simulated instant, which gives reproducible control loops.
+///
+/// ```no_run
+/// # use airsim_client::{MultiRotorClient, NetworkResult, SteppedSim};
+/// # async fn run(client: MultiRotorClient) -> NetworkResult<()> {
+/// let mut sim = SteppedSim::new(client.airsim_client()).await?;
+/// for _ in 0..100 {
+///     sim.step_time(0.02).await?;
+///     let state = client.get_multirotor_state().await?;
+///     // compute and send the next command from `state`
+/// }
+/// sim.release().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct SteppedSim<'a> {
+    client: &'a AirsimClient,
+    poll_interval: Duration,
+    step_timeout: Duration,
+    steps: u64,
+}
+
+impl<'a> SteppedSim<'a> {
+    /// Pause the simulation and take control of its clock
+    pub async fn new(client: &'a AirsimClient) -> NetworkResult<SteppedSim<'a>> {
+        client.sim_pause(true).await?;
+
+        Ok(Self {
+            client,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            step_timeout: DEFAULT_STEP_TIMEOUT,
+            steps: 0,
+        })
+    }
+
+    /// Interval between `simIsPause` polls while waiting for a step to finish
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Wall-clock time a single step is allowed to take before `NetworkError::Timeout` is returned
+    pub fn with_step_timeout(mut self, step_timeout: Duration) -> Self {
+        self.step_timeout = step_timeout;
+        self
+    }
+
+    /// Number of steps taken since the controller was created
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Advance the simulation by exactly `seconds` of simulated time and wait until it is paused again
+    ///
+    /// args:
+    ///     seconds (f64): Simulated time to advance
+    pub async fn step_time(&mut self, seconds: f64) -> NetworkResult<()> {
+        self.client.sim_continue_for_time(seconds).await?;
+        self.wait_for_pause().await?;
+        self.steps += 1;
+        Ok(())
+    }
+
+    /// Advance the simulation by exactly `frames` frames and wait until it is paused again
+    ///
+    /// args:
+    ///     frames (u32): Frames to advance
+    pub async fn step_frames(&mut self, frames: u32) -> NetworkResult<()> {
+        self.client.sim_continue_for_frames(frames).await?;
+        self.wait_for_pause().await?;
+        self.steps += 1;
+        Ok(())
+    }
+
+    /// Release the simulation clock, letting the simulation run freely again
+    pub async fn release(self) -> NetworkResult<()> {
+        self.client.sim_pause(false).await.map(|_| ())
+    }
+
+    async fn wait_for_pause(&self) -> NetworkResult<()> {
+        let wait = async {
+            while !self.client.sim_is_pause().await? {
+                task::sleep(self.poll_interval).await;
+            }
+            Ok(())
+        };
+
+        async_std::future::timeout(self.step_timeout, wait)
+            .await
+            .map_err(|_| NetworkError::Timeout {
+                message: format!("simulation did not pause within {:?}", self.step_timeout),
+            })?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use async_std::task;
+    use rmpv::Value;
+
+    use super::SteppedSim;
+    use crate::clients::test_server::TestServer;
+    use crate::{AirsimClient, NetworkError};
+
+    /// Methods and parameters of the requests the server received
+    type Calls = Arc<Mutex<Vec<(String, Vec<Value>)>>>;
+
+    /// Simulation that pauses again after `polls` calls of `simIsPause`, or never if `None`
+    fn simulation(polls: Option<usize>) -> (TestServer, Calls) {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let recorded = Arc::clone(&calls);
+        let running_polls = Mutex::new(None);
+
+        let server = TestServer::spawn(move |request| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((request.method.clone(), request.params.clone()));
+            let mut running_polls = running_polls.lock().unwrap();
+            match request.method.as_str() {
+                "simContinueForTime" | "simContinueForFrames" => {
+                    *running_polls = Some(0);
+                    Ok(Value::Nil)
+                }
+                "simIsPause" => {
+                    let paused = match (running_polls.as_mut(), polls) {
+                        (Some(count), Some(polls)) => {
+                            *count += 1;
+                            *count > polls
+                        }
+                        (Some(_), None) => false,
+                        (None, _) => true,
+                    };
+                    Ok(Value::Boolean(paused))
+                }
+                _ => Ok(Value::Boolean(true)),
+            }
+        });
+
+        (server, calls)
+    }
+
+    fn methods(calls: &Calls) -> Vec<String> {
+        calls.lock().unwrap().iter().map(|(method, _)| method.clone()).collect()
+    }
+
+    #[test]
+    fn test_step_waits_for_pause() {
+        let (server, calls) = simulation(Some(3));
+
+        task::block_on(async {
+            let client = AirsimClient::connect(&server.addr, "").await.unwrap();
+            let mut sim = SteppedSim::new(&client).await.unwrap();
+            calls.lock().unwrap().clear();
+
+            sim.step_time(0.02).await.unwrap();
+            assert_eq!(
+                calls.lock().unwrap()[0],
+                ("simContinueForTime".to_string(), vec![Value::F64(0.02)])
+            );
+            // three polls while running, the fourth sees the pause
+            assert_eq!(methods(&calls).iter().filter(|m| *m == "simIsPause").count(), 4);
+
+            calls.lock().unwrap().clear();
+            sim.step_frames(5).await.unwrap();
+            assert_eq!(
+                calls.lock().unwrap()[0],
+                ("simContinueForFrames".to_string(), vec![Value::from(5)])
+            );
+            assert_eq!(methods(&calls).last().unwrap(), "simIsPause");
+            assert_eq!(sim.steps(), 2);
+        });
+    }
+
+    #[test]
+    fn test_step_times_out_when_not_paused() {
+        let (server, _) = simulation(None);
+
+        task::block_on(async {
+            let client = AirsimClient::connect(&server.addr, "").await.unwrap();
+            let mut sim = SteppedSim::new(&client)
+                .await
+                .unwrap()
+                .with_step_timeout(Duration::from_millis(50));
+
+            assert!(matches!(sim.step_time(0.02).await, Err(NetworkError::Timeout { .. })));
+            assert_eq!(sim.steps(), 0);
+        });
+    }
+}
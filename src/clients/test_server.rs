@@ -0,0 +1,59 @@
+use std::io::Write;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use msgpack_rpc::message::{Message, Request, Response};
+use rmpv::Value;
+
+type Handler = dyn Fn(&Request) -> Result<Value, Value> + Send + Sync;
+
+/// Minimal msgpack-rpc server for tests, answering every request with the result of a handler
+pub(crate) struct TestServer {
+    pub(crate) addr: String,
+    connections: Arc<AtomicUsize>,
+}
+
+impl TestServer {
+    pub(crate) fn spawn(handler: impl Fn(&Request) -> Result<Value, Value> + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let accepted = Arc::clone(&connections);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = stream.try_clone().unwrap();
+                let handler = Arc::clone(&handler);
+                accepted.fetch_add(1, Ordering::SeqCst);
+
+                thread::spawn(move || {
+                    while let Ok(Message::Request(request)) = Message::decode(&mut reader) {
+                        let response = Message::Response(Response {
+                            id: request.id,
+                            result: handler(&request),
+                        });
+                        if stream.write_all(&response.pack().unwrap()).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        Self { addr, connections }
+    }
+
+    /// Server answering `true` to every request
+    pub(crate) fn spawn_default() -> Self {
+        Self::spawn(|_| Ok(Value::Boolean(true)))
+    }
+
+    /// Number of connections accepted so far
+    pub(crate) fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+}
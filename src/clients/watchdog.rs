@@ -0,0 +1,173 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_std::task::{self, JoinHandle};
+
+use super::multi_rotor_client::MultiRotorClient;
+use super::safe_multi_rotor::BreachAction;
+
+/// Shortest interval between two checks of a `Watchdog`
+const MIN_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+struct State {
+    last_feed: Mutex<Instant>,
+    tripped: AtomicBool,
+    running: AtomicBool,
+}
+
+/// Cloneable handle to feed a `Watchdog` from other tasks or threads
+#[derive(Clone)]
+pub struct Heartbeat {
+    state: Arc<State>,
+}
+
+impl Heartbeat {
+    /// Signal that the application is alive, re-arms a tripped watchdog
+    pub fn feed(&self) {
+        *self.state.last_feed.lock().unwrap() = Instant::now();
+        if self.state.tripped.swap(false, Ordering::AcqRel) {
+            log::info!("Watchdog fed again, re-armed");
+        }
+    }
+}
+
+/// Runs a failsafe action when the application stops feeding it
+///
+/// Feed the watchdog with every heartbeat or command of the control loop. If no feed arrives
+/// within the timeout, e.g. because the controller process hangs, the failsafe action is started
+/// once. Feeding again re-arms the watchdog. The watchdog stops when dropped.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use airsim_client::{BreachAction, MultiRotorClient, NetworkResult, Velocity3, Watchdog, DrivetrainType, YawMode};
+/// # async fn run(client: MultiRotorClient) -> NetworkResult<()> {
+/// let watchdog = Watchdog::start(&client, Duration::from_millis(500), BreachAction::Hover);
+/// loop {
+///     let velocity = Velocity3::new(1.0, 0.0, 0.0); // computed by the controller
+///     client.move_by_velocity_async(velocity, 0.1, DrivetrainType::MaxDegreeOfFreedom, YawMode::new(true, 0.0));
+///     watchdog.feed();
+/// #   break;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Watchdog {
+    heartbeat: Heartbeat,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Watchdog {
+    /// Start watching, the timeout starts now
+    ///
+    /// args:
+    ///     client (&MultiRotorClient): Client of the vehicle to bring into a safe state
+    ///     timeout (Duration): Longest allowed time between two feeds
+    ///     action (BreachAction): What to do when the feeds stop
+    pub fn start(client: &MultiRotorClient, timeout: Duration, action: BreachAction) -> Self {
+        let state = Arc::new(State {
+            last_feed: Mutex::new(Instant::now()),
+            tripped: AtomicBool::new(false),
+            running: AtomicBool::new(true),
+        });
+        let check_interval = (timeout / 4).max(MIN_CHECK_INTERVAL);
+
+        let handle = task::spawn({
+            let client = client.clone();
+            let state = Arc::clone(&state);
+
+            async move {
+                while state.running.load(Ordering::Acquire) {
+                    let elapsed = state.last_feed.lock().unwrap().elapsed();
+                    if elapsed > timeout && !state.tripped.swap(true, Ordering::AcqRel) {
+                        log::warn!("Watchdog not fed for {elapsed:?}, starting {action:?}");
+                        let failsafe = action.start(&client);
+                        task::spawn(async move {
+                            if let Err(e) = failsafe.await {
+                                log::error!("Failsafe action failed: {e}");
+                            }
+                        });
+                    }
+                    task::sleep(check_interval).await;
+                }
+            }
+        });
+
+        Self {
+            heartbeat: Heartbeat { state },
+            handle: Some(handle),
+        }
+    }
+
+    /// Signal that the application is alive, re-arms a tripped watchdog
+    pub fn feed(&self) {
+        self.heartbeat.feed()
+    }
+
+    /// Handle to feed the watchdog from elsewhere
+    pub fn heartbeat(&self) -> Heartbeat {
+        self.heartbeat.clone()
+    }
+
+    /// Returns true if the failsafe action was started and the watchdog was not fed since
+    pub fn is_tripped(&self) -> bool {
+        self.heartbeat.state.tripped.load(Ordering::Acquire)
+    }
+
+    /// Stop watching and wait for the background task to end
+    pub async fn stop(mut self) {
+        self.heartbeat.state.running.store(false, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.await;
+        }
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.heartbeat.state.running.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use async_std::task;
+    use rmpv::Value;
+
+    use super::{BreachAction, Watchdog};
+    use crate::clients::test_server::TestServer;
+    use crate::MultiRotorClient;
+
+    #[test]
+    fn test_failsafe_when_not_fed() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let recorded = Arc::clone(&calls);
+        let server = TestServer::spawn(move |request| {
+            recorded.lock().unwrap().push(request.method.clone());
+            Ok(Value::Boolean(true))
+        });
+        let hovers = || calls.lock().unwrap().iter().filter(|m| *m == "hover").count();
+
+        task::block_on(async {
+            let client = MultiRotorClient::connect(&server.addr, "").await.unwrap();
+            let watchdog = Watchdog::start(&client, Duration::from_millis(100), BreachAction::Hover);
+
+            for _ in 0..5 {
+                task::sleep(Duration::from_millis(40)).await;
+                watchdog.feed();
+            }
+            assert_eq!(hovers(), 0);
+
+            task::sleep(Duration::from_millis(300)).await;
+            assert!(watchdog.is_tripped());
+            assert_eq!(hovers(), 1);
+
+            watchdog.heartbeat().feed();
+            assert!(!watchdog.is_tripped());
+            watchdog.stop().await;
+        });
+    }
+}
@@ -0,0 +1,461 @@
+use msgpack_rpc::message::Response;
+use rmpv::Value;
+
+use crate::error::NetworkResult;
+use crate::NetworkError;
+
+/// Encode a type into the msgpack representation the AirSim RPC server expects
+pub trait ToMsgpack {
+    fn to_msgpack(&self) -> Value;
+}
+
+/// Decode a type from the msgpack representation the AirSim RPC server sends
+pub trait FromMsgpack: Sized {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self>;
+
+    /// Decode the result of an RPC response, mapping a server side error to `NetworkError::Rpc`
+    fn from_response(response: Response) -> NetworkResult<Self> {
+        match response.result {
+            Ok(value) => Self::from_msgpack(&value),
+            Err(e) => Err(NetworkError::Rpc { message: e.to_string() }),
+        }
+    }
+}
+
+pub(crate) fn decode_error(expected: &str, value: &Value) -> NetworkError {
+    NetworkError::Parse {
+        message: format!("expected {expected} in msgpack value, got {value}"),
+    }
+}
+
+/// Key-value pairs of a msgpack map, looked up by key rather than position
+pub(crate) struct MsgpackMap<'a> {
+    type_name: &'static str,
+    entries: &'a [(Value, Value)],
+}
+
+impl<'a> MsgpackMap<'a> {
+    pub(crate) fn new(value: &'a Value, type_name: &'static str) -> NetworkResult<Self> {
+        match value {
+            Value::Map(entries) => Ok(Self { type_name, entries }),
+            _ => Err(decode_error(&format!("map for {type_name}"), value)),
+        }
+    }
+
+    pub(crate) fn value(&self, key: &str) -> NetworkResult<&'a Value> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k.as_str() == Some(key))
+            .map(|(_, v)| v)
+            .ok_or_else(|| NetworkError::Parse {
+                message: format!("missing key {key} for {}", self.type_name),
+            })
+    }
+
+    pub(crate) fn get<T: FromMsgpack>(&self, key: &str) -> NetworkResult<T> {
+        T::from_msgpack(self.value(key)?)
+    }
+}
+
+pub(crate) fn encode_map(entries: Vec<(&str, Value)>) -> Value {
+    Value::Map(entries.into_iter().map(|(k, v)| (Value::String(k.into()), v)).collect())
+}
+
+/// Implement `ToMsgpack` and `FromMsgpack` for a struct that travels as a msgpack map,
+/// mapping each field to the key AirSim uses for it, e.g. `x => "x_val"`
+macro_rules! msgpack_map {
+    ($ty:ident { $($field:ident => $key:literal),* $(,)? }) => {
+        impl $crate::codec::ToMsgpack for $ty {
+            fn to_msgpack(&self) -> ::rmpv::Value {
+                $crate::codec::encode_map(vec![
+                    $(($key, $crate::codec::ToMsgpack::to_msgpack(&self.$field))),*
+                ])
+            }
+        }
+
+        impl $crate::codec::FromMsgpack for $ty {
+            fn from_msgpack(value: &::rmpv::Value) -> $crate::error::NetworkResult<Self> {
+                let map = $crate::codec::MsgpackMap::new(value, stringify!($ty))?;
+                Ok(Self {
+                    $($field: map.get($key)?),*
+                })
+            }
+        }
+    };
+}
+
+/// Implement `ToMsgpack` and `FromMsgpack` for a fieldless enum that travels as an integer
+macro_rules! msgpack_enum {
+    ($ty:ident { $($variant:ident = $code:literal),* $(,)? }) => {
+        impl $crate::codec::ToMsgpack for $ty {
+            fn to_msgpack(&self) -> ::rmpv::Value {
+                let code: i64 = match self {
+                    $($ty::$variant => $code),*
+                };
+                ::rmpv::Value::Integer(code.into())
+            }
+        }
+
+        impl $crate::codec::FromMsgpack for $ty {
+            fn from_msgpack(value: &::rmpv::Value) -> $crate::error::NetworkResult<Self> {
+                match value.as_i64() {
+                    $(Some($code) => Ok($ty::$variant),)*
+                    _ => Err($crate::codec::decode_error(stringify!($ty), value)),
+                }
+            }
+        }
+    };
+    // unknown codes decode to the `$unknown(u64)` variant instead of failing
+    ($ty:ident { $($variant:ident = $code:literal),* $(,)? } else $unknown:ident) => {
+        impl $crate::codec::ToMsgpack for $ty {
+            fn to_msgpack(&self) -> ::rmpv::Value {
+                let code: u64 = match self {
+                    $($ty::$variant => $code,)*
+                    $ty::$unknown(code) => *code,
+                };
+                ::rmpv::Value::Integer(code.into())
+            }
+        }
+
+        impl $crate::codec::FromMsgpack for $ty {
+            fn from_msgpack(value: &::rmpv::Value) -> $crate::error::NetworkResult<Self> {
+                match value.as_u64() {
+                    $(Some($code) => Ok($ty::$variant),)*
+                    Some(code) => Ok($ty::$unknown(code)),
+                    None => Err($crate::codec::decode_error(stringify!($ty), value)),
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use msgpack_enum;
+pub(crate) use msgpack_map;
+
+/// Encode a serde type with rmp-serde, keeping field names so structs travel as maps like AirSim's own types
+#[cfg(feature = "serde")]
+pub(crate) fn to_value<T: serde::Serialize>(value: &T) -> NetworkResult<Value> {
+    let bytes = rmp_serde::to_vec_named(value).map_err(|e| NetworkError::Encode { message: e.to_string() })?;
+    rmpv::decode::read_value(&mut bytes.as_slice()).map_err(|e| NetworkError::Encode { message: e.to_string() })
+}
+
+/// Decode a serde type from a msgpack value, structs can be sent either as maps or as arrays
+#[cfg(feature = "serde")]
+pub(crate) fn from_value<T: serde::de::DeserializeOwned>(value: &Value) -> NetworkResult<T> {
+    let mut bytes = vec![];
+    rmpv::encode::write_value(&mut bytes, value).map_err(|e| NetworkError::Parse { message: e.to_string() })?;
+    rmp_serde::from_slice(&bytes).map_err(|e| NetworkError::Parse {
+        message: format!("{e} in msgpack value {value}"),
+    })
+}
+
+/// Positional parameters of a serde typed RPC call: a tuple, each element is one parameter
+///
+/// A single value that serializes to an array, e.g. a `Vec` of waypoints, stays one parameter
+/// when wrapped in a 1-tuple: `(waypoints,)`.
+#[cfg(feature = "serde")]
+pub trait RpcParams {
+    fn to_params(&self) -> NetworkResult<Vec<Value>>;
+}
+
+#[cfg(feature = "serde")]
+macro_rules! rpc_params_tuple {
+    ($($param:ident),*) => {
+        impl<$($param: serde::Serialize),*> RpcParams for ($($param,)*) {
+            #[allow(non_snake_case)]
+            fn to_params(&self) -> NetworkResult<Vec<Value>> {
+                let ($($param,)*) = self;
+                Ok(vec![$(to_value($param)?),*])
+            }
+        }
+    };
+}
+
+#[cfg(feature = "serde")]
+rpc_params_tuple!();
+#[cfg(feature = "serde")]
+rpc_params_tuple!(A);
+#[cfg(feature = "serde")]
+rpc_params_tuple!(A, B);
+#[cfg(feature = "serde")]
+rpc_params_tuple!(A, B, C);
+#[cfg(feature = "serde")]
+rpc_params_tuple!(A, B, C, D);
+#[cfg(feature = "serde")]
+rpc_params_tuple!(A, B, C, D, E);
+#[cfg(feature = "serde")]
+rpc_params_tuple!(A, B, C, D, E, F);
+#[cfg(feature = "serde")]
+rpc_params_tuple!(A, B, C, D, E, F, G);
+#[cfg(feature = "serde")]
+rpc_params_tuple!(A, B, C, D, E, F, G, H);
+#[cfg(feature = "serde")]
+rpc_params_tuple!(A, B, C, D, E, F, G, H, I);
+#[cfg(feature = "serde")]
+rpc_params_tuple!(A, B, C, D, E, F, G, H, I, J);
+
+impl ToMsgpack for f32 {
+    fn to_msgpack(&self) -> Value {
+        Value::F32(*self)
+    }
+}
+
+impl FromMsgpack for f32 {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        f64::from_msgpack(value).map(|v| v as f32)
+    }
+}
+
+impl ToMsgpack for f64 {
+    fn to_msgpack(&self) -> Value {
+        Value::F64(*self)
+    }
+}
+
+impl FromMsgpack for f64 {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        match value {
+            Value::F32(v) => Ok(*v as f64),
+            Value::F64(v) => Ok(*v),
+            Value::Integer(i) => i.as_f64().ok_or_else(|| decode_error("float", value)),
+            _ => Err(decode_error("float", value)),
+        }
+    }
+}
+
+impl ToMsgpack for bool {
+    fn to_msgpack(&self) -> Value {
+        Value::Boolean(*self)
+    }
+}
+
+impl FromMsgpack for bool {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        value.as_bool().ok_or_else(|| decode_error("bool", value))
+    }
+}
+
+impl ToMsgpack for u64 {
+    fn to_msgpack(&self) -> Value {
+        Value::Integer((*self).into())
+    }
+}
+
+impl FromMsgpack for u64 {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        value.as_u64().ok_or_else(|| decode_error("unsigned integer", value))
+    }
+}
+
+impl ToMsgpack for u32 {
+    fn to_msgpack(&self) -> Value {
+        Value::Integer((*self).into())
+    }
+}
+
+impl FromMsgpack for u32 {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        value
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| decode_error("u32", value))
+    }
+}
+
+impl ToMsgpack for i64 {
+    fn to_msgpack(&self) -> Value {
+        Value::Integer((*self).into())
+    }
+}
+
+impl FromMsgpack for i64 {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        value.as_i64().ok_or_else(|| decode_error("integer", value))
+    }
+}
+
+impl ToMsgpack for String {
+    fn to_msgpack(&self) -> Value {
+        Value::String(self.as_str().into())
+    }
+}
+
+impl FromMsgpack for String {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        value
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| decode_error("string", value))
+    }
+}
+
+impl<T: ToMsgpack> ToMsgpack for Vec<T> {
+    fn to_msgpack(&self) -> Value {
+        Value::Array(self.iter().map(ToMsgpack::to_msgpack).collect())
+    }
+}
+
+impl<T: FromMsgpack> FromMsgpack for Vec<T> {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        value
+            .as_array()
+            .ok_or_else(|| decode_error("array", value))?
+            .iter()
+            .map(T::from_msgpack)
+            .collect()
+    }
+}
+
+impl FromMsgpack for Value {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        Ok(value.clone())
+    }
+}
+
+impl ToMsgpack for Value {
+    fn to_msgpack(&self) -> Value {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rmpv::Value;
+
+    use super::{encode_map, FromMsgpack, ToMsgpack};
+    use crate::{
+        AngularControllerGains, CollisionInfo, GeoPoint, ImageRequest, ImageType, Orientation3, PIDGains, Pose3,
+        Position3, Quaternion, RCData, Vector3, YawMode,
+    };
+
+    fn round_trip<T: ToMsgpack + FromMsgpack>(value: &T) -> T {
+        T::from_msgpack(&value.to_msgpack()).unwrap()
+    }
+
+    #[test]
+    fn test_vector_uses_airsim_keys() {
+        let value = Vector3::new(1.0, 2.0, 3.0).to_msgpack();
+        let keys: Vec<_> = value
+            .as_map()
+            .unwrap()
+            .iter()
+            .map(|(k, _)| k.as_str().unwrap())
+            .collect();
+        assert_eq!(keys, ["x_val", "y_val", "z_val"]);
+    }
+
+    #[test]
+    fn test_decode_by_key_not_position() {
+        let value = encode_map(vec![
+            ("z_val", Value::F64(3.0)),
+            ("x_val", Value::F32(1.0)),
+            ("y_val", Value::Integer(2.into())),
+        ]);
+        assert_eq!(Vector3::from_msgpack(&value).unwrap(), Vector3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn test_missing_key_is_an_error() {
+        let value = encode_map(vec![("x_val", Value::F32(1.0)), ("y_val", Value::F32(2.0))]);
+        assert!(Vector3::from_msgpack(&value).is_err());
+        assert!(ImageType::from_msgpack(&Value::Integer(42.into())).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let pose = Pose3::new(Position3::new(1.0, -2.0, 3.5), Quaternion::new(0.5, 0.5, -0.5, 0.5));
+        assert_eq!(round_trip(&pose), pose);
+
+        let collision = CollisionInfo {
+            has_collided: true,
+            penetration_depth: 0.25,
+            timestamp: 1_661_947_200_123_000_000,
+            normal: Vector3::new(0.0, 0.0, -1.0),
+            impact_point: Vector3::new(1.0, 2.0, 0.0),
+            position: Vector3::new(1.0, 2.0, -0.5),
+            object_name: "Ground".to_string(),
+            object_id: -1,
+        };
+        let decoded = round_trip(&collision);
+        assert_eq!(decoded.timestamp, collision.timestamp);
+        assert_eq!(decoded.object_name, "Ground");
+        assert_eq!(decoded.normal, collision.normal);
+
+        let geo = round_trip(&GeoPoint::new(47.641_47, -122.140_17, 122.0));
+        assert_eq!(geo.altitude, 122.0);
+
+        let request = ImageRequest {
+            camera_name: "front_center".to_string(),
+            image_type: ImageType::DepthPerspective,
+            pixels_as_float: true,
+            compress: false,
+        };
+        let decoded = round_trip(&request);
+        assert!(matches!(decoded.image_type, ImageType::DepthPerspective));
+        assert!(decoded.pixels_as_float && !decoded.compress);
+
+        let yaw_mode = YawMode::new(true, 0.5);
+        assert_eq!(round_trip(&yaw_mode).to_msgpack(), yaw_mode.to_msgpack());
+
+        let rc_data = RCData::new(
+            42,
+            Orientation3::new(0.1, -0.2, 0.3),
+            0.6,
+            Some([1, 0, 2, 0, 0, 0, 0, 3]),
+            true,
+            true,
+        );
+        let decoded = round_trip(&rc_data);
+        assert_eq!(decoded.orientation, rc_data.orientation);
+        assert_eq!(decoded.switches, rc_data.switches);
+        assert_eq!((decoded.timestamp, decoded.throttle), (42, 0.6));
+    }
+
+    #[test]
+    fn test_angular_gains_axis_order() {
+        let gains = AngularControllerGains::new(
+            PIDGains::new(1.0, 2.0, 3.0),
+            PIDGains::new(4.0, 5.0, 6.0),
+            PIDGains::new(7.0, 8.0, 9.0),
+        );
+
+        let value = gains.to_msgpack();
+        let kis = Vec::<f32>::from_msgpack(&value.as_array().unwrap()[1]).unwrap();
+        assert_eq!(kis, [2.0, 5.0, 8.0]);
+
+        let decoded = round_trip(&gains);
+        assert_eq!(decoded.yaw_gains.ki, 8.0);
+        assert_eq!(decoded.roll_gains.kd, 3.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_params_and_result() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Vector3r {
+            x_val: f32,
+            y_val: f32,
+            z_val: f32,
+        }
+
+        use super::RpcParams;
+        assert_eq!(("Drone1", 2.5_f32, true).to_params().unwrap().len(), 3);
+        assert!(().to_params().unwrap().is_empty());
+        // an array parameter is not spread into several parameters
+        let params = (vec![1.0_f32, 2.0, 3.0],).to_params().unwrap();
+        assert_eq!(params.len(), 1);
+        assert_eq!(params[0].as_array().unwrap().len(), 3);
+
+        // structs keep their field names, so they decode with the codec types and vice versa
+        let v = Vector3r {
+            x_val: 1.0,
+            y_val: 2.0,
+            z_val: 3.0,
+        };
+        let value = super::to_value(&v).unwrap();
+        assert_eq!(Vector3::from_msgpack(&value).unwrap(), Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(super::from_value::<Vector3r>(&value).unwrap(), v);
+
+        assert!(super::from_value::<Vector3r>(&Value::Boolean(true)).is_err());
+    }
+}
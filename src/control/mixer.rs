@@ -0,0 +1,306 @@
+use std::cmp::Ordering;
+
+use crate::error::{NetworkError, NetworkResult};
+use crate::{RotorLayout, SpinDirection, UnitInterval, Vector3, PWM};
+
+/// Relation between the PWM of a motor and the thrust of its propeller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PwmCurve {
+    /// thrust proportional to the PWM, as in AirSim where the PWM is the rotor's control signal
+    Linear,
+    /// thrust proportional to the squared PWM, for motors whose PWM sets the rotor speed
+    Quadratic,
+}
+
+/// Which parts of a command the motors could not deliver
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Saturation {
+    /// the collective thrust was shifted to make room for roll and pitch
+    pub thrust: bool,
+    /// the roll and pitch torques were scaled down
+    pub roll_pitch: bool,
+    /// the yaw torque was scaled down
+    pub yaw: bool,
+}
+
+impl Saturation {
+    pub fn is_saturated(&self) -> bool {
+        self.thrust || self.roll_pitch || self.yaw
+    }
+}
+
+/// PWM of each motor for a thrust and torque command, see `Mixer::mix`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixerOutput {
+    pub pwm: PWM,
+    /// thrust of each motor in N, after the saturation handling
+    pub thrusts: Vec<f32>,
+    pub saturation: Saturation,
+}
+
+/// Converts a collective thrust and body torques into a PWM value per motor
+///
+/// The allocation follows the geometry of a `RotorLayout`: the arms set the roll and pitch torque
+/// of each motor, the spin directions its yaw torque. Torques are in the NED body frame of the
+/// simulator's physics: positive roll lowers the right side, positive pitch raises the nose and
+/// positive yaw turns clockwise seen from above.
+///
+/// When the motors cannot deliver a command, roll and pitch are kept first, then the collective
+/// thrust, then yaw; `MixerOutput::saturation` tells which parts were cut, e.g. to stop the
+/// integrators of a controller.
+///
+/// ```no_run
+/// # use airsim_client::{Mixer, MultiRotorClient, NetworkResult, Vector3};
+/// # async fn run(client: MultiRotorClient) -> NetworkResult<()> {
+/// let mixer = Mixer::simple_flight_quad();
+/// let hover_thrust = 1.0 * 9.81; // mass times gravity
+/// let output = mixer.mix(hover_thrust, Vector3::new(0.01, 0.0, 0.0));
+/// client.move_by_motor_pwms_async(output.pwm, 0.02)?.await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mixer {
+    layout: RotorLayout,
+    max_thrust: f32,
+    pwm_curve: PwmCurve,
+    /// allocation matrix, per motor the thrust for a unit of `[thrust, roll, pitch, yaw]`
+    allocation: Vec<[f32; 4]>,
+}
+
+impl Mixer {
+    /// Mixer for an airframe
+    ///
+    /// args:
+    ///     layout (RotorLayout): Position and spin direction of each motor
+    ///     arm_length (f32): Distance from the center of the frame to each motor, in m
+    ///     max_thrust (f32): Thrust of one motor at full PWM, in N
+    ///     torque_coefficient (f32): Yaw torque of one motor per N of thrust, in N·m/N
+    ///
+    /// Returns a `NetworkError::ValidationError` if the motors cannot produce all four axes
+    /// independently, e.g. with fewer than 4 motors or only one spin direction.
+    pub fn new(layout: RotorLayout, arm_length: f32, max_thrust: f32, torque_coefficient: f32) -> NetworkResult<Self> {
+        if max_thrust.is_nan() || max_thrust <= 0.0 {
+            return Err(NetworkError::ValidationError {
+                message: format!("max thrust {max_thrust} N is not positive"),
+            });
+        }
+
+        // effectiveness of each motor on [thrust, roll, pitch, yaw], with upward thrust at (x, y)
+        let effectiveness: Vec<[f32; 4]> = layout
+            .motors()
+            .iter()
+            .map(|motor| {
+                let position = motor.position(arm_length);
+                let yaw = match motor.direction {
+                    SpinDirection::CounterClockwise => torque_coefficient,
+                    SpinDirection::Clockwise => -torque_coefficient,
+                };
+                [1.0, -position.y, position.x, yaw]
+            })
+            .collect();
+
+        // pseudo-inverse Aᵀ(AAᵀ)⁻¹, the allocation with the smallest motor thrusts
+        let mut gram = [[0.0; 4]; 4];
+        for row in &effectiveness {
+            for i in 0..4 {
+                for j in 0..4 {
+                    gram[i][j] += row[i] * row[j];
+                }
+            }
+        }
+        let inverse = invert(gram).ok_or_else(|| NetworkError::ValidationError {
+            message: format!(
+                "{} motors of this layout cannot control thrust, roll, pitch and yaw independently",
+                layout.motor_count()
+            ),
+        })?;
+        let allocation = effectiveness
+            .iter()
+            .map(|row| {
+                let mut motor = [0.0; 4];
+                for (j, value) in motor.iter_mut().enumerate() {
+                    *value = (0..4).map(|k| row[k] * inverse[k][j]).sum();
+                }
+                motor
+            })
+            .collect();
+
+        Ok(Self {
+            layout,
+            max_thrust,
+            pwm_curve: PwmCurve::Linear,
+            allocation,
+        })
+    }
+
+    /// The default quadrotor of SimpleFlight, in X configuration
+    pub fn simple_flight_quad() -> Self {
+        Self::new(RotorLayout::quad_x(), 0.2275, 4.179_446, 0.055_562 / 4.179_446)
+            .expect("the quad X layout is controllable")
+    }
+
+    /// How the PWM maps to thrust, defaults to `PwmCurve::Linear`
+    pub fn with_pwm_curve(mut self, pwm_curve: PwmCurve) -> Self {
+        self.pwm_curve = pwm_curve;
+        self
+    }
+
+    pub fn layout(&self) -> &RotorLayout {
+        &self.layout
+    }
+
+    pub fn max_thrust(&self) -> f32 {
+        self.max_thrust
+    }
+
+    /// PWM per motor for a collective thrust in N and body torques in N·m
+    pub fn mix(&self, thrust: f32, torque: Vector3) -> MixerOutput {
+        let max = self.max_thrust;
+        let mut saturation = Saturation::default();
+        let part = |axes: &dyn Fn(&[f32; 4]) -> f32| self.allocation.iter().map(axes).collect::<Vec<f32>>();
+
+        let mut roll_pitch = part(&|a| a[1] * torque.x + a[2] * torque.y);
+        let (low, high) = bounds(&roll_pitch);
+        if high - low > max {
+            let scale = max / (high - low);
+            roll_pitch.iter_mut().for_each(|f| *f *= scale);
+            saturation.roll_pitch = true;
+        }
+
+        let collective = part(&|a| a[0] * thrust.max(0.0));
+        let mut thrusts: Vec<f32> = collective.iter().zip(&roll_pitch).map(|(t, rp)| t + rp).collect();
+        let (low, high) = bounds(&thrusts);
+        let shift = if low < 0.0 {
+            -low
+        } else if high > max {
+            max - high
+        } else {
+            0.0
+        };
+        if shift != 0.0 {
+            thrusts.iter_mut().for_each(|f| *f += shift);
+            saturation.thrust = true;
+        }
+
+        // largest share of the yaw torque that keeps every motor in range
+        let yaw = part(&|a| a[3] * torque.z);
+        let mut scale: f32 = 1.0;
+        for (f, y) in thrusts.iter().zip(&yaw) {
+            if *y > 0.0 {
+                scale = scale.min((max - f) / y);
+            } else if *y < 0.0 {
+                scale = scale.min(f / -y);
+            }
+        }
+        let scale = scale.max(0.0);
+        if scale < 1.0 {
+            saturation.yaw = true;
+        }
+        for (f, y) in thrusts.iter_mut().zip(&yaw) {
+            *f = (*f + scale * y).clamp(0.0, max);
+        }
+
+        let pwm = thrusts
+            .iter()
+            .map(|f| {
+                let ratio = f / max;
+                UnitInterval::saturating(match self.pwm_curve {
+                    PwmCurve::Linear => ratio,
+                    PwmCurve::Quadratic => ratio.sqrt(),
+                })
+            })
+            .collect();
+
+        MixerOutput {
+            pwm: PWM::from_motors(pwm),
+            thrusts,
+            saturation,
+        }
+    }
+}
+
+fn bounds(values: &[f32]) -> (f32, f32) {
+    values
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), v| {
+            (low.min(*v), high.max(*v))
+        })
+}
+
+/// Inverse of a 4x4 matrix by Gauss-Jordan elimination, `None` if it is singular
+fn invert(mut matrix: [[f32; 4]; 4]) -> Option<[[f32; 4]; 4]> {
+    let mut inverse = [[0.0; 4]; 4];
+    for (i, row) in inverse.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+
+    for column in 0..4 {
+        let pivot = (column..4).max_by(|a, b| {
+            matrix[*a][column]
+                .abs()
+                .partial_cmp(&matrix[*b][column].abs())
+                .unwrap_or(Ordering::Equal)
+        })?;
+        if matrix[pivot][column].abs() < 1e-9 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+
+        let scale = matrix[column][column];
+        for j in 0..4 {
+            matrix[column][j] /= scale;
+            inverse[column][j] /= scale;
+        }
+        for row in 0..4 {
+            if row != column {
+                let factor = matrix[row][column];
+                for j in 0..4 {
+                    matrix[row][j] -= factor * matrix[column][j];
+                    inverse[row][j] -= factor * inverse[column][j];
+                }
+            }
+        }
+    }
+
+    Some(inverse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mixer;
+    use crate::{MotorPosition, RotorLayout, SpinDirection, Vector3};
+
+    #[test]
+    fn test_mix_quad() {
+        let mixer = Mixer::simple_flight_quad();
+        let max = mixer.max_thrust();
+
+        let hover = mixer.mix(2.0 * max, Vector3::zeros());
+        assert!(!hover.saturation.is_saturated());
+        for pwm in hover.pwm.motors() {
+            assert!((pwm.value() - 0.5).abs() < 1e-5);
+        }
+
+        // right side down: the left motors (rear left, front left) push harder
+        let roll = mixer.mix(2.0 * max, Vector3::new(0.1, 0.0, 0.0));
+        let t = &roll.thrusts;
+        assert!(t[1] > t[0] && t[2] > t[3] && (t[1] - t[2]).abs() < 1e-5);
+        assert!((t.iter().sum::<f32>() - 2.0 * max).abs() < 1e-4);
+
+        // full thrust leaves no room to yaw, roll and pitch still fit by lowering the thrust
+        let saturated = mixer.mix(4.0 * max, Vector3::new(0.2, 0.0, 1.0));
+        assert!(saturated.saturation.thrust && saturated.saturation.yaw && !saturated.saturation.roll_pitch);
+        assert!(saturated.thrusts[1] > saturated.thrusts[0]);
+        assert!(saturated.thrusts.iter().all(|f| (0.0..=max).contains(f)));
+    }
+
+    #[test]
+    fn test_uncontrollable_layout() {
+        let coaxial = RotorLayout::new(vec![MotorPosition::new(0.0, SpinDirection::Clockwise); 4]);
+        assert!(Mixer::new(coaxial, 0.2, 4.0, 0.01).is_err());
+        assert!(Mixer::new(RotorLayout::hexa_x(), 0.25, 4.0, 0.01).is_ok());
+    }
+}
@@ -0,0 +1,3 @@
+pub(crate) mod mixer;
+pub(crate) mod pid;
+pub(crate) mod position_controller;
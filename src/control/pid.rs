@@ -0,0 +1,83 @@
+use crate::PIDGains;
+
+/// PID controller for one axis, configured with `PIDGains`
+///
+/// The output can be limited, the integral then stops growing while the output saturates so it
+/// does not wind up.
+#[derive(Debug, Clone)]
+pub struct Pid {
+    gains: PIDGains,
+    output_limit: f32,
+    integral: f32,
+    previous_error: Option<f32>,
+}
+
+impl Pid {
+    pub fn new(gains: PIDGains) -> Self {
+        Self {
+            gains,
+            output_limit: f32::INFINITY,
+            integral: 0.0,
+            previous_error: None,
+        }
+    }
+
+    /// Limit the output to `-limit..=limit`, unlimited by default
+    pub fn with_output_limit(mut self, limit: f32) -> Self {
+        self.set_output_limit(limit);
+        self
+    }
+
+    pub fn set_output_limit(&mut self, limit: f32) {
+        self.output_limit = limit.abs();
+    }
+
+    pub fn gains(&self) -> PIDGains {
+        self.gains
+    }
+
+    /// Output for `error`, `dt` seconds after the previous update
+    pub fn update(&mut self, error: f32, dt: f32) -> f32 {
+        let derivative = match self.previous_error {
+            Some(previous) if dt > 0.0 => (error - previous) / dt,
+            _ => 0.0,
+        };
+        self.previous_error = Some(error);
+
+        let integral = self.integral + error * dt.max(0.0);
+        let output = self.gains.kp * error + self.gains.ki * integral + self.gains.kd * derivative;
+        // conditional integration: keep the integral only while it does not push into saturation
+        if output.abs() <= self.output_limit || output.signum() != error.signum() {
+            self.integral = integral;
+        }
+
+        output.clamp(-self.output_limit, self.output_limit)
+    }
+
+    /// Forget the integral and the previous error, e.g. after a new target
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.previous_error = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pid;
+    use crate::PIDGains;
+
+    #[test]
+    fn test_pid_terms_and_anti_windup() {
+        let mut pid = Pid::new(PIDGains::new(2.0, 1.0, 0.5));
+        assert_eq!(pid.update(1.0, 0.5), 2.0 + 0.5);
+        // error drops by 0.5 in 0.5 s: derivative -1.0, integral 0.75
+        assert_eq!(pid.update(0.5, 0.5), 1.0 + 0.75 - 0.5);
+
+        let mut limited = Pid::new(PIDGains::new(1.0, 1.0, 0.0)).with_output_limit(1.0);
+        for _ in 0..100 {
+            assert_eq!(limited.update(5.0, 0.1), 1.0);
+        }
+        // the integral did not wind up, so the output follows the error at once
+        assert!(limited.update(-0.5, 0.1) < 0.0);
+    }
+}
@@ -0,0 +1,302 @@
+use std::f32::consts::PI;
+
+use crate::error::NetworkResult;
+use crate::{
+    AngularControllerGains, KinematicsState, LinearControllerGains, MotionTask, MultiRotorClient, MultiRotorState,
+    Orientation3, Position3, UnitInterval, Vector3,
+};
+
+use super::pid::Pid;
+
+const GRAVITY: f32 = 9.81;
+
+/// Each command lasts this many steps, so a late step does not leave the vehicle without one
+const COMMAND_STEPS: f32 = 3.0;
+
+/// Low level command computed by a `PositionController`, angles follow the convention of the
+/// `move_by_*` methods
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlCommand {
+    /// Sent with `move_by_roll_pitch_yaw_throttle_async`
+    Attitude {
+        rotation: Orientation3,
+        throttle: UnitInterval,
+    },
+    /// Sent with `move_by_angle_rates_throttle_async`
+    AngleRates {
+        rates: Orientation3,
+        throttle: UnitInterval,
+    },
+}
+
+impl ControlCommand {
+    pub fn throttle(&self) -> UnitInterval {
+        match *self {
+            ControlCommand::Attitude { throttle, .. } | ControlCommand::AngleRates { throttle, .. } => throttle,
+        }
+    }
+
+    /// Send the command to the vehicle for `duration` seconds
+    pub fn start(&self, client: &MultiRotorClient, duration: f32) -> MotionTask {
+        match *self {
+            ControlCommand::Attitude { rotation, throttle } => {
+                client.move_by_roll_pitch_yaw_throttle_async(rotation, throttle, duration)
+            }
+            ControlCommand::AngleRates { rates, throttle } => {
+                client.move_by_angle_rates_throttle_async(rates, throttle, duration)
+            }
+        }
+    }
+}
+
+/// Cascaded position and velocity controller for multirotors, running on the client
+///
+/// The outer loop turns the position error into a velocity setpoint, the inner loop turns the
+/// velocity error into an acceleration, which is flown as a tilt and a throttle. With
+/// `with_angle_rates` a third loop turns the attitude error into angle rates. Positions,
+/// velocities and the target yaw are in the NED frame of the state, like `Position3` targets of
+/// `move_to_position_async`.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use airsim_client::{LinearControllerGains, MultiRotorClient, NetworkResult, PIDGains, Position3, PositionController};
+/// # use async_std::task;
+/// # async fn run(client: MultiRotorClient) -> NetworkResult<()> {
+/// let position_gains = LinearControllerGains::new(PIDGains::new(1.0, 0.0, 0.0), PIDGains::new(1.0, 0.0, 0.0), PIDGains::new(1.5, 0.0, 0.0));
+/// let velocity_gains = LinearControllerGains::new(PIDGains::new(2.0, 0.5, 0.0), PIDGains::new(2.0, 0.5, 0.0), PIDGains::new(4.0, 1.0, 0.0));
+/// let mut controller = PositionController::new(position_gains, velocity_gains).with_max_velocity(3.0);
+///
+/// controller.set_target(Position3::new(10.0, 0.0, -10.0), 0.0);
+/// for _ in 0..500 {
+///     controller.step(&client, 0.02).await?;
+///     task::sleep(Duration::from_millis(20)).await;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PositionController {
+    position: [Pid; 3],
+    velocity: [Pid; 3],
+    attitude: Option<[Pid; 3]>,
+    target: Position3,
+    target_yaw: f32,
+    max_velocity: f32,
+    max_vertical_velocity: f32,
+    max_tilt: f32,
+    hover_throttle: UnitInterval,
+}
+
+impl PositionController {
+    /// Controller with the default limits, flying attitude commands
+    ///
+    /// args:
+    ///     position_gains (LinearControllerGains): Position error in m to velocity in m/s, per NED axis
+    ///     velocity_gains (LinearControllerGains): Velocity error in m/s to acceleration in m/s², per NED axis
+    pub fn new(position_gains: LinearControllerGains, velocity_gains: LinearControllerGains) -> Self {
+        let pids = |gains: LinearControllerGains| {
+            [
+                Pid::new(gains.x_gains),
+                Pid::new(gains.y_gains),
+                Pid::new(gains.z_gains),
+            ]
+        };
+
+        Self {
+            position: pids(position_gains),
+            velocity: pids(velocity_gains),
+            attitude: None,
+            target: Position3::new(0.0, 0.0, 0.0),
+            target_yaw: 0.0,
+            max_velocity: 5.0,
+            max_vertical_velocity: 2.0,
+            max_tilt: 0.35,
+            hover_throttle: UnitInterval::saturating(0.6),
+        }
+        .with_limits()
+    }
+
+    /// Horizontal speed limit of the velocity setpoint in m/s, defaults to 5.0
+    pub fn with_max_velocity(mut self, max_velocity: f32) -> Self {
+        self.max_velocity = max_velocity;
+        self.with_limits()
+    }
+
+    /// Vertical speed limit of the velocity setpoint in m/s, defaults to 2.0
+    pub fn with_max_vertical_velocity(mut self, max_vertical_velocity: f32) -> Self {
+        self.max_vertical_velocity = max_vertical_velocity;
+        self.with_limits()
+    }
+
+    /// Roll and pitch limit in radians, defaults to 0.35
+    pub fn with_max_tilt(mut self, max_tilt: f32) -> Self {
+        self.max_tilt = max_tilt;
+        self.with_limits()
+    }
+
+    /// Throttle that holds the vehicle in a hover, defaults to 0.6
+    pub fn with_hover_throttle(mut self, hover_throttle: UnitInterval) -> Self {
+        self.hover_throttle = hover_throttle;
+        self
+    }
+
+    /// Fly angle rate commands instead of attitude commands
+    ///
+    /// args:
+    ///     gains (AngularControllerGains): Attitude error in radians to angle rate in radians per second
+    pub fn with_angle_rates(mut self, gains: AngularControllerGains) -> Self {
+        self.attitude = Some([
+            Pid::new(gains.roll_gains),
+            Pid::new(gains.pitch_gains),
+            Pid::new(gains.yaw_gains),
+        ]);
+        self
+    }
+
+    fn with_limits(mut self) -> Self {
+        let max_acceleration = GRAVITY * self.max_tilt.tan();
+        for (axis, pid) in self.position.iter_mut().enumerate() {
+            pid.set_output_limit(if axis < 2 {
+                self.max_velocity
+            } else {
+                self.max_vertical_velocity
+            });
+        }
+        for (axis, pid) in self.velocity.iter_mut().enumerate() {
+            // vertically between free fall and twice the hover thrust
+            pid.set_output_limit(if axis < 2 { max_acceleration } else { GRAVITY });
+        }
+        self
+    }
+
+    /// Position to fly to and yaw to face, in radians
+    pub fn set_target(&mut self, target: Position3, target_yaw: f32) {
+        self.target = target;
+        self.target_yaw = target_yaw;
+    }
+
+    pub fn target(&self) -> (Position3, f32) {
+        (self.target, self.target_yaw)
+    }
+
+    /// Forget the integrals and previous errors of all loops
+    pub fn reset(&mut self) {
+        let attitude = self.attitude.iter_mut().flatten();
+        for pid in self.position.iter_mut().chain(self.velocity.iter_mut()).chain(attitude) {
+            pid.reset();
+        }
+    }
+
+    /// Command for the estimated state, `dt` seconds after the previous update
+    pub fn update(&mut self, state: &MultiRotorState, dt: f32) -> ControlCommand {
+        self.update_kinematics(&state.kinematics_estimated, dt)
+    }
+
+    /// Command for a kinematics state, e.g. a ground truth or a filtered estimate
+    pub fn update_kinematics(&mut self, kinematics: &KinematicsState, dt: f32) -> ControlCommand {
+        let position = kinematics.position;
+        let velocity = kinematics.linear_velocity;
+        let euler = kinematics.euler();
+
+        let mut velocity_setpoint = Vector3::new(
+            self.position[0].update(self.target.x - position.x, dt),
+            self.position[1].update(self.target.y - position.y, dt),
+            self.position[2].update(self.target.z - position.z, dt),
+        );
+        let horizontal_speed = velocity_setpoint.x.hypot(velocity_setpoint.y);
+        if horizontal_speed > self.max_velocity {
+            let scale = self.max_velocity / horizontal_speed;
+            velocity_setpoint.x *= scale;
+            velocity_setpoint.y *= scale;
+        }
+
+        let acceleration = Vector3::new(
+            self.velocity[0].update(velocity_setpoint.x - velocity.x, dt),
+            self.velocity[1].update(velocity_setpoint.y - velocity.y, dt),
+            self.velocity[2].update(velocity_setpoint.z - velocity.z, dt),
+        );
+
+        // acceleration in the heading frame: forward and to the right of the vehicle
+        let (sin_yaw, cos_yaw) = euler.yaw.sin_cos();
+        let forward = cos_yaw * acceleration.x + sin_yaw * acceleration.y;
+        let right = -sin_yaw * acceleration.x + cos_yaw * acceleration.y;
+        // positive pitch tilts the nose down and positive roll the right side down
+        let pitch = (forward / GRAVITY).atan().clamp(-self.max_tilt, self.max_tilt);
+        let roll = (right / GRAVITY).atan().clamp(-self.max_tilt, self.max_tilt);
+
+        // NED z points down, the thrust carries gravity minus the commanded acceleration
+        let thrust = (GRAVITY - acceleration.z) / (roll.cos() * pitch.cos());
+        let throttle = UnitInterval::saturating(self.hover_throttle.value() * thrust / GRAVITY);
+
+        match &mut self.attitude {
+            // the move_by_* methods take yaw and pitch in the ENU convention of the state negated
+            None => ControlCommand::Attitude {
+                rotation: Orientation3::new(roll, pitch, -self.target_yaw),
+                throttle,
+            },
+            Some([roll_pid, pitch_pid, yaw_pid]) => ControlCommand::AngleRates {
+                rates: Orientation3::new(
+                    roll_pid.update(roll - euler.roll, dt),
+                    pitch_pid.update(pitch + euler.pitch, dt),
+                    yaw_pid.update(-wrap_angle(self.target_yaw - euler.yaw), dt),
+                ),
+                throttle,
+            },
+        }
+    }
+
+    /// Fetch the state, update and send the command, call it every `dt` seconds
+    pub async fn step(&mut self, client: &MultiRotorClient, dt: f32) -> NetworkResult<MotionTask> {
+        let state = client.get_multirotor_state().await?;
+        Ok(self.update(&state, dt).start(client, COMMAND_STEPS * dt))
+    }
+}
+
+/// Angle in `-PI..PI`
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::{ControlCommand, PositionController};
+    use crate::{KinematicsState, LinearControllerGains, Orientation3, PIDGains, Position3, Quaternion, Vector3};
+
+    fn gains(kp: f32) -> LinearControllerGains {
+        let gains = PIDGains::new(kp, 0.0, 0.0);
+        LinearControllerGains::new(gains, gains, gains)
+    }
+
+    fn hovering(yaw: f32) -> KinematicsState {
+        let orientation = Quaternion::from_euler(Orientation3::new(0.0, 0.0, yaw));
+        let zero = Vector3::zeros();
+        KinematicsState::new(Position3::new(0.0, 0.0, -10.0), orientation, zero, zero, zero, zero)
+    }
+
+    #[test]
+    fn test_tilts_towards_the_target() {
+        let mut controller = PositionController::new(gains(1.0), gains(2.0));
+        controller.set_target(Position3::new(10.0, 0.0, -10.0), 0.0);
+
+        // facing north, the target is ahead: nose down
+        match controller.update_kinematics(&hovering(0.0), 0.02) {
+            ControlCommand::Attitude { rotation, throttle } => {
+                assert!(rotation.pitch > 0.3 && rotation.roll.abs() < 1e-6);
+                assert!(throttle.value() > 0.6);
+            }
+            command => panic!("unexpected {command:?}"),
+        }
+
+        // facing east, the target is to the left: left side down
+        controller.reset();
+        match controller.update_kinematics(&hovering(FRAC_PI_2), 0.02) {
+            ControlCommand::Attitude { rotation, .. } => {
+                assert!(rotation.roll < -0.3 && rotation.pitch.abs() < 1e-3);
+                assert_eq!(rotation.yaw, 0.0);
+            }
+            command => panic!("unexpected {command:?}"),
+        }
+    }
+}
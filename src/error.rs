@@ -15,4 +15,22 @@ pub enum NetworkError {
     Send { message: String },
     #[error("Could not decode the message that was received")]
     Decode(#[from] DecodeError),
+    #[error("Could not encode: {message}")]
+    Encode { message: String },
+    #[error("Could not parse: {message}")]
+    Parse { message: String },
+    #[error("AirSim returned an error: {message}")]
+    Rpc { message: String },
+    #[error("Incompatible versions, client {client_version} and server {server_version}: {message}")]
+    IncompatibleVersion {
+        client_version: u64,
+        server_version: u64,
+        message: String,
+    },
+    #[error("Timed out: {message}")]
+    Timeout { message: String },
+    #[error("Invalid value: {message}")]
+    ValidationError { message: String },
+    #[error("Command violates the safety limits: {message}")]
+    SafetyViolation { message: String },
 }
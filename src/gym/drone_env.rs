@@ -0,0 +1,523 @@
+use std::f32::consts::PI;
+
+use async_trait::async_trait;
+
+use crate::error::NetworkResult;
+use crate::types::multi_rotor_state::{LandedState, MultiRotorState};
+use crate::{
+    CompressedImage, DrivetrainType, ImageType, MotionTask, MultiRotorClient, Orientation3, SteppedSim, UnitInterval,
+    Velocity3, YawMode,
+};
+
+use super::{Env, Space, Step};
+
+/// Computes the reward of a step from the resulting observation and the action that was applied
+pub type RewardFn = Box<dyn Fn(&DroneObservation, &DroneAction) -> f32 + Send + Sync>;
+
+/// Decides from an observation whether the episode has reached a terminal state
+pub type TerminationFn = Box<dyn Fn(&DroneObservation) -> bool + Send + Sync>;
+
+/// Terminate the episode as soon as the vehicle has collided with anything
+pub fn terminate_on_collision() -> TerminationFn {
+    Box::new(|obs| obs.state.collision.has_collided)
+}
+
+/// Terminate the episode as soon as the vehicle has landed, e.g. after falling to the ground
+pub fn terminate_on_landed() -> TerminationFn {
+    Box::new(|obs| obs.state.landed_state == LandedState::Landed)
+}
+
+/// Terminate the episode on collision or when the vehicle has landed
+pub fn terminate_on_collision_or_landed() -> TerminationFn {
+    Box::new(|obs| obs.state.collision.has_collided || obs.state.landed_state == LandedState::Landed)
+}
+
+/// How actions are applied to the vehicle, and the bounds of the action space
+#[derive(Debug, Clone, Copy)]
+pub enum ActionMode {
+    /// Velocities in the world NED frame, sent with `move_by_velocity_async`
+    Velocity {
+        /// max absolute velocity along each axis, in m/s
+        max_velocity: f32,
+    },
+    /// Attitude and throttle, sent with `move_by_roll_pitch_yaw_throttle_async`
+    Attitude {
+        /// max absolute roll and pitch angle, in radians
+        max_tilt: f32,
+    },
+}
+
+impl ActionMode {
+    /// Build an action from a flat vector, e.g. the output of a policy network
+    ///
+    /// Velocity actions are `[vx, vy, vz]` and attitude actions are `[roll, pitch, yaw, throttle]`.
+    /// Each value is clamped to the bounds of the action space, NaN to the lower bound.
+    pub fn action(&self, values: &[f32]) -> Option<DroneAction> {
+        let values: Vec<f32> = match self.space() {
+            Space::Box { low, high } if low.len() == values.len() => values
+                .iter()
+                .zip(low.iter().zip(&high))
+                .map(|(value, (low, high))| value.max(*low).min(*high))
+                .collect(),
+            _ => return None,
+        };
+
+        match (self, values.as_slice()) {
+            (ActionMode::Velocity { .. }, [vx, vy, vz]) => Some(DroneAction::Velocity(Velocity3::new(*vx, *vy, *vz))),
+            (ActionMode::Attitude { .. }, [roll, pitch, yaw, throttle]) => Some(DroneAction::Attitude {
+                rotation: Orientation3::new(*roll, *pitch, *yaw),
+                throttle: *throttle,
+            }),
+            _ => None,
+        }
+    }
+
+    fn space(&self) -> Space {
+        match *self {
+            ActionMode::Velocity { max_velocity } => Space::uniform(3, -max_velocity, max_velocity),
+            ActionMode::Attitude { max_tilt } => Space::Box {
+                low: vec![-max_tilt, -max_tilt, -PI, 0.0],
+                high: vec![max_tilt, max_tilt, PI, 1.0],
+            },
+        }
+    }
+}
+
+/// Action applied to the drone for the duration of one step
+#[derive(Debug, Clone, Copy)]
+pub enum DroneAction {
+    Velocity(Velocity3),
+    Attitude { rotation: Orientation3, throttle: f32 },
+}
+
+/// Observation of the drone after a step
+#[derive(Debug, Clone)]
+pub struct DroneObservation {
+    pub state: MultiRotorState,
+    /// one image per camera configured with `DroneEnv::with_camera`, in the same order
+    pub images: Vec<CompressedImage>,
+}
+
+impl DroneObservation {
+    /// Number of elements in `state_vector`
+    pub const STATE_SIZE: usize = 12;
+
+    /// Flattened kinematics: position, roll/pitch/yaw, linear velocity and angular velocity
+    pub fn state_vector(&self) -> Vec<f32> {
+        let k = &self.state.kinematics_estimated;
+        let orientation = k.euler();
+        vec![
+            k.position.x,
+            k.position.y,
+            k.position.z,
+            orientation.roll,
+            orientation.pitch,
+            orientation.yaw,
+            k.linear_velocity.x,
+            k.linear_velocity.y,
+            k.linear_velocity.z,
+            k.angular_velocity.x,
+            k.angular_velocity.y,
+            k.angular_velocity.z,
+        ]
+    }
+}
+
+/// Reinforcement learning environment for a single multirotor
+///
+/// By default each step applies the action for `step_duration` seconds of wall-clock time.
+/// Use `with_stepped_sim` to advance the simulation in lockstep instead, which makes episodes
+/// reproducible and independent of how long the policy takes to compute an action.
+pub struct DroneEnv<'a> {
+    client: &'a MultiRotorClient,
+    sim: Option<SteppedSim<'a>>,
+    action_mode: ActionMode,
+    cameras: Vec<(String, ImageType)>,
+    reward: RewardFn,
+    termination: TerminationFn,
+    step_duration: f32,
+    max_steps: Option<u64>,
+    take_off_timeout: Option<f32>,
+    steps: u64,
+}
+
+impl<'a> DroneEnv<'a> {
+    /// New environment with no cameras, zero reward and termination on collision
+    pub fn new(client: &'a MultiRotorClient, action_mode: ActionMode) -> Self {
+        Self {
+            client,
+            sim: None,
+            action_mode,
+            cameras: vec![],
+            reward: Box::new(|_, _| 0.0),
+            termination: terminate_on_collision(),
+            step_duration: 0.1,
+            max_steps: None,
+            take_off_timeout: Some(20.0),
+            steps: 0,
+        }
+    }
+
+    /// Advance the simulation in lockstep with the environment steps
+    pub fn with_stepped_sim(mut self, sim: SteppedSim<'a>) -> Self {
+        self.sim = Some(sim);
+        self
+    }
+
+    /// Add a camera image to the observation
+    pub fn with_camera(mut self, camera_name: &str, image_type: ImageType) -> Self {
+        self.cameras.push((camera_name.to_string(), image_type));
+        self
+    }
+
+    pub fn with_reward(mut self, reward: RewardFn) -> Self {
+        self.reward = reward;
+        self
+    }
+
+    pub fn with_termination(mut self, termination: TerminationFn) -> Self {
+        self.termination = termination;
+        self
+    }
+
+    /// Duration in seconds each action is applied for
+    pub fn with_step_duration(mut self, step_duration: f32) -> Self {
+        self.step_duration = step_duration;
+        self
+    }
+
+    /// Truncate episodes after this many steps
+    pub fn with_max_steps(mut self, max_steps: u64) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Take off on reset with the given timeout, or stay on the ground with `None`
+    pub fn with_take_off(mut self, timeout_sec: Option<f32>) -> Self {
+        self.take_off_timeout = timeout_sec;
+        self
+    }
+
+    /// Number of steps taken in the current episode
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    async fn observe(&self) -> NetworkResult<DroneObservation> {
+        let state = self.client.get_multirotor_state().await?;
+
+        let mut images = Vec::with_capacity(self.cameras.len());
+        for (camera_name, image_type) in &self.cameras {
+            images.push(self.client.sim_get_image(camera_name, *image_type, None).await?);
+        }
+
+        Ok(DroneObservation { state, images })
+    }
+}
+
+fn apply_action(client: &MultiRotorClient, action: &DroneAction, duration: f32) -> MotionTask {
+    match *action {
+        DroneAction::Velocity(velocity) => client.move_by_velocity_async(
+            velocity,
+            duration,
+            DrivetrainType::MaxDegreeOfFreedom,
+            YawMode::new(true, 0.0),
+        ),
+        DroneAction::Attitude { rotation, throttle } => {
+            client.move_by_roll_pitch_yaw_throttle_async(rotation, UnitInterval::saturating(throttle), duration)
+        }
+    }
+}
+
+#[async_trait]
+impl<'a> Env for DroneEnv<'a> {
+    type Observation = DroneObservation;
+    type Action = DroneAction;
+
+    async fn reset(&mut self) -> NetworkResult<DroneObservation> {
+        self.client.reset().await?;
+        self.client.enable_api_control(true).await?;
+        self.client.arm_disarm(true).await?;
+
+        if let Some(timeout_sec) = self.take_off_timeout {
+            // take off runs in real time, so the simulation clock is released until airborne
+            let airsim = self.client.airsim_client();
+            if self.sim.is_some() {
+                airsim.sim_pause(false).await?;
+            }
+            self.client.take_off_async(timeout_sec).await?;
+            if self.sim.is_some() {
+                airsim.sim_pause(true).await?;
+            }
+        }
+
+        self.steps = 0;
+        self.observe().await
+    }
+
+    async fn step(&mut self, action: DroneAction) -> NetworkResult<Step<DroneObservation>> {
+        let command = apply_action(self.client, &action, self.step_duration);
+        match self.sim.as_mut() {
+            Some(sim) => {
+                // the command must reach the simulator before the clock advances, its answer only
+                // comes once the step has run
+                if !command.sent().await {
+                    command.await?;
+                } else {
+                    let (command, step) = futures::join!(command, sim.step_time(self.step_duration as f64));
+                    command?;
+                    step?;
+                }
+            }
+            None => {
+                command.await?;
+            }
+        }
+        self.steps += 1;
+
+        let observation = self.observe().await?;
+        let reward = (self.reward)(&observation, &action);
+        let terminated = (self.termination)(&observation);
+        let truncated = self.max_steps.map_or(false, |max_steps| self.steps >= max_steps);
+
+        Ok(Step {
+            observation,
+            reward,
+            terminated,
+            truncated,
+        })
+    }
+
+    fn observation_space(&self) -> Space {
+        let state = Space::uniform(DroneObservation::STATE_SIZE, f32::NEG_INFINITY, f32::INFINITY);
+        if self.cameras.is_empty() {
+            return state;
+        }
+
+        let mut spaces = vec![state];
+        spaces.extend(self.cameras.iter().map(|_| Space::Image));
+        Space::Tuple(spaces)
+    }
+
+    fn action_space(&self) -> Space {
+        self.action_mode.space()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use async_std::task;
+    use rmpv::Value;
+
+    use super::{terminate_on_landed, ActionMode, DroneAction, DroneEnv};
+    use crate::clients::test_server::TestServer;
+    use crate::codec::ToMsgpack;
+    use crate::gym::{Env, Space};
+    use crate::types::multi_rotor_state::{LandedState, MultiRotorState};
+    use crate::types::rc_data::RCDataState;
+    use crate::{
+        CollisionInfo, GeoPoint, KinematicsState, MultiRotorClient, Orientation3, Position3, Quaternion, SteppedSim,
+        Vector3, Velocity3,
+    };
+
+    fn state(has_collided: bool, landed_state: LandedState) -> Value {
+        let zero = Vector3::default();
+        MultiRotorState {
+            collision: CollisionInfo {
+                has_collided,
+                penetration_depth: 0.0,
+                timestamp: 0,
+                normal: zero,
+                impact_point: zero,
+                position: zero,
+                object_name: String::new(),
+                object_id: -1,
+            },
+            kinematics_estimated: KinematicsState::new(
+                Position3::new(0.0, 0.0, -5.0),
+                Quaternion::default(),
+                zero,
+                zero,
+                zero,
+                zero,
+            ),
+            gps_location: GeoPoint::new(47.6, -122.1, 120.0),
+            timestamp: 0,
+            landed_state,
+            rc_data: RCDataState {
+                timestamp: 0,
+                orientation: Orientation3::new(0.0, 0.0, 0.0),
+                throttle: 0.0,
+                switches: 0,
+                is_initialized: false,
+                is_valid: false,
+            },
+        }
+        .to_msgpack()
+    }
+
+    /// Simulator recording the methods it receives, the vehicle collides or lands once the flags are set
+    struct Simulator {
+        server: TestServer,
+        calls: Arc<Mutex<Vec<String>>>,
+        collided: Arc<AtomicBool>,
+        landed: Arc<AtomicBool>,
+    }
+
+    fn simulator() -> Simulator {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let collided = Arc::new(AtomicBool::new(false));
+        let landed = Arc::new(AtomicBool::new(false));
+
+        let (recorded, has_collided, has_landed) = (Arc::clone(&calls), Arc::clone(&collided), Arc::clone(&landed));
+        let server = TestServer::spawn(move |request| {
+            recorded.lock().unwrap().push(request.method.clone());
+            Ok(match request.method.as_str() {
+                "getMultirotorState" => {
+                    let landed_state = match has_landed.load(Ordering::SeqCst) {
+                        true => LandedState::Landed,
+                        false => LandedState::Flying,
+                    };
+                    state(has_collided.load(Ordering::SeqCst), landed_state)
+                }
+                "simContinueForTime" => Value::Nil,
+                _ => Value::Boolean(true),
+            })
+        });
+
+        Simulator {
+            server,
+            calls,
+            collided,
+            landed,
+        }
+    }
+
+    #[test]
+    fn test_reset_and_step() {
+        let Simulator {
+            server,
+            calls,
+            collided,
+            landed,
+        } = simulator();
+        let rewards = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&rewards);
+
+        task::block_on(async {
+            let client = MultiRotorClient::connect(&server.addr, "Drone1").await.unwrap();
+            let mut env = DroneEnv::new(&client, ActionMode::Velocity { max_velocity: 5.0 }).with_reward(Box::new(
+                move |observation, action| {
+                    counted.fetch_add(1, Ordering::SeqCst);
+                    match action {
+                        DroneAction::Velocity(v) => v.vx + observation.state.kinematics_estimated.position.z,
+                        _ => 0.0,
+                    }
+                },
+            ));
+
+            let observation = env.reset().await.unwrap();
+            assert_eq!(observation.state.kinematics_estimated.position.z, -5.0);
+            let methods = calls.lock().unwrap().clone();
+            let expected = [
+                "reset",
+                "enableApiControl",
+                "armDisarm",
+                "takeoff",
+                "getMultirotorState",
+            ];
+            assert_eq!(methods[methods.len() - expected.len()..], expected);
+
+            calls.lock().unwrap().clear();
+            let step = env
+                .step(DroneAction::Velocity(Velocity3::new(2.0, 0.0, 0.0)))
+                .await
+                .unwrap();
+            assert_eq!(*calls.lock().unwrap(), ["moveByVelocity", "getMultirotorState"]);
+            assert_eq!(step.reward, 2.0 - 5.0);
+            assert!(!step.terminated && !step.truncated);
+
+            collided.store(true, Ordering::SeqCst);
+            let step = env
+                .step(DroneAction::Velocity(Velocity3::new(0.0, 0.0, 0.0)))
+                .await
+                .unwrap();
+            assert!(step.terminated);
+            assert_eq!(env.steps(), 2);
+
+            collided.store(false, Ordering::SeqCst);
+            landed.store(true, Ordering::SeqCst);
+            let mut env = env.with_termination(terminate_on_landed());
+            let step = env
+                .step(DroneAction::Velocity(Velocity3::new(0.0, 0.0, 0.0)))
+                .await
+                .unwrap();
+            assert!(step.terminated);
+        });
+
+        assert_eq!(rewards.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_stepped_sim_sends_command_before_stepping() {
+        let Simulator { server, calls, .. } = simulator();
+
+        task::block_on(async {
+            let client = MultiRotorClient::connect(&server.addr, "Drone1").await.unwrap();
+            let sim = SteppedSim::new(client.airsim_client()).await.unwrap();
+            let mut env = DroneEnv::new(&client, ActionMode::Velocity { max_velocity: 5.0 })
+                .with_stepped_sim(sim)
+                .with_take_off(None)
+                .with_max_steps(1);
+
+            env.reset().await.unwrap();
+            calls.lock().unwrap().clear();
+            let step = env
+                .step(DroneAction::Velocity(Velocity3::new(1.0, 0.0, 0.0)))
+                .await
+                .unwrap();
+            assert!(step.truncated);
+        });
+
+        let calls = calls.lock().unwrap();
+        let position = |method: &str| calls.iter().position(|m| m == method).unwrap();
+        // the command must be queued before the clock advances, or the step runs without it
+        assert!(position("moveByVelocity") < position("simContinueForTime"));
+        assert!(position("simIsPause") < position("getMultirotorState"));
+    }
+
+    #[test]
+    fn test_action_from_vector() {
+        let mode = ActionMode::Velocity { max_velocity: 5.0 };
+        assert!(matches!(mode.action(&[1.0, 2.0, 3.0]), Some(DroneAction::Velocity(v)) if v.vz == 3.0));
+        assert!(mode.action(&[1.0, 2.0, 3.0, 0.5]).is_none());
+
+        let mode = ActionMode::Attitude { max_tilt: 0.5 };
+        assert!(matches!(
+            mode.action(&[0.1, 0.2, 0.3, 0.6]),
+            Some(DroneAction::Attitude { throttle, .. }) if throttle == 0.6
+        ));
+        assert!(matches!(
+            mode.action(&[2.0, -2.0, 0.3, 1.5]),
+            Some(DroneAction::Attitude { rotation, throttle }) if rotation.roll == 0.5 && rotation.pitch == -0.5 && throttle == 1.0
+        ));
+        assert!(matches!(
+            ActionMode::Velocity { max_velocity: 5.0 }.action(&[9.0, f32::NAN, 0.0]),
+            Some(DroneAction::Velocity(v)) if v.vx == 5.0 && v.vy == -5.0
+        ));
+    }
+
+    #[test]
+    fn test_action_space_bounds() {
+        let space = ActionMode::Attitude { max_tilt: 0.5 }.space();
+        assert!(space.contains(&[0.5, -0.5, 3.0, 1.0]));
+        assert!(!space.contains(&[0.6, 0.0, 0.0, 0.5]));
+        assert_eq!(
+            ActionMode::Velocity { max_velocity: 2.0 }.space(),
+            Space::uniform(3, -2.0, 2.0)
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+
+use crate::error::NetworkResult;
+
+pub(crate) mod drone_env;
+
+/// Description of the values an observation or action can take
+#[derive(Debug, Clone, PartialEq)]
+pub enum Space {
+    /// Integer values in `0..n`
+    Discrete(usize),
+    /// Real valued vector where element `i` lies within `low[i]..=high[i]`
+    Box { low: Vec<f32>, high: Vec<f32> },
+    /// Compressed image of variable size, as returned by `sim_get_image`
+    Image,
+    /// Fixed sequence of spaces, e.g. a state vector followed by camera images
+    Tuple(Vec<Space>),
+}
+
+impl Space {
+    /// Box space with the same bounds for all `n` dimensions
+    pub fn uniform(n: usize, low: f32, high: f32) -> Self {
+        Space::Box {
+            low: vec![low; n],
+            high: vec![high; n],
+        }
+    }
+
+    /// Returns true if `values` lies within a `Box` space
+    pub fn contains(&self, values: &[f32]) -> bool {
+        match self {
+            Space::Box { low, high } => {
+                values.len() == low.len()
+                    && values
+                        .iter()
+                        .zip(low.iter().zip(high.iter()))
+                        .all(|(v, (l, h))| l <= v && v <= h)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Outcome of a single `Env::step`
+#[derive(Debug, Clone)]
+pub struct Step<O> {
+    pub observation: O,
+    pub reward: f32,
+    /// the episode reached a terminal state, e.g. the vehicle collided
+    pub terminated: bool,
+    /// the episode was cut short, e.g. by reaching the maximum number of steps
+    pub truncated: bool,
+}
+
+impl<O> Step<O> {
+    /// Returns true if the episode is over and `Env::reset` should be called
+    pub fn done(&self) -> bool {
+        self.terminated || self.truncated
+    }
+}
+
+/// Gym-style reinforcement learning environment
+#[async_trait]
+pub trait Env {
+    type Observation;
+    type Action;
+
+    /// Reset the environment to its initial state and return the first observation
+    async fn reset(&mut self) -> NetworkResult<Self::Observation>;
+
+    /// Apply an action and advance the environment by one step
+    async fn step(&mut self, action: Self::Action) -> NetworkResult<Step<Self::Observation>>;
+
+    fn observation_space(&self) -> Space;
+
+    fn action_space(&self) -> Space;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Space;
+
+    #[test]
+    fn test_box_space_contains() {
+        let space = Space::uniform(3, -1.0, 1.0);
+        assert!(space.contains(&[0.0, -1.0, 1.0]));
+        assert!(!space.contains(&[0.0, -1.5, 1.0]));
+        assert!(!space.contains(&[0.0, 0.0]));
+        assert!(!Space::Discrete(2).contains(&[0.0]));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{Pose3, Position3, Quaternion, Vector3};
+
+impl From<Vector3> for glam::Vec3 {
+    fn from(v: Vector3) -> Self {
+        glam::Vec3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<glam::Vec3> for Vector3 {
+    fn from(v: glam::Vec3) -> Self {
+        Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Position3> for glam::Vec3 {
+    fn from(p: Position3) -> Self {
+        glam::Vec3::new(p.x, p.y, p.z)
+    }
+}
+
+impl From<glam::Vec3> for Position3 {
+    fn from(v: glam::Vec3) -> Self {
+        Position3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Quaternion> for glam::Quat {
+    fn from(q: Quaternion) -> Self {
+        glam::Quat::from_xyzw(q.x, q.y, q.z, q.w)
+    }
+}
+
+impl From<glam::Quat> for Quaternion {
+    fn from(q: glam::Quat) -> Self {
+        Quaternion::new(q.w, q.x, q.y, q.z)
+    }
+}
+
+impl From<Pose3> for glam::Affine3A {
+    fn from(pose: Pose3) -> Self {
+        glam::Affine3A::from_rotation_translation(pose.orientation.normalize().into(), pose.position.into())
+    }
+}
+
+/// Any scale in the affine transform is discarded
+impl From<glam::Affine3A> for Pose3 {
+    fn from(affine: glam::Affine3A) -> Self {
+        let (_, rotation, translation) = affine.to_scale_rotation_translation();
+        Pose3::new(translation.into(), rotation.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Orientation3, Pose3, Position3, Quaternion, Vector3};
+
+    #[test]
+    fn test_glam_round_trip() {
+        let v = Vector3::new(1.0, 2.0, 3.0);
+        assert_eq!(Vector3::from(glam::Vec3::from(v)), v);
+
+        let q = Quaternion::from_euler(Orientation3::new(0.1, 0.2, 0.3));
+        assert_eq!(Quaternion::from(glam::Quat::from(q)), q);
+    }
+
+    #[test]
+    fn test_glam_affine_matches_pose() {
+        let pose = Pose3::new(
+            Position3::new(10.0, -2.0, -5.0),
+            Quaternion::from_euler(Orientation3::new(0.3, -0.2, 1.2)),
+        );
+        let affine: glam::Affine3A = pose.into();
+        let point = Position3::new(1.0, 2.0, 3.0);
+
+        let expected = pose.transform_point(point);
+        let actual: Position3 = affine.transform_point3(point.into()).into();
+        assert!(expected.distance(&actual) < 1e-5);
+        assert!(Pose3::from(affine).position.distance(&pose.position) < 1e-5);
+    }
+}
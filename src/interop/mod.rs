@@ -0,0 +1,7 @@
+//! Conversions between the crate's geometry types and external math libraries,
+//! each enabled by the cargo feature of the same name
+
+#[cfg(feature = "glam")]
+mod glam;
+#[cfg(feature = "nalgebra")]
+mod nalgebra;
@@ -0,0 +1,115 @@
+use nalgebra as na;
+
+use crate::{Pose3, Position3, Quaternion, Vector3};
+
+impl From<Vector3> for na::Vector3<f32> {
+    fn from(v: Vector3) -> Self {
+        na::Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<na::Vector3<f32>> for Vector3 {
+    fn from(v: na::Vector3<f32>) -> Self {
+        Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Position3> for na::Point3<f32> {
+    fn from(p: Position3) -> Self {
+        na::Point3::new(p.x, p.y, p.z)
+    }
+}
+
+impl From<na::Point3<f32>> for Position3 {
+    fn from(p: na::Point3<f32>) -> Self {
+        Position3::new(p.x, p.y, p.z)
+    }
+}
+
+impl From<Position3> for na::Vector3<f32> {
+    fn from(p: Position3) -> Self {
+        na::Vector3::new(p.x, p.y, p.z)
+    }
+}
+
+impl From<na::Vector3<f32>> for Position3 {
+    fn from(v: na::Vector3<f32>) -> Self {
+        Position3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Quaternion> for na::Quaternion<f32> {
+    fn from(q: Quaternion) -> Self {
+        na::Quaternion::new(q.w, q.x, q.y, q.z)
+    }
+}
+
+impl From<na::Quaternion<f32>> for Quaternion {
+    fn from(q: na::Quaternion<f32>) -> Self {
+        Quaternion::new(q.w, q.i, q.j, q.k)
+    }
+}
+
+/// The quaternion is normalized, as AirSim does not guarantee unit quaternions
+impl From<Quaternion> for na::UnitQuaternion<f32> {
+    fn from(q: Quaternion) -> Self {
+        na::UnitQuaternion::from_quaternion(q.into())
+    }
+}
+
+impl From<na::UnitQuaternion<f32>> for Quaternion {
+    fn from(q: na::UnitQuaternion<f32>) -> Self {
+        q.into_inner().into()
+    }
+}
+
+impl From<Pose3> for na::Isometry3<f32> {
+    fn from(pose: Pose3) -> Self {
+        na::Isometry3::from_parts(
+            na::Translation3::new(pose.position.x, pose.position.y, pose.position.z),
+            pose.orientation.into(),
+        )
+    }
+}
+
+impl From<na::Isometry3<f32>> for Pose3 {
+    fn from(iso: na::Isometry3<f32>) -> Self {
+        Pose3::new(iso.translation.vector.into(), iso.rotation.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra as na;
+
+    use crate::{Orientation3, Pose3, Position3, Quaternion, Vector3};
+
+    #[test]
+    fn test_nalgebra_round_trip() {
+        let v = Vector3::new(1.0, 2.0, 3.0);
+        assert_eq!(Vector3::from(na::Vector3::from(v)), v);
+
+        let p = Position3::new(-1.0, 0.5, 2.0);
+        assert_eq!(Position3::from(na::Point3::from(p)), p);
+
+        let q = Quaternion::from_euler(Orientation3::new(0.1, 0.2, 0.3));
+        let unit: na::UnitQuaternion<f32> = q.into();
+        let (roll, pitch, yaw) = unit.euler_angles();
+        assert!((roll - 0.1).abs() < 1e-5 && (pitch - 0.2).abs() < 1e-5 && (yaw - 0.3).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_nalgebra_isometry_matches_pose() {
+        let pose = Pose3::new(
+            Position3::new(10.0, -2.0, -5.0),
+            Quaternion::from_euler(Orientation3::new(0.3, -0.2, 1.2)),
+        );
+        let iso: na::Isometry3<f32> = pose.into();
+        let point = Position3::new(1.0, 2.0, 3.0);
+
+        let expected = pose.transform_point(point);
+        let actual: Position3 = (iso * na::Point3::from(point)).into();
+        assert!(expected.distance(&actual) < 1e-5);
+        assert!(Pose3::from(iso).position.distance(&pose.position) < 1e-6);
+    }
+}
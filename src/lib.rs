@@ -1,25 +1,63 @@
 pub use clients::airsim_client::AirsimClient;
+pub use clients::batch::Batch;
+pub use clients::capabilities::{Capabilities, OPTIONAL_METHODS};
 pub use clients::car_client::CarClient;
+pub use clients::channel::Channel;
+pub use clients::commands::{
+    MotionCommand, MoveByVelocity, MoveByVelocityZ, MoveOnPath, MoveToGps, MoveToPosition, MoveToZ, DEFAULT_DURATION,
+    DEFAULT_VELOCITY,
+};
+pub use clients::connect_options::{ConnectOptions, RetryPolicy, DEFAULT_ADDRESS};
+pub use clients::motion::MotionTask;
 pub use clients::multi_rotor_client::MultiRotorClient;
+pub use clients::offboard::{OffboardStreamer, Setpoint, StreamStats};
+pub use clients::safe_multi_rotor::{Breach, BreachAction, LimitPolicy, SafeMultiRotor, SafetyMonitor};
+pub use clients::shutdown::{ReleaseAction, ShutdownGuard};
+pub use clients::stepped_sim::SteppedSim;
+pub use clients::watchdog::{Heartbeat, Watchdog};
+#[cfg(feature = "serde")]
+pub use codec::RpcParams;
+pub use codec::{FromMsgpack, ToMsgpack};
+pub use control::mixer::{Mixer, MixerOutput, PwmCurve, Saturation};
+pub use control::pid::Pid;
+pub use control::position_controller::{ControlCommand, PositionController};
 pub use error::{NetworkError, NetworkResult};
+pub use gym::drone_env::{
+    terminate_on_collision, terminate_on_collision_or_landed, terminate_on_landed, ActionMode, DroneAction, DroneEnv,
+    DroneObservation, RewardFn, TerminationFn,
+};
+pub use gym::{Env, Space, Step};
 pub use msgpack_rpc::DecodeError;
+pub use types::collision_info::CollisionInfo;
 pub use types::drive_train::DrivetrainType;
+pub use types::frame::{LocalFrame, NedEnu};
 pub use types::gains::{AngularControllerGains, LinearControllerGains, PIDGains};
+pub use types::geofence::Geofence;
 pub use types::geopoint::GeoPoint;
 pub use types::image::{CompressedImage, ImageRequest, ImageRequests, ImageType};
+pub use types::multi_rotor_state::{LandedState, MultiRotorState};
 pub use types::path::Path;
-pub use types::pose::{Orientation2, Orientation3, Pose3, Position3, Quaternion, Velocity2, Velocity3};
+pub use types::pose::{
+    KinematicsState, Orientation2, Orientation3, Pose3, Position3, Quaternion, Velocity2, Velocity3,
+};
 pub use types::pwm::PWM;
-pub use types::rc_data::RCData;
+pub use types::rc_data::{RCData, RCDataState};
+pub use types::recording::{RecordEntry, Recording};
+pub use types::rotor_layout::{MotorPosition, RotorLayout, SpinDirection};
 pub use types::rotor_states::{RotorState, RotorStates};
 pub use types::simulation::SceneObjects;
+pub use types::unit_interval::UnitInterval;
 pub use types::vector::Vector3;
 pub use types::weather::WeatherParameter;
 pub use types::yaw_mode::YawMode;
 
 pub(crate) use msgpack::MsgPackClient;
 mod clients;
+mod codec;
+mod control;
 mod error;
+mod gym;
+mod interop;
 mod msgpack;
 mod types;
 
@@ -1,4 +1,3 @@
-#![allow(dead_code)]
 use async_std::channel::{unbounded, Receiver, Sender};
 use async_std::io::prelude::*;
 use async_std::net::{TcpStream, ToSocketAddrs};
@@ -7,38 +6,125 @@ use async_std::task;
 use futures::future::FutureExt;
 use futures::select;
 use msgpack_rpc::message::{Message, Notification, Request, Response};
+use msgpack_rpc::DecodeError;
+use rmpv::Value;
 use std::collections::HashMap;
-use std::io::Cursor;
+use std::fmt;
+use std::io::{self, Cursor};
+use std::sync::{PoisonError, RwLock};
 
 use crate::error::NetworkResult;
 use crate::NetworkError;
 
+/// Handles a notification pushed by the server, called with its parameters
+pub(crate) type NotificationHandler = std::sync::Arc<dyn Fn(&[Value]) + Send + Sync>;
+
+/// Answers a request sent by the server, called with its parameters
+pub(crate) type RequestHandler = std::sync::Arc<dyn Fn(&[Value]) -> Result<Value, Value> + Send + Sync>;
+
+/// Handlers for messages initiated by the server, by method name
+///
+/// Handlers run on the connection's background task, so they should return quickly and
+/// hand longer work off to a channel or a spawned task. They are called without the locks held,
+/// and a handler that panicked cannot leave the maps half updated, so poisoned locks are reused.
+#[derive(Default)]
+pub(crate) struct Handlers {
+    notifications: RwLock<HashMap<String, NotificationHandler>>,
+    requests: RwLock<HashMap<String, RequestHandler>>,
+}
+
+impl Handlers {
+    pub(crate) fn on_notification(&self, method: &str, handler: NotificationHandler) {
+        let mut notifications = self.notifications.write().unwrap_or_else(PoisonError::into_inner);
+        notifications.insert(method.to_string(), handler);
+    }
+
+    pub(crate) fn on_request(&self, method: &str, handler: RequestHandler) {
+        let mut requests = self.requests.write().unwrap_or_else(PoisonError::into_inner);
+        requests.insert(method.to_string(), handler);
+    }
+
+    fn notify(&self, notification: &Notification) {
+        // the handler runs without the lock held, so it may register handlers itself
+        let handler = self
+            .notifications
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&notification.method)
+            .cloned();
+        match handler {
+            Some(handler) => handler(&notification.params),
+            None => log::debug!("no handler for notification {}", notification.method),
+        }
+    }
+
+    fn respond(&self, request: &Request) -> Response {
+        let handler = self
+            .requests
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&request.method)
+            .cloned();
+        let result = match handler {
+            Some(handler) => handler(&request.params),
+            None => Err(Value::from(format!("no handler for method {}", request.method))),
+        };
+
+        Response { id: request.id, result }
+    }
+}
+
+impl fmt::Debug for Handlers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handlers")
+            .field(
+                "notifications",
+                &self
+                    .notifications
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .keys()
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "requests",
+                &self
+                    .requests
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .keys()
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
 /// msgpack client used to interface with the airsim msgpack server
 #[derive(Clone, Debug)]
 pub struct MsgPackClient {
     request_sender: Sender<Request>,
     notification_sender: Sender<Notification>,
-    pub notification_receiver: Receiver<Notification>,
-    pub request_receiver: Receiver<Request>,
     response_channels: Arc<Mutex<HashMap<u32, Sender<Response>>>>,
 }
 
 enum Rpc {
     Send(Message),
     Receive(usize),
+    Close(NetworkError),
 }
 
 impl MsgPackClient {
     /// Establish a TCP socket connection to the `MessagePack-RPC` server
     /// running in a background thread
-    pub async fn connect(addrs: impl ToSocketAddrs) -> NetworkResult<Self> {
+    ///
+    /// Notifications and requests sent by the server are dispatched to `handlers`
+    pub async fn connect(addrs: impl ToSocketAddrs, handlers: Arc<Handlers>, tcp_nodelay: bool) -> NetworkResult<Self> {
         let mut stream = TcpStream::connect(addrs).await?;
-        let response_channels = Arc::new(Mutex::new(HashMap::new()));
+        stream.set_nodelay(tcp_nodelay)?;
+        let response_channels: Arc<Mutex<HashMap<u32, Sender<Response>>>> = Arc::new(Mutex::new(HashMap::new()));
 
         let (request_sender, request_receiver) = unbounded::<Request>();
-        let (inner_request_sender, inner_request_receiver) = unbounded::<Request>();
         let (notification_sender, notification_receiver) = unbounded::<Notification>();
-        let (inner_notification_sender, inner_notification_receiver) = unbounded::<Notification>();
         let res_channels = Arc::clone(&response_channels);
 
         task::spawn(async move {
@@ -52,90 +138,105 @@ impl MsgPackClient {
             // for the bytes buffer
             let mut buf = vec![0_u8; buf_size];
 
-            loop {
+            let error = loop {
                 let to_process = select! {
-                    maybe_request = request_receiver.recv().fuse() => {
-                        if let Ok(request) = maybe_request {
-                            Some(Rpc::Send(Message::Request(request)))
-                        } else {
-                            None
-                        }
+                    maybe_request = request_receiver.recv().fuse() => match maybe_request {
+                        Ok(request) => Rpc::Send(Message::Request(request)),
+                        // every handle of the client was dropped
+                        Err(e) => Rpc::Close(NetworkError::Recv(e)),
                     },
-                    maybe_notification = notification_receiver.recv().fuse() => {
-                        if let Ok(notification) = maybe_notification {
-                            Some(Rpc::Send(Message::Notification(notification)))
-                        } else {
-                            None
-                        }
+                    maybe_notification = notification_receiver.recv().fuse() => match maybe_notification {
+                        Ok(notification) => Rpc::Send(Message::Notification(notification)),
+                        Err(e) => Rpc::Close(NetworkError::Recv(e)),
                     },
-                    maybe_bytes_read = stream.read(&mut buf).fuse() => {
-                        if let Ok(bytes_read) = maybe_bytes_read {
-                            Some(Rpc::Receive(bytes_read))
-                        } else {
-                            None
-                        }
+                    maybe_bytes_read = stream.read(&mut buf).fuse() => match maybe_bytes_read {
+                        // the server closed the connection
+                        Ok(0) => Rpc::Close(NetworkError::Io(io::ErrorKind::UnexpectedEof.into())),
+                        Ok(bytes_read) => Rpc::Receive(bytes_read),
+                        Err(e) => Rpc::Close(NetworkError::Io(e)),
                     }
                 };
                 match to_process {
-                    Some(Rpc::Send(m)) => {
+                    Rpc::Send(m) => {
                         let message = m.pack().expect("Couldn't serialize message");
-                        stream.write_all(&message).await.expect("Couldn't send message");
+                        if let Err(e) = stream.write_all(&message).await {
+                            break NetworkError::Io(e);
+                        }
                     }
-                    Some(Rpc::Receive(n)) => {
+                    Rpc::Receive(n) => {
                         current_message.extend(&buf[..n]);
-                        let mut frame = Cursor::new(current_message.clone());
-
-                        let recv_res = match Message::decode(&mut frame) {
-                            Ok(Message::Notification(n)) => inner_notification_sender
-                                .send(n)
-                                .await
-                                .map_err(|e| NetworkError::Send { message: e.to_string() }),
-                            Ok(Message::Request(r)) => inner_request_sender
-                                .send(r)
-                                .await
-                                .map_err(|e| NetworkError::Send { message: e.to_string() }),
-                            Ok(Message::Response(r)) => {
-                                let mut senders = res_channels.lock().await;
-                                let sender: Sender<Response> =
-                                    senders.remove(&r.id).expect("Got response but no request awaiting it");
-
-                                // send response to the `request` function
-                                sender
-                                    .send(r)
-                                    .await
-                                    .map_err(|e| NetworkError::Send { message: e.to_string() })
-                            }
-                            Err(e) => {
-                                // DecodeError
-                                panic!("{e}");
+
+                        // with pipelined requests a single read can hold several responses,
+                        // or only the first part of a large one
+                        let dispatch_error = loop {
+                            let mut frame = Cursor::new(current_message.as_slice());
+                            let message = match Message::decode(&mut frame) {
+                                Ok(message) => message,
+                                Err(DecodeError::Truncated(_)) => break None,
+                                // the stream cannot be resynchronized after a malformed frame
+                                Err(e) => break Some(NetworkError::Decode(e)),
+                            };
+
+                            #[allow(clippy::cast_possible_truncation)]
+                            let consumed = frame.position() as usize;
+                            current_message.drain(..consumed);
+
+                            let recv_res = match message {
+                                Message::Notification(n) => {
+                                    handlers.notify(&n);
+                                    Ok(())
+                                }
+                                Message::Request(r) => {
+                                    let response = Message::Response(handlers.respond(&r));
+                                    let message = response.pack().expect("Couldn't serialize message");
+                                    stream.write_all(&message).await.map_err(NetworkError::Io)
+                                }
+                                Message::Response(r) => {
+                                    let sender = res_channels.lock().await.remove(&r.id);
+                                    match sender {
+                                        // the requester may have stopped waiting for it, e.g. after a timeout
+                                        Some(sender) => {
+                                            let _ = sender.send(r).await;
+                                        }
+                                        None => log::warn!("dropping response {} that no request awaits", r.id),
+                                    }
+                                    Ok(())
+                                }
+                            };
+
+                            // if error, return it
+                            if let Err(e) = recv_res {
+                                break Some(e);
                             }
                         };
 
-                        // if error, return it
-                        if let Err(e) = recv_res {
-                            return e;
-                        }
-
-                        #[allow(clippy::cast_possible_truncation)]
-                        {
-                            let (_, remaining) = current_message.split_at(frame.position() as usize);
-                            current_message = remaining.to_vec();
+                        if let Some(e) = dispatch_error {
+                            break e;
                         }
                     }
-                    None => {}
+                    Rpc::Close(e) => break e,
                 }
-            }
+            };
+
+            // refuse new requests first, so none registers a response channel after the clear
+            request_receiver.close();
+            notification_receiver.close();
+            // dropping the response senders wakes up the requests still waiting for an answer
+            res_channels.lock().await.clear();
+            error
         });
         Ok(Self {
             request_sender,
             notification_sender,
-            notification_receiver: inner_notification_receiver,
-            request_receiver: inner_request_receiver,
             response_channels,
         })
     }
 
-    pub async fn request(&self, request: Request) -> Result<Response, NetworkError> {
+    /// Queue a request for sending and return the channel its response arrives on, the response
+    /// is forwarded from the background thread above
+    ///
+    /// Requests are written to the connection in the order they are queued
+    pub async fn send_request(&self, request: Request) -> Result<Receiver<Response>, NetworkError> {
         let (response_sender, response_receiver) = unbounded();
 
         // add the response sender (forwards the response from the server) by request id
@@ -143,17 +244,18 @@ impl MsgPackClient {
 
         // forward request to the thread that then forwards it to the MessagePack-RPC server
         // the response is added to the response channel
+        let id = request.id;
         let send_res = self.request_sender.send(request).await;
         if send_res.is_err() {
+            self.response_channels.lock().await.remove(&id);
             let e = format!("Failed to send request: {:?}", send_res);
             return Err(NetworkError::Send { message: e });
         }
 
-        // return result from request which is forwarded from the background thread above
-        response_receiver.recv().await.map_err(NetworkError::Recv)
+        Ok(response_receiver)
     }
 
-    pub async fn _notify(&self, notification: Notification) -> Result<(), NetworkError> {
+    pub async fn notify(&self, notification: Notification) -> Result<(), NetworkError> {
         let res = self.notification_sender.send(notification.to_owned()).await;
         if res.is_err() {
             let e = format!("Failed to send notification: {:?}", notification);
@@ -1,18 +1,13 @@
-use msgpack_rpc::Value;
+use crate::codec::msgpack_enum;
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DrivetrainType {
     MaxDegreeOfFreedom,
     ForwardOnly,
 }
 
-impl DrivetrainType {
-    pub(crate) fn as_msgpack(&self) -> Value {
-        let val = match self {
-            DrivetrainType::MaxDegreeOfFreedom => 0_i64,
-            DrivetrainType::ForwardOnly => 1_i64,
-        };
-
-        Value::Integer(val.into())
-    }
-}
+msgpack_enum!(DrivetrainType {
+    MaxDegreeOfFreedom = 0,
+    ForwardOnly = 1,
+});
@@ -0,0 +1,241 @@
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use super::geopoint::GeoPoint;
+use super::pose::{Orientation3, Pose3, Position3, Quaternion, Velocity3};
+use super::vector::Vector3;
+
+/// WGS84 semi-major axis, in meters
+const WGS84_A: f64 = 6_378_137.0;
+/// WGS84 flattening
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// WGS84 first eccentricity squared
+const WGS84_E2: f64 = WGS84_F * (2.0 - WGS84_F);
+
+/// Rotation between the NED and ENU world frames (pi around the x = y axis)
+const Q_NED_ENU: Quaternion = Quaternion {
+    w: 0.0,
+    x: FRAC_1_SQRT_2,
+    y: FRAC_1_SQRT_2,
+    z: 0.0,
+};
+
+/// Rotation between the FRD and FLU body frames (pi around the x axis)
+const Q_FRD_FLU: Quaternion = Quaternion {
+    w: 0.0,
+    x: 1.0,
+    y: 0.0,
+    z: 0.0,
+};
+
+/// Conversion between AirSim's North-East-Down world frame (with Forward-Right-Down body frame)
+/// and the East-North-Up world frame (with Forward-Left-Up body frame) used by e.g. ROS
+///
+/// Both conversions are the same operation, `ned_to_enu` and `enu_to_ned` are provided for readability.
+pub trait NedEnu: Sized {
+    fn ned_to_enu(&self) -> Self;
+
+    fn enu_to_ned(&self) -> Self {
+        self.ned_to_enu()
+    }
+}
+
+impl NedEnu for Vector3 {
+    fn ned_to_enu(&self) -> Self {
+        Vector3::new(self.y, self.x, -self.z)
+    }
+}
+
+impl NedEnu for Position3 {
+    fn ned_to_enu(&self) -> Self {
+        Position3::new(self.y, self.x, -self.z)
+    }
+}
+
+impl NedEnu for Velocity3 {
+    fn ned_to_enu(&self) -> Self {
+        Velocity3::new(self.vy, self.vx, -self.vz)
+    }
+}
+
+impl NedEnu for Quaternion {
+    fn ned_to_enu(&self) -> Self {
+        let q = Q_NED_ENU * *self * Q_FRD_FLU;
+
+        // keep the scalar part positive so equal rotations compare equal
+        if q.w < 0.0 {
+            Quaternion::new(-q.w, -q.x, -q.y, -q.z)
+        } else {
+            q
+        }
+    }
+}
+
+impl NedEnu for Orientation3 {
+    fn ned_to_enu(&self) -> Self {
+        Quaternion::from_euler(*self).ned_to_enu().to_euler()
+    }
+}
+
+impl NedEnu for Pose3 {
+    fn ned_to_enu(&self) -> Self {
+        Pose3::new(self.position.ned_to_enu(), self.orientation.ned_to_enu())
+    }
+}
+
+/// Local NED frame anchored at a geodetic origin, usually the vehicle's home point
+///
+/// Converts between `GeoPoint` (WGS84 latitude/longitude in degrees, altitude in meters)
+/// and local NED positions (meters), so targets for `move_to_gps_async` and
+/// `move_to_position_async` can be mixed freely.
+#[derive(Debug, Clone, Copy)]
+pub struct LocalFrame {
+    origin: GeoPoint,
+    origin_ecef: [f64; 3],
+    /// rows are the north, east and down axes expressed in ECEF
+    rotation: [[f64; 3]; 3],
+}
+
+impl LocalFrame {
+    pub fn new(origin: GeoPoint) -> Self {
+        let lat = (origin.latitude as f64).to_radians();
+        let lon = (origin.longitude as f64).to_radians();
+        let (sin_lat, cos_lat) = lat.sin_cos();
+        let (sin_lon, cos_lon) = lon.sin_cos();
+
+        let rotation = [
+            [-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat],
+            [-sin_lon, cos_lon, 0.0],
+            [-cos_lat * cos_lon, -cos_lat * sin_lon, -sin_lat],
+        ];
+
+        Self {
+            origin,
+            origin_ecef: geodetic_to_ecef(&origin),
+            rotation,
+        }
+    }
+
+    /// Geodetic point at the origin of the local frame
+    pub fn origin(&self) -> GeoPoint {
+        self.origin
+    }
+
+    /// Local NED position of a geodetic point
+    pub fn geo_to_ned(&self, point: &GeoPoint) -> Position3 {
+        let ecef = geodetic_to_ecef(point);
+        let d = [
+            ecef[0] - self.origin_ecef[0],
+            ecef[1] - self.origin_ecef[1],
+            ecef[2] - self.origin_ecef[2],
+        ];
+
+        let [n, e, down] = self.rotation.map(|row| row[0] * d[0] + row[1] * d[1] + row[2] * d[2]);
+        Position3::new(n as f32, e as f32, down as f32)
+    }
+
+    /// Geodetic point of a local NED position
+    pub fn ned_to_geo(&self, position: &Position3) -> GeoPoint {
+        let ned = [position.x as f64, position.y as f64, position.z as f64];
+
+        // the rotation is orthonormal, so its transpose maps NED back to ECEF
+        let mut ecef = self.origin_ecef;
+        for (axis, row) in ned.iter().zip(self.rotation.iter()) {
+            for i in 0..3 {
+                ecef[i] += axis * row[i];
+            }
+        }
+
+        ecef_to_geodetic(ecef)
+    }
+}
+
+fn geodetic_to_ecef(point: &GeoPoint) -> [f64; 3] {
+    let lat = (point.latitude as f64).to_radians();
+    let lon = (point.longitude as f64).to_radians();
+    let alt = point.altitude as f64;
+    let (sin_lat, cos_lat) = lat.sin_cos();
+    let (sin_lon, cos_lon) = lon.sin_cos();
+
+    // prime vertical radius of curvature
+    let n = WGS84_A / (1.0 - WGS84_E2 * sin_lat * sin_lat).sqrt();
+
+    [
+        (n + alt) * cos_lat * cos_lon,
+        (n + alt) * cos_lat * sin_lon,
+        (n * (1.0 - WGS84_E2) + alt) * sin_lat,
+    ]
+}
+
+fn ecef_to_geodetic(ecef: [f64; 3]) -> GeoPoint {
+    let [x, y, z] = ecef;
+    let p = (x * x + y * y).sqrt();
+    let lon = y.atan2(x);
+
+    // fixed point iteration on latitude, converges to well below a millimeter in a few steps
+    let mut lat = z.atan2(p * (1.0 - WGS84_E2));
+    let mut alt = 0.0;
+    for _ in 0..5 {
+        let sin_lat = lat.sin();
+        let n = WGS84_A / (1.0 - WGS84_E2 * sin_lat * sin_lat).sqrt();
+        alt = p / lat.cos() - n;
+        lat = z.atan2(p * (1.0 - WGS84_E2 * n / (n + alt)));
+    }
+
+    GeoPoint::new(lat.to_degrees() as f32, lon.to_degrees() as f32, alt as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::{LocalFrame, NedEnu};
+    use crate::{GeoPoint, Orientation3, Position3, Quaternion};
+
+    #[test]
+    fn test_ned_enu_position() {
+        let ned = Position3::new(1.0, 2.0, -3.0);
+        assert_eq!(ned.ned_to_enu(), Position3::new(2.0, 1.0, 3.0));
+        assert_eq!(ned.ned_to_enu().enu_to_ned(), ned);
+    }
+
+    #[test]
+    fn test_ned_enu_orientation() {
+        // facing north in NED is a yaw of 90 degrees in ENU
+        let enu = Quaternion::identity().ned_to_enu().to_euler();
+        assert!((enu.yaw - FRAC_PI_2).abs() < 1e-5);
+        assert!(enu.roll.abs() < 1e-5 && enu.pitch.abs() < 1e-5);
+
+        // nose up in FRD (positive pitch) is nose up in FLU (negative pitch)
+        let enu = Orientation3::new(0.2, 0.3, 0.0).ned_to_enu();
+        assert!((enu.roll - 0.2).abs() < 1e-5);
+        assert!((enu.pitch + 0.3).abs() < 1e-5);
+
+        let q = Quaternion::from_euler(Orientation3::new(0.1, -0.2, 1.0));
+        let back = q.ned_to_enu().enu_to_ned();
+        assert!((back.dot(&q).abs() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_local_frame_round_trip() {
+        let frame = LocalFrame::new(GeoPoint::new(47.641_47, -122.140_17, 122.0));
+
+        let ned = frame.geo_to_ned(&frame.origin());
+        assert!(ned.x.abs() < 1e-3 && ned.y.abs() < 1e-3 && ned.z.abs() < 1e-3);
+
+        let target = Position3::new(100.0, -50.0, -20.0);
+        let geo = frame.ned_to_geo(&target);
+        assert!((geo.altitude - 142.0).abs() < 0.01);
+        // GeoPoint is single precision, which limits the horizontal resolution to about a meter
+        assert!(frame.geo_to_ned(&geo).distance(&target) < 1.0);
+    }
+
+    #[test]
+    fn test_local_frame_north_offset() {
+        let frame = LocalFrame::new(GeoPoint::new(0.0, 0.0, 0.0));
+
+        // one thousandth of a degree of latitude at the equator is about 110.6m
+        let ned = frame.geo_to_ned(&GeoPoint::new(0.001, 0.0, 0.0));
+        assert!((ned.x - 110.57).abs() < 0.1);
+        assert!(ned.y.abs() < 1e-3);
+    }
+}
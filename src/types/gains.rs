@@ -1,6 +1,10 @@
-use msgpack_rpc::Value;
+use rmpv::Value;
+
+use crate::codec::{decode_error, FromMsgpack, ToMsgpack};
+use crate::error::NetworkResult;
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Struct to store values of PID gains. Used to transmit controller gain values while instantiating
 pub struct PIDGains {
     /// Proportional gain
@@ -15,14 +19,25 @@ impl PIDGains {
     pub fn new(kp: f32, ki: f32, kd: f32) -> Self {
         Self { kp, ki, kd }
     }
+}
+
+impl ToMsgpack for PIDGains {
+    fn to_msgpack(&self) -> Value {
+        vec![self.kp, self.ki, self.kd].to_msgpack()
+    }
+}
 
-    pub(crate) fn _to_msgpack(&self) -> Value {
-        let gains = vec![Value::F32(self.kp), Value::F32(self.ki), Value::F32(self.kd)];
-        Value::Array(gains)
+impl FromMsgpack for PIDGains {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        match Vec::<f32>::from_msgpack(value)?[..] {
+            [kp, ki, kd] => Ok(Self::new(kp, ki, kd)),
+            _ => Err(decode_error("[kp, ki, kd]", value)),
+        }
     }
 }
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Struct to contain controller gains used by angle rate and level PID controller
 pub struct AngularControllerGains {
     /// kp, ki, kd for roll axis
@@ -42,30 +57,40 @@ impl AngularControllerGains {
         }
     }
 
-    pub(crate) fn as_msgpack(&self, vehicle_name: &'static str) -> Vec<Value> {
-        let kps = Value::Array(vec![
-            Value::F32(self.roll_gains.kp),
-            Value::F32(self.pitch_gains.kp),
-            Value::F32(self.yaw_gains.kp),
-        ]);
-
-        let kis = Value::Array(vec![
-            Value::F32(self.yaw_gains.ki),
-            Value::F32(self.roll_gains.ki),
-            Value::F32(self.pitch_gains.ki),
-        ]);
-
-        let kds = Value::Array(vec![
-            Value::F32(self.roll_gains.kd),
-            Value::F32(self.pitch_gains.kd),
-            Value::F32(self.yaw_gains.kd),
-        ]);
-
-        vec![kps, kis, kds, Value::String(vehicle_name.into())]
+    /// RPC parameters: gains as `[kps, kis, kds]` followed by the vehicle name
+    pub(crate) fn rpc_params(&self, vehicle_name: &str) -> Vec<Value> {
+        let mut params = self.to_msgpack().as_array().cloned().unwrap_or_default();
+        params.push(Value::String(vehicle_name.into()));
+        params
+    }
+}
+
+impl ToMsgpack for AngularControllerGains {
+    fn to_msgpack(&self) -> Value {
+        let axes = [self.roll_gains, self.pitch_gains, self.yaw_gains];
+        let kps = axes.iter().map(|g| g.kp).collect::<Vec<_>>();
+        let kis = axes.iter().map(|g| g.ki).collect::<Vec<_>>();
+        let kds = axes.iter().map(|g| g.kd).collect::<Vec<_>>();
+
+        Value::Array(vec![kps.to_msgpack(), kis.to_msgpack(), kds.to_msgpack()])
+    }
+}
+
+impl FromMsgpack for AngularControllerGains {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        let gains = Vec::<Vec<f32>>::from_msgpack(value)?;
+        match &gains[..] {
+            [kps, kis, kds] if kps.len() == 3 && kis.len() == 3 && kds.len() == 3 => {
+                let axis = |i: usize| PIDGains::new(kps[i], kis[i], kds[i]);
+                Ok(Self::new(axis(0), axis(1), axis(2)))
+            }
+            _ => Err(decode_error("[kps, kis, kds] for 3 axes", value)),
+        }
     }
 }
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Struct to contain controller gains used by velocity and Position PID controller
 pub struct LinearControllerGains {
     /// kp, ki, kd for X axis
@@ -85,25 +110,34 @@ impl LinearControllerGains {
         }
     }
 
-    pub(crate) fn as_msgpack(&self, vehicle_name: &'static str) -> Vec<Value> {
-        let kps = Value::Array(vec![
-            Value::F32(self.x_gains.kp),
-            Value::F32(self.y_gains.kp),
-            Value::F32(self.z_gains.kp),
-        ]);
-
-        let kis = Value::Array(vec![
-            Value::F32(self.x_gains.ki),
-            Value::F32(self.y_gains.ki),
-            Value::F32(self.z_gains.ki),
-        ]);
-
-        let kds = Value::Array(vec![
-            Value::F32(self.x_gains.kd),
-            Value::F32(self.y_gains.kd),
-            Value::F32(self.z_gains.kd),
-        ]);
-
-        vec![kps, kis, kds, Value::String(vehicle_name.into())]
+    /// RPC parameters: gains as `[kps, kis, kds]` followed by the vehicle name
+    pub(crate) fn rpc_params(&self, vehicle_name: &str) -> Vec<Value> {
+        let mut params = self.to_msgpack().as_array().cloned().unwrap_or_default();
+        params.push(Value::String(vehicle_name.into()));
+        params
+    }
+}
+
+impl ToMsgpack for LinearControllerGains {
+    fn to_msgpack(&self) -> Value {
+        let axes = [self.x_gains, self.y_gains, self.z_gains];
+        let kps = axes.iter().map(|g| g.kp).collect::<Vec<_>>();
+        let kis = axes.iter().map(|g| g.ki).collect::<Vec<_>>();
+        let kds = axes.iter().map(|g| g.kd).collect::<Vec<_>>();
+
+        Value::Array(vec![kps.to_msgpack(), kis.to_msgpack(), kds.to_msgpack()])
+    }
+}
+
+impl FromMsgpack for LinearControllerGains {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        let gains = Vec::<Vec<f32>>::from_msgpack(value)?;
+        match &gains[..] {
+            [kps, kis, kds] if kps.len() == 3 && kis.len() == 3 && kds.len() == 3 => {
+                let axis = |i: usize| PIDGains::new(kps[i], kis[i], kds[i]);
+                Ok(Self::new(axis(0), axis(1), axis(2)))
+            }
+            _ => Err(decode_error("[kps, kis, kds] for 3 axes", value)),
+        }
     }
 }
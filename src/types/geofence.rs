@@ -0,0 +1,212 @@
+use std::cmp::Ordering;
+
+use crate::error::{NetworkError, NetworkResult};
+use crate::{GeoPoint, LocalFrame, Position3};
+
+/// Allowed flight volume: a horizontal polygon between two altitudes, in the local NED frame
+///
+/// Altitudes are measured upwards from the origin of the frame (i.e. `-z`), so a fence from
+/// 2 m to 50 m allows `z` between -50.0 and -2.0.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "GeofenceSpec"))]
+pub struct Geofence {
+    /// corners of the polygon, only `x` (north) and `y` (east) are used
+    polygon: Vec<Position3>,
+    min_altitude: f32,
+    max_altitude: f32,
+}
+
+/// Fields of a `Geofence` as deserialized, checked by `Geofence::new`
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct GeofenceSpec {
+    polygon: Vec<Position3>,
+    min_altitude: f32,
+    max_altitude: f32,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<GeofenceSpec> for Geofence {
+    type Error = NetworkError;
+
+    fn try_from(spec: GeofenceSpec) -> Result<Self, Self::Error> {
+        Self::new(spec.polygon, spec.min_altitude, spec.max_altitude)
+    }
+}
+
+impl Geofence {
+    /// Fence from local NED corners
+    ///
+    /// args:
+    ///     polygon (Vec<Position3>): Corners in order, at least 3, the `z` values are ignored
+    ///     min_altitude (f32): Lowest allowed altitude above the origin, in meters
+    ///     max_altitude (f32): Highest allowed altitude above the origin, in meters
+    ///
+    /// Returns a `NetworkError::ValidationError` with fewer than 3 corners, if an altitude is NaN or
+    /// if `min_altitude` is above `max_altitude`
+    pub fn new(polygon: Vec<Position3>, min_altitude: f32, max_altitude: f32) -> NetworkResult<Self> {
+        if polygon.len() < 3 {
+            return Err(NetworkError::ValidationError {
+                message: format!("a geofence needs at least 3 corners, got {}", polygon.len()),
+            });
+        }
+        if min_altitude.is_nan() || max_altitude.is_nan() || min_altitude > max_altitude {
+            return Err(NetworkError::ValidationError {
+                message: format!("min_altitude {min_altitude} is above max_altitude {max_altitude}"),
+            });
+        }
+
+        Ok(Self {
+            polygon,
+            min_altitude,
+            max_altitude,
+        })
+    }
+
+    /// Fence from geodetic corners, converted to the local NED frame of `frame`
+    pub fn from_geo(
+        frame: &LocalFrame,
+        polygon: &[GeoPoint],
+        min_altitude: f32,
+        max_altitude: f32,
+    ) -> NetworkResult<Self> {
+        Self::new(
+            polygon.iter().map(|p| frame.geo_to_ned(p)).collect(),
+            min_altitude,
+            max_altitude,
+        )
+    }
+
+    /// Axis aligned box around the origin, `half_width` meters to each side
+    pub fn square(half_width: f32, min_altitude: f32, max_altitude: f32) -> NetworkResult<Self> {
+        let h = half_width;
+        Self::new(
+            vec![
+                Position3::new(h, h, 0.0),
+                Position3::new(h, -h, 0.0),
+                Position3::new(-h, -h, 0.0),
+                Position3::new(-h, h, 0.0),
+            ],
+            min_altitude,
+            max_altitude,
+        )
+    }
+
+    pub fn polygon(&self) -> &[Position3] {
+        &self.polygon
+    }
+
+    pub fn min_altitude(&self) -> f32 {
+        self.min_altitude
+    }
+
+    pub fn max_altitude(&self) -> f32 {
+        self.max_altitude
+    }
+
+    /// Returns true if the altitude of `position` is between the min and max altitude
+    pub fn contains_altitude(&self, position: &Position3) -> bool {
+        (self.min_altitude..=self.max_altitude).contains(&-position.z)
+    }
+
+    /// Returns true if `position` is inside the polygon, ignoring the altitude
+    pub fn contains_horizontal(&self, position: &Position3) -> bool {
+        // ray casting along +x, counting the edges crossed
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            if (a.y > position.y) != (b.y > position.y) {
+                let x = a.x + (position.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if position.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    pub fn contains(&self, position: &Position3) -> bool {
+        self.contains_altitude(position) && self.contains_horizontal(position)
+    }
+
+    /// Closest position inside the fence, `position` itself if it is inside
+    pub fn clamp(&self, position: &Position3) -> Position3 {
+        let z = position.z.clamp(-self.max_altitude, -self.min_altitude);
+        if self.contains_horizontal(position) {
+            return Position3::new(position.x, position.y, z);
+        }
+
+        self.edges()
+            .map(|(a, b)| {
+                let (dx, dy) = (b.x - a.x, b.y - a.y);
+                let length_sq = dx * dx + dy * dy;
+                let t = if length_sq > 0.0 {
+                    (((position.x - a.x) * dx + (position.y - a.y) * dy) / length_sq).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let candidate = Position3::new(a.x + t * dx, a.y + t * dy, z);
+                let distance = (candidate.x - position.x).hypot(candidate.y - position.y);
+                (candidate, distance)
+            })
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+            .map_or(Position3::new(position.x, position.y, z), |(closest, _)| closest)
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (&Position3, &Position3)> {
+        self.polygon.iter().zip(self.polygon.iter().cycle().skip(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Geofence;
+    use crate::Position3;
+
+    #[test]
+    fn test_contains_and_clamp() {
+        // L-shaped, the notch at x > 0, y > 0 is outside
+        let fence = Geofence::new(
+            vec![
+                Position3::new(-10.0, -10.0, 0.0),
+                Position3::new(10.0, -10.0, 0.0),
+                Position3::new(10.0, 0.0, 0.0),
+                Position3::new(0.0, 0.0, 0.0),
+                Position3::new(0.0, 10.0, 0.0),
+                Position3::new(-10.0, 10.0, 0.0),
+            ],
+            2.0,
+            50.0,
+        )
+        .unwrap();
+
+        assert!(fence.contains(&Position3::new(5.0, -5.0, -10.0)));
+        assert!(!fence.contains(&Position3::new(5.0, 5.0, -10.0)));
+        assert!(!fence.contains(&Position3::new(5.0, -5.0, -60.0)));
+        assert!(!fence.contains(&Position3::new(5.0, -5.0, 0.0)));
+
+        assert_eq!(
+            fence.clamp(&Position3::new(20.0, -5.0, -80.0)),
+            Position3::new(10.0, -5.0, -50.0)
+        );
+        assert_eq!(
+            fence.clamp(&Position3::new(5.0, 1.0, -10.0)),
+            Position3::new(5.0, 0.0, -10.0)
+        );
+
+        assert!(Geofence::square(10.0, 50.0, 2.0).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_validates() {
+        let fence = Geofence::square(10.0, 2.0, 50.0).unwrap();
+        let json = serde_json::to_string(&fence).unwrap();
+        assert_eq!(serde_json::from_str::<Geofence>(&json).unwrap(), fence);
+
+        let empty = r#"{"polygon":[],"min_altitude":2.0,"max_altitude":50.0}"#;
+        assert!(serde_json::from_str::<Geofence>(empty).is_err());
+        let inverted = json.replace("50.0", "1.0");
+        assert!(serde_json::from_str::<Geofence>(&inverted).is_err());
+    }
+}
@@ -1,6 +1,7 @@
-use msgpack_rpc::{message::Response, Value};
+use crate::codec::msgpack_map;
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GeoPoint {
     pub latitude: f32,
     pub longitude: f32,
@@ -17,42 +18,8 @@ impl GeoPoint {
     }
 }
 
-impl From<Response> for GeoPoint {
-    fn from(msgpack: Response) -> Self {
-        let mut points = vec![];
-
-        match msgpack.result {
-            Ok(res) => {
-                let payload: &Vec<(Value, Value)> = res.as_map().unwrap();
-                for (_, v) in payload {
-                    let p = v.as_f64().unwrap() as f32;
-                    points.push(p);
-                }
-            }
-            Err(_) => panic!("Could not decode result from GeoPoint msgpack"),
-        };
-
-        GeoPoint {
-            latitude: points[0],
-            longitude: points[1],
-            altitude: points[2],
-        }
-    }
-}
-
-impl From<Value> for GeoPoint {
-    fn from(msgpack: Value) -> Self {
-        let mut points = vec![];
-        let payload: &Vec<(Value, Value)> = msgpack.as_map().unwrap();
-        for (_, v) in payload {
-            let p = v.as_f64().unwrap() as f32;
-            points.push(p);
-        }
-
-        GeoPoint {
-            latitude: points[0],
-            longitude: points[1],
-            altitude: points[2],
-        }
-    }
-}
+msgpack_map!(GeoPoint {
+    latitude => "latitude",
+    longitude => "longitude",
+    altitude => "altitude",
+});
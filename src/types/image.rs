@@ -1,6 +1,10 @@
-use msgpack_rpc::{message::Response, Utf8String, Value};
+use rmpv::Value;
+
+use crate::codec::{decode_error, msgpack_enum, msgpack_map, FromMsgpack, ToMsgpack};
+use crate::error::NetworkResult;
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ImageType {
     Scene,
     DepthPlanar,
@@ -13,47 +17,40 @@ pub enum ImageType {
     OpticalFlowVis,
 }
 
-impl ImageType {
-    pub(crate) fn as_msgpack(&self) -> Value {
-        let val = match self {
-            ImageType::Scene => 0_i64,
-            ImageType::DepthPlanar => 1_i64,
-            ImageType::DepthPerspective => 2_i64,
-            ImageType::DepthVis => 3_i64,
-            ImageType::DisparityNormalized => 4_i64,
-            ImageType::SurfaceNormals => 5_i64,
-            ImageType::Infrared => 6_i64,
-            ImageType::OpticalFlow => 7_i64,
-            ImageType::OpticalFlowVis => 8_i64,
-        };
-
-        Value::Integer(val.into())
-    }
-}
+msgpack_enum!(ImageType {
+    Scene = 0,
+    DepthPlanar = 1,
+    DepthPerspective = 2,
+    DepthVis = 3,
+    DisparityNormalized = 4,
+    SurfaceNormals = 5,
+    Infrared = 6,
+    OpticalFlow = 7,
+    OpticalFlowVis = 8,
+});
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Binary string literal of compressed png image in presented as an vector of bytes
 pub struct CompressedImage(pub Vec<u8>);
 
-impl From<Response> for CompressedImage {
-    fn from(msgpack: Response) -> Self {
-        let mut pixels = vec![];
-
-        match msgpack.result {
-            Ok(res) => {
-                let slice: &[u8] = res.as_slice().unwrap();
-                for p in slice {
-                    pixels.push(*p);
-                }
-            }
-            Err(_) => panic!("Could not decode result from CompressedImage msgpack"),
-        };
+impl ToMsgpack for CompressedImage {
+    fn to_msgpack(&self) -> Value {
+        Value::Binary(self.0.clone())
+    }
+}
 
-        Self(pixels)
+impl FromMsgpack for CompressedImage {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        value
+            .as_slice()
+            .map(|bytes| CompressedImage(bytes.to_vec()))
+            .ok_or_else(|| decode_error("binary image", value))
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImageRequest {
     pub camera_name: String,
     pub image_type: ImageType,
@@ -61,34 +58,25 @@ pub struct ImageRequest {
     pub compress: bool,
 }
 
+msgpack_map!(ImageRequest {
+    camera_name => "camera_name",
+    image_type => "image_type",
+    pixels_as_float => "pixels_as_float",
+    compress => "compress",
+});
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImageRequests(pub Vec<ImageRequest>);
 
-impl ImageRequest {
-    pub(crate) fn as_msgpack(&self) -> Value {
-        let camera_name: Utf8String = "camera_name".into();
-        let image_type: Utf8String = "image_type".into();
-        let pixels_as_float: Utf8String = "pixels_as_float".into();
-        let compress: Utf8String = "compress".into();
-
-        let val = Value::Map(vec![
-            (
-                Value::String(camera_name),
-                Value::String(self.camera_name.to_owned().into()),
-            ),
-            (Value::String(image_type), self.image_type.as_msgpack()),
-            (Value::String(pixels_as_float), Value::Boolean(self.pixels_as_float)),
-            (Value::String(compress), Value::Boolean(self.compress)),
-        ]);
-
-        let msg: Vec<(msgpack_rpc::Value, msgpack_rpc::Value)> = val.as_map().map(|x| x.to_owned()).unwrap();
-        Value::Map(msg)
+impl ToMsgpack for ImageRequests {
+    fn to_msgpack(&self) -> Value {
+        self.0.to_msgpack()
     }
 }
 
-impl ImageRequests {
-    pub(crate) fn as_msgpack(&self) -> Value {
-        let images = self.0.iter().cloned().map(|img| img.as_msgpack()).collect();
-        Value::Array(images)
+impl FromMsgpack for ImageRequests {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        Vec::from_msgpack(value).map(ImageRequests)
     }
 }
@@ -1,6 +1,8 @@
 pub mod collision_info;
 pub mod drive_train;
+pub mod frame;
 pub mod gains;
+pub mod geofence;
 pub mod geopoint;
 pub mod image;
 pub mod multi_rotor_state;
@@ -8,8 +10,11 @@ pub mod path;
 pub mod pose;
 pub mod pwm;
 pub mod rc_data;
+pub mod recording;
+pub mod rotor_layout;
 pub mod rotor_states;
 pub mod simulation;
+pub mod unit_interval;
 pub mod vector;
 pub mod weather;
 pub mod yaw_mode;
@@ -1,29 +1,24 @@
-use msgpack_rpc::{message::Response, Value};
-
+use crate::codec::{msgpack_enum, msgpack_map};
 use crate::GeoPoint;
 
 use super::{collision_info::CollisionInfo, pose::KinematicsState, rc_data::RCDataState};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LandedState {
     Landed, // 0
     Flying, // 1
+    /// Code not known to this client, e.g. from a newer AirSim version
+    Unknown(u64),
 }
 
-impl From<Value> for LandedState {
-    fn from(msgpack: Value) -> Self {
-        let landed = msgpack.as_u64().unwrap();
-        if landed == 0 {
-            LandedState::Landed
-        } else if landed == 1 {
-            LandedState::Flying
-        } else {
-            panic!("could not convert value {landed:?}. Has to be either Landed(0) or Flying(1)");
-        }
-    }
-}
+msgpack_enum!(LandedState {
+    Landed = 0,
+    Flying = 1,
+} else Unknown);
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MultiRotorState {
     pub collision: CollisionInfo,
     pub kinematics_estimated: KinematicsState,
@@ -33,40 +28,26 @@ pub struct MultiRotorState {
     pub rc_data: RCDataState,
 }
 
-impl From<Response> for MultiRotorState {
-    fn from(msgpack: Response) -> Self {
-        match msgpack.result {
-            Ok(res) => {
-                let payload: &Vec<(Value, Value)> = res.as_map().unwrap();
-
-                // collision
-                let collision: CollisionInfo = payload[0].1.to_owned().into();
-
-                // kinematics estimated
-                let kinematics_estimated: KinematicsState = payload[1].1.to_owned().into();
-
-                // gps location
-                let gps_location: GeoPoint = payload[2].1.to_owned().into();
-
-                // timestamp
-                let timestamp = payload[3].1.as_u64().unwrap();
-
-                // landed state
-                let landed_state: LandedState = payload[4].1.to_owned().into();
-
-                // rc data
-                let rc_data: RCDataState = payload[5].1.to_owned().into();
-
-                Self {
-                    collision,
-                    kinematics_estimated,
-                    gps_location,
-                    timestamp,
-                    landed_state,
-                    rc_data,
-                }
-            }
-            Err(_) => panic!("Could not decode result from MultiRotorState msgpack"),
-        }
+msgpack_map!(MultiRotorState {
+    collision => "collision",
+    kinematics_estimated => "kinematics_estimated",
+    gps_location => "gps_location",
+    timestamp => "timestamp",
+    landed_state => "landed_state",
+    rc_data => "rc_data",
+});
+
+#[cfg(test)]
+mod tests {
+    use rmpv::Value;
+
+    use super::LandedState;
+    use crate::codec::{FromMsgpack, ToMsgpack};
+
+    #[test]
+    fn test_unknown_landed_state() {
+        let unknown = LandedState::from_msgpack(&Value::Integer(7.into())).unwrap();
+        assert_eq!(unknown, LandedState::Unknown(7));
+        assert_eq!(LandedState::from_msgpack(&unknown.to_msgpack()).unwrap(), unknown);
     }
 }
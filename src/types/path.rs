@@ -1,13 +1,21 @@
-use msgpack_rpc::Value;
+use rmpv::Value;
 
+use crate::codec::{FromMsgpack, ToMsgpack};
+use crate::error::NetworkResult;
 use crate::Vector3;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Path(pub Vec<Vector3>);
 
-impl Path {
-    pub(crate) fn as_msgpack(&self) -> Value {
-        let v3_msgpack = self.0.iter().cloned().map(|v3| v3.as_msgpack()).collect();
-        Value::Array(v3_msgpack)
+impl ToMsgpack for Path {
+    fn to_msgpack(&self) -> Value {
+        self.0.to_msgpack()
+    }
+}
+
+impl FromMsgpack for Path {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        Vec::from_msgpack(value).map(Path)
     }
 }
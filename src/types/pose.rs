@@ -1,8 +1,13 @@
-use msgpack_rpc::{message::Response, Utf8String, Value};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
 
+use rmpv::Value;
+
+use crate::codec::{msgpack_map, FromMsgpack, ToMsgpack};
+use crate::error::NetworkResult;
 use crate::Vector3;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position3 {
     pub x: f32,
     pub y: f32,
@@ -15,21 +20,14 @@ impl Position3 {
     }
 }
 
-impl From<Value> for Position3 {
-    fn from(msgpack: Value) -> Self {
-        let payload: &Vec<(Value, Value)> = msgpack.as_map().unwrap();
+msgpack_map!(Position3 {
+    x => "x_val",
+    y => "y_val",
+    z => "z_val",
+});
 
-        // position
-        let mut points = vec![];
-        for (_, v) in payload {
-            let p = v.as_f64().unwrap() as f32;
-            points.push(p);
-        }
-        Position3::new(points[0], points[1], points[2])
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Orientation3 {
     /// roll angle, in radians
     pub roll: f32,
@@ -45,21 +43,21 @@ impl Orientation3 {
     }
 }
 
-impl From<Value> for Quaternion {
-    fn from(msgpack: Value) -> Self {
-        let payload: &Vec<(Value, Value)> = msgpack.as_map().unwrap();
+/// AirSim has no Euler angle type, orientations travel as a quaternion
+impl ToMsgpack for Orientation3 {
+    fn to_msgpack(&self) -> Value {
+        Quaternion::from_euler(*self).to_msgpack()
+    }
+}
 
-        // quaternion
-        let mut quats = vec![];
-        for (_, q_i) in payload {
-            let q = q_i.as_f64().unwrap() as f32;
-            quats.push(q);
-        }
-        Quaternion::new(quats[0], quats[1], quats[2], quats[3])
+impl FromMsgpack for Orientation3 {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        Quaternion::from_msgpack(value).map(|q| q.to_euler())
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Quaternion {
     pub w: f32,
     pub x: f32,
@@ -73,7 +71,15 @@ impl Quaternion {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+msgpack_map!(Quaternion {
+    w => "w_val",
+    x => "x_val",
+    y => "y_val",
+    z => "z_val",
+});
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pose3 {
     pub position: Position3,
     pub orientation: Quaternion,
@@ -83,73 +89,15 @@ impl Pose3 {
     pub fn new(position: Position3, orientation: Quaternion) -> Self {
         Self { position, orientation }
     }
-
-    pub(crate) fn as_msgpack(&self) -> Value {
-        // position
-        let x_val: Utf8String = "x_val".into();
-        let y_val: Utf8String = "y_val".into();
-        let z_val: Utf8String = "z_val".into();
-
-        let position = Value::Map(vec![
-            (Value::String(x_val.to_owned()), Value::F32(self.position.x)),
-            (Value::String(y_val.to_owned()), Value::F32(self.position.y)),
-            (Value::String(z_val.to_owned()), Value::F32(self.position.z)),
-        ]);
-
-        let pos_msg: Vec<(msgpack_rpc::Value, msgpack_rpc::Value)> = position.as_map().map(|x| x.to_owned()).unwrap();
-        let position_msg = Value::Map(pos_msg);
-
-        // orientation
-        let w_val: Utf8String = "w_val".into();
-
-        let orientation = Value::Map(vec![
-            (Value::String(w_val), Value::F32(self.orientation.w)),
-            (Value::String(x_val), Value::F32(self.orientation.x)),
-            (Value::String(y_val), Value::F32(self.orientation.y)),
-            (Value::String(z_val), Value::F32(self.orientation.z)),
-        ]);
-
-        let orr_msg: Vec<(msgpack_rpc::Value, msgpack_rpc::Value)> =
-            orientation.as_map().map(|x| x.to_owned()).unwrap();
-        let orientation_msg = Value::Map(orr_msg);
-
-        // pose
-        let position_key: Utf8String = "position".into();
-        let orientation_key: Utf8String = "orientation".into();
-
-        let pose = Value::Map(vec![
-            (Value::String(position_key), position_msg),
-            (Value::String(orientation_key), orientation_msg),
-        ]);
-
-        let pose_msg: Vec<(msgpack_rpc::Value, msgpack_rpc::Value)> = pose.as_map().map(|x| x.to_owned()).unwrap();
-        Value::Map(pose_msg)
-    }
 }
 
-impl From<Response> for Pose3 {
-    fn from(msgpack: Response) -> Self {
-        println!("\n received pose: {msgpack:?} \n \n");
-        match msgpack.result {
-            Ok(res) => {
-                let payload: &Vec<(Value, Value)> = res.as_map().unwrap();
-
-                // position
-                let position: Position3 = payload[0].1.to_owned().into();
-                // println!("pose3 position: {position:?}");
-
-                // orientation
-                let orientation: Quaternion = payload[1].1.to_owned().into();
-                // println!("pose3 orientation: {orientation:?}");
-
-                Self { position, orientation }
-            }
-            Err(_) => panic!("Could not decode result from Pose3 msgpack"),
-        }
-    }
-}
+msgpack_map!(Pose3 {
+    position => "position",
+    orientation => "orientation",
+});
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Orientation2 {
     /// roll angle, in radians
     pub roll: f32,
@@ -163,7 +111,8 @@ impl Orientation2 {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Velocity3 {
     pub vx: f32,
     pub vy: f32,
@@ -176,7 +125,8 @@ impl Velocity3 {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Velocity2 {
     pub vx: f32,
     pub vy: f32,
@@ -190,11 +140,12 @@ impl Velocity2 {
 
 /// The kinematic state of the vehicle
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KinematicsState {
     /// position in the frame of the vehicle's starting point
     pub position: Position3,
-    /// orientation in the frame of the vehicle's starting point
-    pub orientation: Orientation3,
+    /// orientation in the frame of the vehicle's starting point, as sent by AirSim
+    pub orientation: Quaternion,
     /// linear velocity in ENU body frame
     pub linear_velocity: Vector3,
     /// angular velocity in ENU body frame
@@ -208,7 +159,7 @@ pub struct KinematicsState {
 impl KinematicsState {
     pub fn new(
         position: Position3,
-        orientation: Orientation3,
+        orientation: Quaternion,
         linear_velocity: Vector3,
         angular_velocity: Vector3,
         linear_acceleration: Vector3,
@@ -223,73 +174,457 @@ impl KinematicsState {
             angular_acceleration,
         }
     }
+
+    /// Orientation as Euler angles, see `Quaternion::to_euler`
+    pub fn euler(&self) -> Orientation3 {
+        self.orientation.to_euler()
+    }
+
+    /// Roll angle, in radians
+    pub fn roll(&self) -> f32 {
+        self.euler().roll
+    }
+
+    /// Pitch angle, in radians
+    pub fn pitch(&self) -> f32 {
+        self.euler().pitch
+    }
+
+    /// Yaw angle, in radians
+    pub fn yaw(&self) -> f32 {
+        self.euler().yaw
+    }
 }
 
-impl From<Value> for KinematicsState {
-    fn from(msgpack: Value) -> Self {
-        let payload: &Vec<(Value, Value)> = msgpack.as_map().unwrap();
+msgpack_map!(KinematicsState {
+    position => "position",
+    orientation => "orientation",
+    linear_velocity => "linear_velocity",
+    angular_velocity => "angular_velocity",
+    linear_acceleration => "linear_acceleration",
+    angular_acceleration => "angular_acceleration",
+});
 
-        // position
-        let mut points = vec![];
-        let position_msgpack: &Vec<(Value, Value)> = payload[0].1.as_map().unwrap();
-        for (_, v) in position_msgpack {
-            let p = v.as_f64().unwrap() as f32;
-            points.push(p);
-        }
-        let position = Position3::new(points[0], points[1], points[2]);
-
-        // orientation
-        let mut points = vec![];
-        let orientation_msgpack: &Vec<(Value, Value)> = payload[1].1.as_map().unwrap();
-        for (_, v) in orientation_msgpack {
-            let p = v.as_f64().unwrap() as f32;
-            points.push(p);
-        }
-        let orientation = Orientation3::new(points[0], points[1], points[2]);
-
-        // linear velocity
-        let mut points = vec![];
-        let linear_velocity_msgpack: &Vec<(Value, Value)> = payload[2].1.as_map().unwrap();
-        for (_, v) in linear_velocity_msgpack {
-            let p = v.as_f64().unwrap() as f32;
-            points.push(p);
-        }
-        let linear_velocity = Vector3::new(points[0], points[1], points[2]);
-
-        // angular velocity
-        let mut points = vec![];
-        let angular_velocity_msgpack: &Vec<(Value, Value)> = payload[3].1.as_map().unwrap();
-        for (_, v) in angular_velocity_msgpack {
-            let p = v.as_f64().unwrap() as f32;
-            points.push(p);
-        }
-        let angular_velocity = Vector3::new(points[0], points[1], points[2]);
-
-        // linear acceleration
-        let mut points = vec![];
-        let linear_acceleration_msgpack: &Vec<(Value, Value)> = payload[4].1.as_map().unwrap();
-        for (_, v) in linear_acceleration_msgpack {
-            let p = v.as_f64().unwrap() as f32;
-            points.push(p);
-        }
-        let linear_acceleration = Vector3::new(points[0], points[1], points[2]);
-
-        // linear acceleration
-        let mut points = vec![];
-        let angular_acceleration_msgpack: &Vec<(Value, Value)> = payload[5].1.as_map().unwrap();
-        for (_, v) in angular_acceleration_msgpack {
-            let p = v.as_f64().unwrap() as f32;
-            points.push(p);
+impl Position3 {
+    pub fn origin() -> Self {
+        Position3::new(0.0, 0.0, 0.0)
+    }
+
+    /// Straight line distance to another position
+    pub fn distance(&self, other: &Position3) -> f32 {
+        (*self - *other).norm()
+    }
+}
+
+impl From<Vector3> for Position3 {
+    fn from(v: Vector3) -> Self {
+        Position3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Position3> for Vector3 {
+    fn from(p: Position3) -> Self {
+        Vector3::new(p.x, p.y, p.z)
+    }
+}
+
+impl Add<Vector3> for Position3 {
+    type Output = Position3;
+
+    fn add(self, rhs: Vector3) -> Position3 {
+        Position3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Sub<Vector3> for Position3 {
+    type Output = Position3;
+
+    fn sub(self, rhs: Vector3) -> Position3 {
+        Position3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+/// The difference of two positions is the vector pointing from `rhs` to `self`
+impl Sub for Position3 {
+    type Output = Vector3;
+
+    fn sub(self, rhs: Position3) -> Vector3 {
+        Vector3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl AddAssign<Vector3> for Position3 {
+    fn add_assign(&mut self, rhs: Vector3) {
+        *self = *self + rhs;
+    }
+}
+
+impl From<Vector3> for Velocity3 {
+    fn from(v: Vector3) -> Self {
+        Velocity3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Velocity3> for Vector3 {
+    fn from(v: Velocity3) -> Self {
+        Vector3::new(v.vx, v.vy, v.vz)
+    }
+}
+
+impl Add for Velocity3 {
+    type Output = Velocity3;
+
+    fn add(self, rhs: Velocity3) -> Velocity3 {
+        Velocity3::new(self.vx + rhs.vx, self.vy + rhs.vy, self.vz + rhs.vz)
+    }
+}
+
+impl Sub for Velocity3 {
+    type Output = Velocity3;
+
+    fn sub(self, rhs: Velocity3) -> Velocity3 {
+        Velocity3::new(self.vx - rhs.vx, self.vy - rhs.vy, self.vz - rhs.vz)
+    }
+}
+
+impl Neg for Velocity3 {
+    type Output = Velocity3;
+
+    fn neg(self) -> Velocity3 {
+        Velocity3::new(-self.vx, -self.vy, -self.vz)
+    }
+}
+
+impl Mul<f32> for Velocity3 {
+    type Output = Velocity3;
+
+    fn mul(self, rhs: f32) -> Velocity3 {
+        Velocity3::new(self.vx * rhs, self.vy * rhs, self.vz * rhs)
+    }
+}
+
+impl Velocity3 {
+    /// Speed, i.e. the length of the velocity vector
+    pub fn norm(&self) -> f32 {
+        Vector3::from(*self).norm()
+    }
+}
+
+impl Orientation3 {
+    /// Quaternion equivalent of these Euler angles, see `Quaternion::from_euler`
+    pub fn to_quaternion(&self) -> Quaternion {
+        Quaternion::from_euler(*self)
+    }
+}
+
+impl From<Quaternion> for Orientation3 {
+    fn from(q: Quaternion) -> Self {
+        q.to_euler()
+    }
+}
+
+impl From<Orientation3> for Quaternion {
+    fn from(o: Orientation3) -> Self {
+        Quaternion::from_euler(o)
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion::identity()
+    }
+}
+
+impl Quaternion {
+    /// Quaternion representing no rotation
+    pub fn identity() -> Self {
+        Quaternion::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    /// Rotation of `angle` radians around `axis`
+    pub fn from_axis_angle(axis: Vector3, angle: f32) -> Self {
+        let axis = axis.normalize();
+        let (s, c) = (angle / 2.0).sin_cos();
+        Quaternion::new(c, axis.x * s, axis.y * s, axis.z * s)
+    }
+
+    /// Rotation from Euler angles applied in yaw, pitch, roll (Z-Y-X) order, as AirSim does
+    pub fn from_euler(orientation: Orientation3) -> Self {
+        let (sr, cr) = (orientation.roll / 2.0).sin_cos();
+        let (sp, cp) = (orientation.pitch / 2.0).sin_cos();
+        let (sy, cy) = (orientation.yaw / 2.0).sin_cos();
+
+        Quaternion::new(
+            cr * cp * cy + sr * sp * sy,
+            sr * cp * cy - cr * sp * sy,
+            cr * sp * cy + sr * cp * sy,
+            cr * cp * sy - sr * sp * cy,
+        )
+    }
+
+    /// Euler angles in yaw, pitch, roll (Z-Y-X) order, the inverse of `from_euler`
+    pub fn to_euler(&self) -> Orientation3 {
+        let roll = (2.0 * (self.w * self.x + self.y * self.z)).atan2(1.0 - 2.0 * (self.x * self.x + self.y * self.y));
+        let pitch = (2.0 * (self.w * self.y - self.z * self.x)).clamp(-1.0, 1.0).asin();
+        let yaw = (2.0 * (self.w * self.z + self.x * self.y)).atan2(1.0 - 2.0 * (self.y * self.y + self.z * self.z));
+
+        Orientation3::new(roll, pitch, yaw)
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f32 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn norm(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Unit quaternion representing the same rotation, or the identity if the norm is zero
+    pub fn normalize(&self) -> Quaternion {
+        let norm = self.norm();
+        if norm > 0.0 {
+            Quaternion::new(self.w / norm, self.x / norm, self.y / norm, self.z / norm)
+        } else {
+            Quaternion::identity()
         }
-        let angular_acceleration = Vector3::new(points[0], points[1], points[2]);
+    }
 
-        Self {
-            position,
-            orientation,
-            linear_velocity,
-            angular_velocity,
-            linear_acceleration,
-            angular_acceleration,
+    pub fn conjugate(&self) -> Quaternion {
+        Quaternion::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Inverse rotation, equal to the conjugate for unit quaternions
+    pub fn inverse(&self) -> Quaternion {
+        let norm_sq = self.dot(self);
+        let c = self.conjugate();
+        Quaternion::new(c.w / norm_sq, c.x / norm_sq, c.y / norm_sq, c.z / norm_sq)
+    }
+
+    /// Rotate a vector by this (unit) quaternion, e.g. from the body frame to the world frame
+    pub fn rotate(&self, v: Vector3) -> Vector3 {
+        let u = Vector3::new(self.x, self.y, self.z);
+        let t = u.cross(&v) * 2.0;
+        v + t * self.w + u.cross(&t)
+    }
+
+    /// Spherical linear interpolation between two unit quaternions along the shortest path
+    ///
+    /// args:
+    ///     other (Quaternion): rotation at `t = 1`
+    ///     t (f32): interpolation parameter between 0.0 and 1.0
+    pub fn slerp(&self, other: &Quaternion, t: f32) -> Quaternion {
+        let mut other = *other;
+        let mut cos_theta = self.dot(&other);
+
+        // q and -q represent the same rotation, take the shorter arc
+        if cos_theta < 0.0 {
+            other = Quaternion::new(-other.w, -other.x, -other.y, -other.z);
+            cos_theta = -cos_theta;
         }
+
+        // fall back to linear interpolation when the quaternions are nearly parallel
+        let (a, b) = if cos_theta > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (((1.0 - t) * theta).sin() / sin_theta, (t * theta).sin() / sin_theta)
+        };
+
+        Quaternion::new(
+            a * self.w + b * other.w,
+            a * self.x + b * other.x,
+            a * self.y + b * other.y,
+            a * self.z + b * other.z,
+        )
+        .normalize()
+    }
+}
+
+/// Hamilton product, `a * b` applies rotation `b` first and then `a`
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, rhs: Quaternion) -> Quaternion {
+        Quaternion::new(
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        )
+    }
+}
+
+impl Mul<Vector3> for Quaternion {
+    type Output = Vector3;
+
+    fn mul(self, rhs: Vector3) -> Vector3 {
+        self.rotate(rhs)
+    }
+}
+
+impl Pose3 {
+    /// Pose at the origin with no rotation
+    pub fn identity() -> Self {
+        Pose3::new(Position3::origin(), Quaternion::identity())
+    }
+
+    /// Transform a point from this pose's frame into the parent frame, e.g. body to world
+    pub fn transform_point(&self, point: Position3) -> Position3 {
+        self.position + self.orientation.rotate(point.into())
+    }
+
+    /// Rotate a direction from this pose's frame into the parent frame, e.g. a body frame velocity
+    pub fn transform_vector(&self, v: Vector3) -> Vector3 {
+        self.orientation.rotate(v)
+    }
+
+    /// Transform a point from the parent frame into this pose's frame, e.g. world to body
+    pub fn inverse_transform_point(&self, point: Position3) -> Position3 {
+        self.orientation.inverse().rotate(point - self.position).into()
+    }
+
+    /// Pose of the parent frame expressed in this pose's frame
+    pub fn inverse(&self) -> Pose3 {
+        let orientation = self.orientation.inverse();
+        let position = -orientation.rotate(self.position.into());
+        Pose3::new(position.into(), orientation)
+    }
+
+    /// Chain two poses, `a.compose(&b)` is the pose `b` (given in frame `a`) expressed in `a`'s parent frame
+    pub fn compose(&self, other: &Pose3) -> Pose3 {
+        Pose3::new(
+            self.transform_point(other.position),
+            self.orientation * other.orientation,
+        )
+    }
+}
+
+impl Default for Pose3 {
+    fn default() -> Self {
+        Pose3::identity()
+    }
+}
+
+impl Mul for Pose3 {
+    type Output = Pose3;
+
+    fn mul(self, rhs: Pose3) -> Pose3 {
+        self.compose(&rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use rmpv::Value;
+
+    use crate::codec::{encode_map, FromMsgpack, ToMsgpack};
+    use crate::{KinematicsState, Orientation3, Pose3, Position3, Quaternion, Vector3};
+
+    const EPS: f32 = 1e-5;
+
+    fn assert_vec_eq(a: Vector3, b: Vector3) {
+        assert!((a - b).norm() < EPS, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_euler_round_trip() {
+        let orientation = Orientation3::new(0.1, -0.4, 2.5);
+        let back = Quaternion::from_euler(orientation).to_euler();
+        assert!((back.roll - orientation.roll).abs() < EPS);
+        assert!((back.pitch - orientation.pitch).abs() < EPS);
+        assert!((back.yaw - orientation.yaw).abs() < EPS);
+    }
+
+    #[test]
+    fn test_rotate_vector() {
+        // 90 degrees yaw maps north (x) to east (y)
+        let q = Quaternion::from_euler(Orientation3::new(0.0, 0.0, FRAC_PI_2));
+        assert_vec_eq(q * Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+
+        let axis = Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), FRAC_PI_2);
+        assert!((axis.dot(&q) - 1.0).abs() < EPS);
+    }
+
+    #[test]
+    fn test_multiply_and_inverse() {
+        let a = Quaternion::from_euler(Orientation3::new(0.3, 0.2, -1.0));
+        let b = Quaternion::from_euler(Orientation3::new(-0.5, 0.1, 0.7));
+        let v = Vector3::new(1.0, -2.0, 0.5);
+
+        assert_vec_eq((a * b).rotate(v), a.rotate(b.rotate(v)));
+        assert_vec_eq((a * a.inverse()).rotate(v), v);
+        assert!((a.conjugate().dot(&a.inverse()) - 1.0).abs() < EPS);
+        assert!((Quaternion::new(2.0, 0.0, 0.0, 0.0).normalize().w - 1.0).abs() < EPS);
+    }
+
+    #[test]
+    fn test_slerp() {
+        let a = Quaternion::identity();
+        let b = Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), FRAC_PI_2);
+        let half = a.slerp(&b, 0.5);
+        assert!((half.to_euler().yaw - FRAC_PI_2 / 2.0).abs() < EPS);
+        assert!((a.slerp(&b, 1.0).dot(&b) - 1.0).abs() < EPS);
+    }
+
+    #[test]
+    fn test_pose_compose_and_inverse() {
+        let body = Pose3::new(
+            Position3::new(10.0, 0.0, -5.0),
+            Quaternion::from_euler(Orientation3::new(0.0, 0.0, FRAC_PI_2)),
+        );
+
+        // a point 1m in front of the body is 1m east of it in the world
+        let world = body.transform_point(Position3::new(1.0, 0.0, 0.0));
+        assert_vec_eq(world.into(), Vector3::new(10.0, 1.0, -5.0));
+        assert_vec_eq(body.inverse_transform_point(world).into(), Vector3::new(1.0, 0.0, 0.0));
+
+        let identity = body * body.inverse();
+        assert_vec_eq(identity.position.into(), Vector3::zeros());
+        assert!((identity.orientation.w.abs() - 1.0).abs() < EPS);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_round_trip() {
+        let pose = Pose3::new(Position3::new(1.0, -2.0, -3.5), Quaternion::new(0.5, 0.5, -0.5, 0.5));
+        let json = serde_json::to_string(&pose).unwrap();
+        assert_eq!(
+            json,
+            r#"{"position":{"x":1.0,"y":-2.0,"z":-3.5},"orientation":{"w":0.5,"x":0.5,"y":-0.5,"z":0.5}}"#
+        );
+        assert_eq!(serde_json::from_str::<Pose3>(&json).unwrap(), pose);
+    }
+
+    #[test]
+    fn test_kinematics_orientation_is_a_quaternion() {
+        let attitude = Orientation3::new(0.1, -0.2, 1.5);
+        let q = Quaternion::from_euler(attitude);
+        let zero = Vector3::default().to_msgpack();
+        // AirSim sends w first, so decoding by position would read w as roll
+        let value = encode_map(vec![
+            ("position", Position3::new(1.0, 2.0, -3.0).to_msgpack()),
+            (
+                "orientation",
+                encode_map(vec![
+                    ("w_val", Value::F32(q.w)),
+                    ("x_val", Value::F32(q.x)),
+                    ("y_val", Value::F32(q.y)),
+                    ("z_val", Value::F32(q.z)),
+                ]),
+            ),
+            ("linear_velocity", zero.clone()),
+            ("angular_velocity", zero.clone()),
+            ("linear_acceleration", zero.clone()),
+            ("angular_acceleration", zero),
+        ]);
+
+        let kinematics = KinematicsState::from_msgpack(&value).unwrap();
+        assert_eq!(kinematics.orientation, q);
+        assert!((kinematics.roll() - attitude.roll).abs() < 1e-5);
+        assert!((kinematics.pitch() - attitude.pitch).abs() < 1e-5);
+        assert!((kinematics.yaw() - attitude.yaw).abs() < 1e-5);
     }
 }
@@ -1,49 +1,79 @@
-#[derive(Debug, Clone, Copy)]
+use std::convert::TryFrom;
+
+use crate::error::{NetworkError, NetworkResult};
+
+use super::unit_interval::UnitInterval;
+
+/// PWM value for each motor (between 0.0 to 1.0), indexed like the motors of a `RotorLayout`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PWM {
-    /// PWM value for the front right motor (between 0.0 to 1.0)
-    pub front_right_pwm: f32,
-    /// PWM value for the rear left motor (between 0.0 to 1.0)
-    pub rear_left_pwm: f32,
-    /// PWM value for the front left motor (between 0.0 to 1.0)
-    pub front_left_pwm: f32,
-    /// PWM value for the rear right motor (between 0.0 to 1.0)
-    pub rear_right_pwm: f32,
+    motors: Vec<UnitInterval>,
 }
 
 impl PWM {
-    pub fn new(front_right_pwm: f32, rear_left_pwm: f32, front_left_pwm: f32, rear_right_pwm: f32) -> Self {
-        if front_right_pwm.is_sign_negative() || front_right_pwm > 1.0 {
-            panic!("front_right_pwm outside of valid range 0.0 to 1.0")
-        }
-
-        if rear_left_pwm.is_sign_negative() || rear_left_pwm > 1.0 {
-            panic!("rear_left_pwm outside of valid range 0.0 to 1.0")
-        }
-
-        if front_left_pwm.is_sign_negative() || front_left_pwm > 1.0 {
-            panic!("front_left_pwm outside of valid range 0.0 to 1.0")
-        }
-
-        if rear_right_pwm.is_sign_negative() || rear_right_pwm > 1.0 {
-            panic!("rear_right_pwm outside of valid range 0.0 to 1.0")
-        }
-
-        Self {
-            front_right_pwm,
-            rear_left_pwm,
-            front_left_pwm,
-            rear_right_pwm,
-        }
+    /// PWM values for a quadrotor in X configuration, see `RotorLayout::quad_x`
+    ///
+    /// Returns a `NetworkError::ValidationError` if a value is outside of 0.0 to 1.0
+    pub fn new(
+        front_right_pwm: f32,
+        rear_left_pwm: f32,
+        front_left_pwm: f32,
+        rear_right_pwm: f32,
+    ) -> NetworkResult<Self> {
+        Self::try_from(vec![front_right_pwm, rear_left_pwm, front_left_pwm, rear_right_pwm])
+    }
+
+    /// PWM values for any number of motors, indexed like the motors of a `RotorLayout`
+    pub fn from_motors(motors: Vec<UnitInterval>) -> Self {
+        Self { motors }
+    }
+
+    /// The same PWM value for each of `motor_count` motors
+    pub fn uniform(motor_count: usize, pwm: UnitInterval) -> Self {
+        Self::from_motors(vec![pwm; motor_count])
+    }
+
+    pub fn motors(&self) -> &[UnitInterval] {
+        &self.motors
+    }
+
+    pub fn motor_count(&self) -> usize {
+        self.motors.len()
+    }
+}
+
+impl TryFrom<Vec<f32>> for PWM {
+    type Error = NetworkError;
+
+    /// One value per motor index, between 0.0 to 1.0
+    fn try_from(motors: Vec<f32>) -> Result<Self, Self::Error> {
+        motors
+            .into_iter()
+            .enumerate()
+            .map(|(index, pwm)| {
+                UnitInterval::try_from(pwm).map_err(|_| NetworkError::ValidationError {
+                    message: format!("pwm {pwm} of motor {index} outside of valid range 0.0 to 1.0"),
+                })
+            })
+            .collect::<NetworkResult<_>>()
+            .map(Self::from_motors)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::PWM;
+    use std::convert::TryFrom;
+
+    use crate::{NetworkError, PWM};
 
     #[test]
-    #[should_panic]
     fn test_pwm_range() {
-        PWM::new(-1.0, 0.1, 0.1, 0.1);
+        assert!(matches!(
+            PWM::new(-1.0, 0.1, 0.1, 0.1),
+            Err(NetworkError::ValidationError { .. })
+        ));
+        assert!(PWM::try_from(vec![0.5, 0.5, 0.5, 0.5, 0.5, 1.5]).is_err());
+        assert_eq!(PWM::try_from(vec![0.5; 6]).unwrap().motor_count(), 6);
     }
 }
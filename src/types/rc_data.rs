@@ -1,8 +1,16 @@
-use msgpack_rpc::{Utf8String, Value};
+use rmpv::Value;
+
+use crate::codec::{encode_map, FromMsgpack, MsgpackMap, ToMsgpack};
+use crate::error::NetworkResult;
 
 use super::pose::Orientation3;
 
+const SWITCH_KEYS: [&str; 8] = [
+    "switch1", "switch2", "switch3", "switch4", "switch5", "switch6", "switch7", "switch8",
+];
+
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RCData {
     pub timestamp: u64,
     pub orientation: Orientation3,
@@ -32,48 +40,51 @@ impl RCData {
             is_valid,
         }
     }
+}
 
-    pub(crate) fn as_msgpack(&self) -> Value {
-        let timestamp: Utf8String = "timestamp".into();
-        let pitch: Utf8String = "pitch".into();
-        let roll: Utf8String = "roll".into();
-        let throttle: Utf8String = "throttle".into();
-        let yaw: Utf8String = "yaw".into();
-        let switch1: Utf8String = "switch1".into();
-        let switch2: Utf8String = "switch2".into();
-        let switch3: Utf8String = "switch3".into();
-        let switch4: Utf8String = "switch4".into();
-        let switch5: Utf8String = "switch5".into();
-        let switch6: Utf8String = "switch6".into();
-        let switch7: Utf8String = "switch7".into();
-        let switch8: Utf8String = "switch8".into();
-        let is_initialized: Utf8String = "is_initialized".into();
-        let is_valid: Utf8String = "is_valid".into();
+impl ToMsgpack for RCData {
+    fn to_msgpack(&self) -> Value {
+        let mut entries = vec![
+            ("timestamp", self.timestamp.to_msgpack()),
+            ("pitch", self.orientation.pitch.to_msgpack()),
+            ("roll", self.orientation.roll.to_msgpack()),
+            ("throttle", self.throttle.to_msgpack()),
+            ("yaw", self.orientation.yaw.to_msgpack()),
+        ];
+        entries.extend(
+            SWITCH_KEYS
+                .iter()
+                .zip(self.switches)
+                .map(|(key, s)| (*key, s.to_msgpack())),
+        );
+        entries.push(("is_initialized", self.is_initialized.to_msgpack()));
+        entries.push(("is_valid", self.is_valid.to_msgpack()));
+
+        encode_map(entries)
+    }
+}
 
-        let val = Value::Map(vec![
-            (Value::String(timestamp), Value::Integer(self.timestamp.into())),
-            (Value::String(pitch), Value::F32(self.orientation.pitch)),
-            (Value::String(roll), Value::F32(self.orientation.roll)),
-            (Value::String(throttle), Value::F32(self.throttle)),
-            (Value::String(yaw), Value::F32(self.orientation.yaw)),
-            (Value::String(switch1), Value::Integer(self.switches[0].into())),
-            (Value::String(switch2), Value::Integer(self.switches[1].into())),
-            (Value::String(switch3), Value::Integer(self.switches[2].into())),
-            (Value::String(switch4), Value::Integer(self.switches[3].into())),
-            (Value::String(switch5), Value::Integer(self.switches[4].into())),
-            (Value::String(switch6), Value::Integer(self.switches[5].into())),
-            (Value::String(switch7), Value::Integer(self.switches[6].into())),
-            (Value::String(switch8), Value::Integer(self.switches[7].into())),
-            (Value::String(is_initialized), Value::Boolean(self.is_initialized)),
-            (Value::String(is_valid), Value::Boolean(self.is_valid)),
-        ]);
+impl FromMsgpack for RCData {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        let map = MsgpackMap::new(value, "RCData")?;
+        let mut switches = [0; 8];
+        for (switch, key) in switches.iter_mut().zip(SWITCH_KEYS) {
+            *switch = map.get(key)?;
+        }
 
-        let msg: Vec<(msgpack_rpc::Value, msgpack_rpc::Value)> = val.as_map().map(|x| x.to_owned()).unwrap();
-        Value::Map(msg)
+        Ok(Self {
+            timestamp: map.get("timestamp")?,
+            orientation: Orientation3::new(map.get("roll")?, map.get("pitch")?, map.get("yaw")?),
+            throttle: map.get("throttle")?,
+            switches,
+            is_initialized: map.get("is_initialized")?,
+            is_valid: map.get("is_valid")?,
+        })
     }
 }
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RCDataState {
     pub timestamp: u64,
     pub orientation: Orientation3,
@@ -83,38 +94,32 @@ pub struct RCDataState {
     pub is_valid: bool,
 }
 
-impl From<Value> for RCDataState {
-    fn from(msgpack: Value) -> Self {
-        let payload: &Vec<(Value, Value)> = msgpack.as_map().unwrap();
-
-        // timestamp
-        let timestamp = payload[0].1.as_u64().unwrap();
-
-        // orientation
-        let pitch = payload[1].1.as_f64().unwrap() as f32;
-        let roll = payload[2].1.as_f64().unwrap() as f32;
-        let yaw = payload[4].1.as_f64().unwrap() as f32;
-        let orientation = Orientation3::new(roll, pitch, yaw);
-
-        // throttle
-        let throttle = payload[3].1.as_f64().unwrap() as f32;
-
-        // switches
-        let switches = payload[7].1.as_u64().unwrap();
-
-        // is initialized
-        let is_initialized = payload[9].1.as_bool().unwrap();
-
-        // is valid
-        let is_valid = payload[10].1.as_bool().unwrap();
+impl ToMsgpack for RCDataState {
+    fn to_msgpack(&self) -> Value {
+        encode_map(vec![
+            ("timestamp", self.timestamp.to_msgpack()),
+            ("pitch", self.orientation.pitch.to_msgpack()),
+            ("roll", self.orientation.roll.to_msgpack()),
+            ("throttle", self.throttle.to_msgpack()),
+            ("yaw", self.orientation.yaw.to_msgpack()),
+            ("switches", self.switches.to_msgpack()),
+            ("is_initialized", self.is_initialized.to_msgpack()),
+            ("is_valid", self.is_valid.to_msgpack()),
+        ])
+    }
+}
 
-        Self {
-            timestamp,
-            orientation,
-            throttle,
-            switches,
-            is_initialized,
-            is_valid,
-        }
+impl FromMsgpack for RCDataState {
+    fn from_msgpack(value: &Value) -> NetworkResult<Self> {
+        let map = MsgpackMap::new(value, "RCDataState")?;
+
+        Ok(Self {
+            timestamp: map.get("timestamp")?,
+            orientation: Orientation3::new(map.get("roll")?, map.get("pitch")?, map.get("yaw")?),
+            throttle: map.get("throttle")?,
+            switches: map.get("switches")?,
+            is_initialized: map.get("is_initialized")?,
+            is_valid: map.get("is_valid")?,
+        })
     }
 }
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path as FsPath, PathBuf};
+
+use crate::error::NetworkResult;
+use crate::NetworkError;
+
+use super::pose::{Pose3, Position3, Quaternion};
+
+/// Name of the log file AirSim writes inside each recording folder
+pub const RECORDING_FILE_NAME: &str = "airsim_rec.txt";
+
+/// Name of the folder AirSim writes the recorded images to
+pub const RECORDING_IMAGES_DIR: &str = "images";
+
+/// Columns every `airsim_rec.txt` contains, regardless of vehicle type
+const REQUIRED_COLUMNS: [&str; 10] = [
+    "VehicleName",
+    "TimeStamp",
+    "POS_X",
+    "POS_Y",
+    "POS_Z",
+    "Q_W",
+    "Q_X",
+    "Q_Y",
+    "Q_Z",
+    "ImageFile",
+];
+
+/// A single line of an `airsim_rec.txt` log
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordEntry {
+    pub vehicle_name: String,
+    /// timestamp in milliseconds
+    pub timestamp: u64,
+    /// pose of the vehicle in the world NED frame
+    pub pose: Pose3,
+    /// file names of the images captured at this timestamp, relative to the images folder
+    pub image_files: Vec<String>,
+    /// vehicle specific columns, e.g. `Throttle`, `Steering` or `Speed` for cars
+    pub extra: HashMap<String, String>,
+}
+
+/// A recording folder written by AirSim after `start_recording`/`stop_recording`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recording {
+    /// folder containing `airsim_rec.txt` and the `images` folder
+    pub dir: PathBuf,
+    pub entries: Vec<RecordEntry>,
+}
+
+impl Recording {
+    /// Load a recording from a folder, e.g. `~/Documents/AirSim/2022-09-01-10-00-00`
+    pub fn from_dir(dir: impl AsRef<FsPath>) -> NetworkResult<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let file = File::open(dir.join(RECORDING_FILE_NAME))?;
+        let entries = Self::parse(BufReader::new(file))?;
+
+        Ok(Self { dir, entries })
+    }
+
+    /// Parse the tab-separated contents of an `airsim_rec.txt` log
+    pub fn parse(reader: impl BufRead) -> NetworkResult<Vec<RecordEntry>> {
+        let mut lines = reader.lines();
+
+        let header = match lines.next() {
+            Some(line) => line?,
+            None => return Err(parse_error("recording is empty")),
+        };
+        let columns: Vec<&str> = header.trim_end_matches('\r').split('\t').collect();
+
+        let mut index = HashMap::new();
+        for required in REQUIRED_COLUMNS {
+            let i = columns
+                .iter()
+                .position(|c| *c == required)
+                .ok_or_else(|| parse_error(&format!("missing column {required}")))?;
+            index.insert(required, i);
+        }
+
+        let mut entries = vec![];
+        for (line_number, line) in lines.enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                continue;
+            }
+
+            // the image column is empty when no cameras are recorded
+            let mut fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() == columns.len() - 1 {
+                fields.push("");
+            }
+            if fields.len() != columns.len() {
+                return Err(parse_error(&format!(
+                    "line {} has {} fields, expected {}",
+                    line_number + 2,
+                    fields.len(),
+                    columns.len()
+                )));
+            }
+
+            let field = |name: &str| fields[index[name]];
+            let number = |name: &str| -> NetworkResult<f32> {
+                field(name)
+                    .parse()
+                    .map_err(|_| parse_error(&format!("invalid {name} on line {}", line_number + 2)))
+            };
+
+            let timestamp = field("TimeStamp")
+                .parse()
+                .map_err(|_| parse_error(&format!("invalid TimeStamp on line {}", line_number + 2)))?;
+            let position = Position3::new(number("POS_X")?, number("POS_Y")?, number("POS_Z")?);
+            let orientation = Quaternion::new(number("Q_W")?, number("Q_X")?, number("Q_Y")?, number("Q_Z")?);
+            let image_files = field("ImageFile")
+                .split(';')
+                .filter(|f| !f.is_empty())
+                .map(String::from)
+                .collect();
+
+            let extra = columns
+                .iter()
+                .zip(fields.iter())
+                .filter(|(c, _)| !REQUIRED_COLUMNS.contains(c))
+                .map(|(c, f)| (c.to_string(), f.to_string()))
+                .collect();
+
+            entries.push(RecordEntry {
+                vehicle_name: field("VehicleName").to_string(),
+                timestamp,
+                pose: Pose3::new(position, orientation),
+                image_files,
+                extra,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Full path of an image file referenced by a `RecordEntry`
+    pub fn image_path(&self, image_file: &str) -> PathBuf {
+        self.dir.join(RECORDING_IMAGES_DIR).join(image_file)
+    }
+}
+
+fn parse_error(message: &str) -> NetworkError {
+    NetworkError::Parse {
+        message: format!("airsim_rec.txt: {message}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Recording;
+
+    const MULTIROTOR_REC: &str = "VehicleName\tTimeStamp\tPOS_X\tPOS_Y\tPOS_Z\tQ_W\tQ_X\tQ_Y\tQ_Z\tImageFile
+SimpleFlight\t1661947200123\t0.5\t-1\t-2.25\t1\t0\t0\t0\timg_SimpleFlight_0_0_1661947200123.png;img_SimpleFlight_1_0_1661947200123.pfm
+SimpleFlight\t1661947200173\t0.6\t-1\t-2.5\t0.707\t0\t0\t0.707\t
+";
+
+    #[test]
+    fn test_parse_multirotor_recording() {
+        let entries = Recording::parse(MULTIROTOR_REC.as_bytes()).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let first = &entries[0];
+        assert_eq!(first.vehicle_name, "SimpleFlight");
+        assert_eq!(first.timestamp, 1661947200123);
+        assert_eq!(first.pose.position.z, -2.25);
+        assert_eq!(first.pose.orientation.w, 1.0);
+        assert_eq!(first.image_files.len(), 2);
+        assert!(first.extra.is_empty());
+
+        assert!(entries[1].image_files.is_empty());
+        assert_eq!(entries[1].pose.orientation.z, 0.707);
+    }
+
+    #[test]
+    fn test_parse_car_recording_extra_columns() {
+        let rec = "VehicleName\tTimeStamp\tPOS_X\tPOS_Y\tPOS_Z\tQ_W\tQ_X\tQ_Y\tQ_Z\tThrottle\tSteering\tImageFile
+PhysXCar\t1000\t1\t2\t3\t1\t0\t0\t0\t0.5\t-0.1\timg_PhysXCar_0_0_1000.png
+";
+        let entries = Recording::parse(rec.as_bytes()).unwrap();
+        assert_eq!(entries[0].extra["Throttle"], "0.5");
+        assert_eq!(entries[0].extra["Steering"], "-0.1");
+        assert_eq!(entries[0].image_files, vec!["img_PhysXCar_0_0_1000.png".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_missing_column() {
+        let rec = "VehicleName\tTimeStamp\tPOS_X\n";
+        assert!(Recording::parse(rec.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::Vector3;
+
+/// Direction a propeller spins in, seen from above
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpinDirection {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Where a motor sits on the frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MotorPosition {
+    /// angle of the arm from the nose, in degrees, positive to the right (clockwise seen from above)
+    pub angle_deg: f32,
+    pub direction: SpinDirection,
+}
+
+impl MotorPosition {
+    pub fn new(angle_deg: f32, direction: SpinDirection) -> Self {
+        Self { angle_deg, direction }
+    }
+
+    /// Position of the motor in the body frame (NED, x forward and y right) for the given arm length
+    pub fn position(&self, arm_length: f32) -> Vector3 {
+        let (sin, cos) = self.angle_deg.to_radians().sin_cos();
+        Vector3::new(cos * arm_length, sin * arm_length, 0.0)
+    }
+}
+
+/// Maps motor indices, as used by `RotorStates` and `PWM`, to their position on the frame
+///
+/// The predefined layouts use the ArduPilot motor order, which AirSim follows for its quad X and
+/// hexa X frames. Use `RotorLayout::new` for any other frame.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RotorLayout {
+    motors: Vec<MotorPosition>,
+}
+
+impl RotorLayout {
+    /// Layout with one entry per motor index
+    pub fn new(motors: Vec<MotorPosition>) -> Self {
+        Self { motors }
+    }
+
+    /// Quadcopter in X configuration: front right, rear left, front left, rear right
+    pub fn quad_x() -> Self {
+        use SpinDirection::*;
+        Self::from_table(&[
+            (45.0, CounterClockwise),
+            (-135.0, CounterClockwise),
+            (-45.0, Clockwise),
+            (135.0, Clockwise),
+        ])
+    }
+
+    /// Hexacopter in X configuration: right, left, front left, rear right, front right, rear left
+    pub fn hexa_x() -> Self {
+        use SpinDirection::*;
+        Self::from_table(&[
+            (90.0, Clockwise),
+            (-90.0, CounterClockwise),
+            (-30.0, Clockwise),
+            (150.0, CounterClockwise),
+            (30.0, CounterClockwise),
+            (-150.0, Clockwise),
+        ])
+    }
+
+    /// Octocopter in X configuration
+    pub fn octo_x() -> Self {
+        use SpinDirection::*;
+        Self::from_table(&[
+            (22.5, Clockwise),
+            (-157.5, Clockwise),
+            (67.5, CounterClockwise),
+            (157.5, CounterClockwise),
+            (-22.5, CounterClockwise),
+            (-112.5, CounterClockwise),
+            (-67.5, Clockwise),
+            (112.5, Clockwise),
+        ])
+    }
+
+    fn from_table(table: &[(f32, SpinDirection)]) -> Self {
+        Self::new(
+            table
+                .iter()
+                .map(|(angle_deg, direction)| MotorPosition::new(*angle_deg, *direction))
+                .collect(),
+        )
+    }
+
+    pub fn motor_count(&self) -> usize {
+        self.motors.len()
+    }
+
+    pub fn motors(&self) -> &[MotorPosition] {
+        &self.motors
+    }
+
+    /// Position of the motor with the given index, `None` if the frame has fewer motors
+    pub fn motor(&self, index: usize) -> Option<&MotorPosition> {
+        self.motors.get(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RotorLayout, SpinDirection};
+
+    #[test]
+    fn test_layouts_are_balanced() {
+        for layout in [RotorLayout::quad_x(), RotorLayout::hexa_x(), RotorLayout::octo_x()] {
+            let n = layout.motor_count();
+            let center = layout
+                .motors()
+                .iter()
+                .fold(crate::Vector3::default(), |sum, m| sum + m.position(1.0));
+            let clockwise = layout
+                .motors()
+                .iter()
+                .filter(|m| m.direction == SpinDirection::Clockwise)
+                .count();
+
+            assert!(center.norm() < 1e-5, "{n} motors are off center");
+            assert_eq!(clockwise * 2, n, "{n} motors have unbalanced yaw torque");
+        }
+    }
+
+    #[test]
+    fn test_quad_front_right() {
+        let front_right = RotorLayout::quad_x().motor(0).unwrap().position(1.0);
+        assert!(front_right.x > 0.0 && front_right.y > 0.0);
+        assert!(RotorLayout::quad_x().motor(4).is_none());
+    }
+}
@@ -1,60 +1,96 @@
-use msgpack_rpc::{message::Response, Value};
+use crate::codec::msgpack_map;
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RotorState {
     pub thrust: f32,
     pub torque_scaler: f32,
     pub speed: f32,
 }
 
-impl From<Value> for RotorState {
-    fn from(msgpack: Value) -> Self {
-        let mut states = vec![];
-        let payload: &Vec<(Value, Value)> = msgpack.as_map().unwrap();
-        for (_, v) in payload {
-            let s = v.as_f64().unwrap() as f32;
-            states.push(s);
-        }
-
-        RotorState {
-            thrust: states[0],
-            torque_scaler: states[1],
-            speed: states[2],
-        }
-    }
-}
+msgpack_map!(RotorState {
+    thrust => "thrust",
+    torque_scaler => "torque_scaler",
+    speed => "speed",
+});
 
-#[derive(Debug, Clone, Copy)]
+/// State of every rotor, indexed like the motors of a `RotorLayout`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RotorStates {
-    pub rotors: [RotorState; 4],
+    pub rotors: Vec<RotorState>,
     pub timestamp: u64,
 }
 
-impl From<Response> for RotorStates {
-    fn from(msgpack: Response) -> Self {
-        match msgpack.result {
-            Ok(res) => {
-                let payload: &Vec<(Value, Value)> = res.as_map().unwrap();
-
-                // rotors
-                let rotors: Vec<RotorState> = payload[0]
-                    .1
-                    .as_array()
-                    .unwrap()
-                    .iter()
-                    .cloned()
-                    .map(Into::into)
-                    .collect();
-
-                // timestamp
-                let timestamp: u64 = payload[1].1.to_owned().as_u64().unwrap();
-
-                RotorStates {
-                    rotors: [rotors[0], rotors[1], rotors[2], rotors[3]],
-                    timestamp,
-                }
-            }
-            Err(_) => panic!("Could not decode result from RotorState(s) msgpack"),
-        }
+impl RotorStates {
+    pub fn new(rotors: Vec<RotorState>, timestamp: u64) -> Self {
+        Self { rotors, timestamp }
+    }
+
+    pub fn rotor_count(&self) -> usize {
+        self.rotors.len()
+    }
+}
+
+msgpack_map!(RotorStates {
+    rotors => "rotors",
+    timestamp => "timestamp",
+});
+
+#[cfg(test)]
+mod tests {
+    use rmpv::Value;
+
+    use crate::codec::{encode_map, FromMsgpack};
+    use crate::{RotorLayout, RotorStates, SpinDirection};
+
+    fn states(speeds: &[f32]) -> Value {
+        let rotors = speeds
+            .iter()
+            .map(|speed| {
+                encode_map(vec![
+                    ("thrust", Value::F32(1.0)),
+                    ("torque_scaler", Value::F32(0.1)),
+                    ("speed", Value::F32(*speed)),
+                ])
+            })
+            .collect();
+        encode_map(vec![
+            ("rotors", Value::Array(rotors)),
+            ("timestamp", Value::Integer(10.into())),
+        ])
+    }
+
+    #[test]
+    fn test_rotor_states_any_rotor_count() {
+        let quad = RotorStates::from_msgpack(&states(&[500.0; 4])).unwrap();
+        assert_eq!(quad.rotors[3].speed, 500.0);
+
+        let hexa = RotorStates::from_msgpack(&states(&[500.0; 6])).unwrap();
+        assert_eq!(hexa.rotor_count(), 6);
+        assert_eq!(hexa.timestamp, 10);
+    }
+
+    #[test]
+    fn test_rotor_states_match_layout() {
+        // rotor i of the state is motor i of the layout, right and left spin against each other
+        let states = RotorStates::from_msgpack(&states(&[100.0, 200.0, 300.0, 400.0, 500.0, 600.0])).unwrap();
+        let layout = RotorLayout::hexa_x();
+        assert_eq!(states.rotor_count(), layout.motor_count());
+
+        let right = layout.motor(0).unwrap();
+        assert_eq!((right.angle_deg, right.direction), (90.0, SpinDirection::Clockwise));
+        assert_eq!(states.rotors[0].speed, 100.0);
+        let left = layout.motor(1).unwrap();
+        assert_eq!(
+            (left.angle_deg, left.direction),
+            (-90.0, SpinDirection::CounterClockwise)
+        );
+        assert_eq!(states.rotors[1].speed, 200.0);
+
+        // a hexa state has rotors a quad layout does not know
+        let quad = RotorLayout::quad_x();
+        assert_ne!(states.rotor_count(), quad.motor_count());
+        assert!(quad.motor(4).is_none());
     }
 }
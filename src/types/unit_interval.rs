@@ -0,0 +1,68 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::error::{NetworkError, NetworkResult};
+
+/// Value between 0.0 and 1.0 inclusive, like a throttle, a motor PWM or a weather intensity
+///
+/// ```
+/// # use std::convert::TryFrom;
+/// # use airsim_client::UnitInterval;
+/// assert_eq!(UnitInterval::try_from(0.6).unwrap().value(), 0.6);
+/// assert!(UnitInterval::try_from(1.5).is_err());
+/// assert_eq!(UnitInterval::saturating(1.5), UnitInterval::ONE);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "f32", into = "f32"))]
+pub struct UnitInterval(f32);
+
+impl UnitInterval {
+    pub const ZERO: Self = Self(0.0);
+    pub const ONE: Self = Self(1.0);
+
+    /// Validated value, see `TryFrom<f32>`
+    pub fn new(value: f32) -> NetworkResult<Self> {
+        Self::try_from(value)
+    }
+
+    /// Clamp `value` into the interval, NaN becomes 0.0
+    pub fn saturating(value: f32) -> Self {
+        if value.is_nan() {
+            Self::ZERO
+        } else {
+            Self(value.clamp(0.0, 1.0))
+        }
+    }
+
+    pub fn value(self) -> f32 {
+        self.0
+    }
+}
+
+impl TryFrom<f32> for UnitInterval {
+    type Error = NetworkError;
+
+    fn try_from(value: f32) -> Result<Self, Self::Error> {
+        if (0.0..=1.0).contains(&value) {
+            // normalizes -0.0, which AirSim may not accept
+            Ok(Self(value.abs()))
+        } else {
+            Err(NetworkError::ValidationError {
+                message: format!("{value} outside of valid range 0.0 to 1.0"),
+            })
+        }
+    }
+}
+
+impl From<UnitInterval> for f32 {
+    fn from(value: UnitInterval) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for UnitInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
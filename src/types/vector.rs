@@ -1,6 +1,9 @@
-use msgpack_rpc::{Utf8String, Value};
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
-#[derive(Debug, Clone, Copy)]
+use crate::codec::msgpack_map;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector3 {
     pub x: f32,
     pub y: f32,
@@ -11,19 +14,133 @@ impl Vector3 {
     pub fn new(x: f32, y: f32, z: f32) -> Self {
         Vector3 { x, y, z }
     }
+}
+
+msgpack_map!(Vector3 {
+    x => "x_val",
+    y => "y_val",
+    z => "z_val",
+});
+
+impl Vector3 {
+    pub fn zeros() -> Self {
+        Vector3::new(0.0, 0.0, 0.0)
+    }
+
+    pub fn dot(&self, other: &Vector3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(&self, other: &Vector3) -> Vector3 {
+        Vector3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    /// Euclidean length of the vector
+    pub fn norm(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Unit vector in the same direction, or the zero vector if the length is zero
+    pub fn normalize(&self) -> Vector3 {
+        let norm = self.norm();
+        if norm > 0.0 {
+            *self / norm
+        } else {
+            *self
+        }
+    }
+}
+
+impl Add for Vector3 {
+    type Output = Vector3;
+
+    fn add(self, rhs: Vector3) -> Vector3 {
+        Vector3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Sub for Vector3 {
+    type Output = Vector3;
+
+    fn sub(self, rhs: Vector3) -> Vector3 {
+        Vector3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl Neg for Vector3 {
+    type Output = Vector3;
+
+    fn neg(self) -> Vector3 {
+        Vector3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl Mul<f32> for Vector3 {
+    type Output = Vector3;
+
+    fn mul(self, rhs: f32) -> Vector3 {
+        Vector3::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl Mul<Vector3> for f32 {
+    type Output = Vector3;
+
+    fn mul(self, rhs: Vector3) -> Vector3 {
+        rhs * self
+    }
+}
+
+impl Div<f32> for Vector3 {
+    type Output = Vector3;
+
+    fn div(self, rhs: f32) -> Vector3 {
+        Vector3::new(self.x / rhs, self.y / rhs, self.z / rhs)
+    }
+}
+
+impl AddAssign for Vector3 {
+    fn add_assign(&mut self, rhs: Vector3) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Vector3 {
+    fn sub_assign(&mut self, rhs: Vector3) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign<f32> for Vector3 {
+    fn mul_assign(&mut self, rhs: f32) {
+        *self = *self * rhs;
+    }
+}
 
-    pub(crate) fn as_msgpack(&self) -> Value {
-        let x_val: Utf8String = "x_val".into();
-        let y_val: Utf8String = "y_val".into();
-        let z_val: Utf8String = "z_val".into();
+#[cfg(test)]
+mod tests {
+    use crate::Vector3;
 
-        let val = Value::Map(vec![
-            (Value::String(x_val), Value::F32(self.x)),
-            (Value::String(y_val), Value::F32(self.y)),
-            (Value::String(z_val), Value::F32(self.z)),
-        ]);
+    #[test]
+    fn test_vector_arithmetic() {
+        let a = Vector3::new(1.0, 2.0, 3.0);
+        let b = Vector3::new(4.0, 5.0, 6.0);
 
-        let msg: Vec<(msgpack_rpc::Value, msgpack_rpc::Value)> = val.as_map().map(|x| x.to_owned()).unwrap();
-        Value::Map(msg)
+        assert_eq!(a + b, Vector3::new(5.0, 7.0, 9.0));
+        assert_eq!(b - a, Vector3::new(3.0, 3.0, 3.0));
+        assert_eq!(-a, Vector3::new(-1.0, -2.0, -3.0));
+        assert_eq!(a * 2.0, 2.0 * a);
+        assert_eq!(b / 2.0, Vector3::new(2.0, 2.5, 3.0));
+        assert_eq!(a.dot(&b), 32.0);
+        assert_eq!(
+            Vector3::new(1.0, 0.0, 0.0).cross(&Vector3::new(0.0, 1.0, 0.0)),
+            Vector3::new(0.0, 0.0, 1.0)
+        );
+        assert_eq!(Vector3::new(3.0, 4.0, 0.0).norm(), 5.0);
+        assert_eq!(Vector3::zeros().normalize(), Vector3::zeros());
     }
 }
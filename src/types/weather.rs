@@ -1,6 +1,7 @@
-use msgpack_rpc::Value;
+use crate::codec::msgpack_enum;
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WeatherParameter {
     Rain,
     Roadwetness,
@@ -13,20 +14,14 @@ pub enum WeatherParameter {
     Enabled,
 }
 
-impl WeatherParameter {
-    pub(crate) fn _as_msgpack(&self) -> Value {
-        let val = match self {
-            WeatherParameter::Rain => 0_i64,
-            WeatherParameter::Roadwetness => 1_i64,
-            WeatherParameter::Snow => 2_i64,
-            WeatherParameter::RoadSnow => 3_i64,
-            WeatherParameter::MapleLeaf => 4_i64,
-            WeatherParameter::RoadLeaf => 5_i64,
-            WeatherParameter::Dust => 6_i64,
-            WeatherParameter::Fog => 7_i64,
-            WeatherParameter::Enabled => 8_i64,
-        };
-
-        Value::Integer(val.into())
-    }
-}
+msgpack_enum!(WeatherParameter {
+    Rain = 0,
+    Roadwetness = 1,
+    Snow = 2,
+    RoadSnow = 3,
+    MapleLeaf = 4,
+    RoadLeaf = 5,
+    Dust = 6,
+    Fog = 7,
+    Enabled = 8,
+});
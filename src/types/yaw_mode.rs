@@ -1,6 +1,7 @@
-use msgpack_rpc::{Utf8String, Value};
+use crate::codec::msgpack_map;
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct YawMode {
     is_rate: bool,
     yaw_or_rate: f32,
@@ -11,15 +12,17 @@ impl YawMode {
         Self { is_rate, yaw_or_rate }
     }
 
-    pub fn as_msgpack(&self) -> Value {
-        let is_rate_str: Utf8String = "is_rate".into();
-        let yaw_or_rate_str: Utf8String = "yaw_or_rate".into();
+    /// True if `yaw_or_rate` is a rate in degrees per second, false if it is an angle in degrees
+    pub fn is_rate(&self) -> bool {
+        self.is_rate
+    }
 
-        let val = Value::Map(vec![
-            (Value::String(is_rate_str), Value::Boolean(self.is_rate)),
-            (Value::String(yaw_or_rate_str), Value::F32(self.yaw_or_rate)),
-        ]);
-        let msg: Vec<(msgpack_rpc::Value, msgpack_rpc::Value)> = val.as_map().map(|x| x.to_owned()).unwrap();
-        Value::Map(msg)
+    pub fn yaw_or_rate(&self) -> f32 {
+        self.yaw_or_rate
     }
 }
+
+msgpack_map!(YawMode {
+    is_rate => "is_rate",
+    yaw_or_rate => "yaw_or_rate",
+});